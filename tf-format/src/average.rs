@@ -0,0 +1,129 @@
+use crate::{Rotation, Transform};
+use anyhow::{bail, ensure, Result};
+use nalgebra as na;
+
+/// The mean of rotations along with the dispersion.
+#[derive(Debug, Clone)]
+pub struct RotationMean {
+    pub mean: Rotation,
+    /// The root-mean-square angle in radians between the samples and
+    /// the mean.
+    pub angular_std: f64,
+}
+
+/// The mean of transforms along with the dispersion.
+#[derive(Debug, Clone)]
+pub struct TransformMean {
+    pub mean: Transform,
+    /// The root-mean-square angle in radians between the sample
+    /// rotations and the mean rotation.
+    pub angular_std: f64,
+    /// The root-mean-square distance between the sample translations
+    /// and the mean translation.
+    pub translation_std: f64,
+}
+
+impl Rotation {
+    /// Compute the mean rotation. See [Rotation::weighted_mean].
+    pub fn mean(rots: &[Rotation]) -> Result<RotationMean> {
+        Self::weighted_mean(rots, &vec![1.0; rots.len()])
+    }
+
+    /// Compute the weighted mean rotation, which minimizes the
+    /// weighted sum of squared chordal distances between quaternions.
+    /// It's the eigenvector of the largest eigenvalue of `sum(w * q *
+    /// q^T)`. The mean is in the format of the first rotation.
+    pub fn weighted_mean(rots: &[Rotation], weights: &[f64]) -> Result<RotationMean> {
+        let weights = normalize_weights(rots.len(), weights)?;
+        let quats: Vec<na::UnitQuaternion<f64>> =
+            rots.iter().map(|rot| rot.clone().into()).collect();
+
+        let mean = quaternion_mean(&quats, &weights);
+        let angular_std = weights
+            .iter()
+            .zip(&quats)
+            .map(|(w, quat)| w * mean.angle_to(quat).powi(2))
+            .sum::<f64>()
+            .sqrt();
+
+        Ok(RotationMean {
+            mean: rots[0].try_convert_like(&mean)?,
+            angular_std,
+        })
+    }
+}
+
+impl Transform {
+    /// Compute the mean transform. See [Transform::weighted_mean].
+    pub fn mean(tfs: &[Transform]) -> Result<TransformMean> {
+        Self::weighted_mean(tfs, &vec![1.0; tfs.len()])
+    }
+
+    /// Compute the weighted mean transform. The rotation is averaged
+    /// as in [Rotation::weighted_mean] and the translation is the
+    /// weighted arithmetic mean. The mean is in the rotation format and
+    /// length units of the first transform.
+    pub fn weighted_mean(tfs: &[Transform], weights: &[f64]) -> Result<TransformMean> {
+        let rots: Vec<_> = tfs.iter().map(|tf| tf.r.clone()).collect();
+        let RotationMean {
+            mean: rot,
+            angular_std,
+        } = Rotation::weighted_mean(&rots, weights)?;
+
+        let weights = normalize_weights(tfs.len(), weights)?;
+        let trans: Vec<na::Vector3<f64>> = tfs.iter().map(|tf| tf.t.to_na_vector3()).collect();
+
+        let mean_trans: na::Vector3<f64> = weights.iter().zip(&trans).map(|(w, t)| t * *w).sum();
+        let translation_std = weights
+            .iter()
+            .zip(&trans)
+            .map(|(w, t)| w * (t - mean_trans).norm_squared())
+            .sum::<f64>()
+            .sqrt();
+
+        Ok(TransformMean {
+            mean: Transform {
+                r: rot,
+                t: tfs[0].t.try_convert_like(&mean_trans)?,
+                cov: None,
+            },
+            angular_std,
+            translation_std,
+        })
+    }
+}
+
+/// Check the weights and scale them to sum up to one.
+fn normalize_weights(len: usize, weights: &[f64]) -> Result<Vec<f64>> {
+    if len == 0 {
+        bail!("unable to average an empty list");
+    }
+    ensure!(
+        weights.len() == len,
+        "expect {len} weights, but found {}",
+        weights.len()
+    );
+    if let Some(weight) = weights.iter().find(|w| !w.is_finite() || **w < 0.0) {
+        bail!("weights must be finite and non-negative, but found {weight}");
+    }
+
+    let sum: f64 = weights.iter().sum();
+    ensure!(sum > 0.0, "the sum of weights must be positive");
+    Ok(weights.iter().map(|w| w / sum).collect())
+}
+
+fn quaternion_mean(quats: &[na::UnitQuaternion<f64>], weights: &[f64]) -> na::UnitQuaternion<f64> {
+    let mat: na::Matrix4<f64> = quats
+        .iter()
+        .zip(weights)
+        .map(|(quat, w)| {
+            let coords = quat.coords;
+            coords * coords.transpose() * *w
+        })
+        .sum();
+
+    let eigen = mat.symmetric_eigen();
+    let (idx, _) = eigen.eigenvalues.argmax();
+    let coords = eigen.eigenvectors.column(idx).into_owned();
+    na::UnitQuaternion::from_quaternion(na::Quaternion::from(coords))
+}
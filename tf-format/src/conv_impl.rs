@@ -1,10 +1,15 @@
 use crate::{
-    rotation::{Quaternion, Rodrigues, Rotation},
-    Angle, AxisAngle, Euler, EulerAxis, EulerAxisOrder, RotationMatrix, Transform, Translation,
+    rotation::{
+        Gibbs, Mrp, Quaternion, QuaternionConvention, QuaternionOrder, Rodrigues, Rotation,
+    },
+    AffineMatrix, Angle, AxisAngle, DualQuaternion, Euler, EulerAxis, EulerAxisOrder,
+    EulerConvention, HomogeneousMatrix, RotationMatrix, Transform, TransformMatrix, Translation,
+    Twist,
 };
 use nalgebra as na;
 use noisy_float::types::{r64, R64};
 use num::{NumCast, ToPrimitive, Zero};
+use std::f64::consts::{FRAC_PI_2, PI};
 
 impl From<RotationMatrix> for Rotation {
     fn from(v: RotationMatrix) -> Self {
@@ -36,6 +41,18 @@ impl From<Rodrigues> for Rotation {
     }
 }
 
+impl From<Mrp> for Rotation {
+    fn from(v: Mrp) -> Self {
+        Self::Mrp(v)
+    }
+}
+
+impl From<Gibbs> for Rotation {
+    fn from(v: Gibbs) -> Self {
+        Self::Gibbs(v)
+    }
+}
+
 impl From<Rotation> for Euler {
     fn from(rot: Rotation) -> Self {
         let quat: na::UnitQuaternion<f64> = rot.into();
@@ -71,6 +88,22 @@ impl From<Rotation> for Rodrigues {
     }
 }
 
+impl From<Rotation> for Mrp {
+    fn from(rot: Rotation) -> Self {
+        let quat: na::UnitQuaternion<f64> = rot.into();
+        quat.into()
+    }
+}
+
+impl TryFrom<Rotation> for Gibbs {
+    type Error = anyhow::Error;
+
+    fn try_from(rot: Rotation) -> Result<Self, Self::Error> {
+        let quat: na::UnitQuaternion<f64> = rot.into();
+        quat.try_into()
+    }
+}
+
 impl<T> From<Transform> for na::Isometry3<T>
 where
     T: na::RealField + NumCast,
@@ -83,16 +116,20 @@ where
         }
 
         let Transform {
-            r: rot,
-            t: Translation([x, y, z]),
+            r: rot, t: trans, ..
         } = tf;
 
+        let [x, y, z] = trans.as_meters_values();
         let trans = na::Translation3::new(cast!(x), cast!(y), cast!(z));
         let rot: na::UnitQuaternion<T> = rot.into();
         Self::from_parts(trans, rot)
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Transform::try_from_na_isometry3] to get an error instead.
 impl<T> From<na::Isometry3<T>> for Transform
 where
     T: na::RealField + NumCast,
@@ -114,7 +151,224 @@ where
 
         Self {
             r: rotation.into(),
-            t: Translation([cast!(x), cast!(y), cast!(z)]),
+            t: Translation::from_meters([cast!(x), cast!(y), cast!(z)]),
+            cov: None,
+        }
+    }
+}
+
+impl From<HomogeneousMatrix> for TransformMatrix {
+    fn from(v: HomogeneousMatrix) -> Self {
+        Self::HomogeneousMatrix(v)
+    }
+}
+
+impl From<AffineMatrix> for TransformMatrix {
+    fn from(v: AffineMatrix) -> Self {
+        Self::AffineMatrix(v)
+    }
+}
+
+impl From<AffineMatrix> for Transform {
+    fn from(mat: AffineMatrix) -> Self {
+        let [[r11, r12, r13, t1], [r21, r22, r23, t2], [r31, r32, r33, t3]] = mat.matrix;
+
+        Self {
+            r: RotationMatrix {
+                matrix: [[r11, r12, r13], [r21, r22, r23], [r31, r32, r33]],
+            }
+            .into(),
+            t: Translation::from_meters([t1, t2, t3]),
+            cov: None,
+        }
+    }
+}
+
+impl TryFrom<HomogeneousMatrix> for Transform {
+    type Error = anyhow::Error;
+
+    fn try_from(mat: HomogeneousMatrix) -> Result<Self, Self::Error> {
+        Ok(mat.to_affine_matrix()?.into())
+    }
+}
+
+impl TryFrom<TransformMatrix> for Transform {
+    type Error = anyhow::Error;
+
+    fn try_from(mat: TransformMatrix) -> Result<Self, Self::Error> {
+        match mat {
+            TransformMatrix::HomogeneousMatrix(mat) => mat.try_into(),
+            TransformMatrix::AffineMatrix(mat) => Ok(mat.into()),
+        }
+    }
+}
+
+impl From<Transform> for AffineMatrix {
+    fn from(tf: Transform) -> Self {
+        let iso: na::Isometry3<f64> = tf.into();
+        iso.into()
+    }
+}
+
+impl From<Transform> for HomogeneousMatrix {
+    fn from(tf: Transform) -> Self {
+        let iso: na::Isometry3<f64> = tf.into();
+        iso.into()
+    }
+}
+
+impl<T> From<AffineMatrix> for na::Isometry3<T>
+where
+    T: na::RealField + NumCast,
+{
+    fn from(mat: AffineMatrix) -> Self {
+        Transform::from(mat).into()
+    }
+}
+
+impl<T> TryFrom<HomogeneousMatrix> for na::Isometry3<T>
+where
+    T: na::RealField + NumCast,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(mat: HomogeneousMatrix) -> Result<Self, Self::Error> {
+        Ok(mat.to_affine_matrix()?.into())
+    }
+}
+
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [AffineMatrix::try_from_na_isometry3] to get an error instead.
+impl<T> From<na::Isometry3<T>> for AffineMatrix
+where
+    T: na::RealField + NumCast,
+{
+    fn from(iso: na::Isometry3<T>) -> Self {
+        macro_rules! cast {
+            ($val:expr) => {
+                <R64 as NumCast>::from($val.clone()).unwrap()
+            };
+        }
+
+        let mat = iso.to_homogeneous();
+        let matrix = [0, 1, 2].map(|row| [0, 1, 2, 3].map(|col| cast!(mat[(row, col)])));
+        Self { matrix }
+    }
+}
+
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [HomogeneousMatrix::try_from_na_isometry3] to get an error instead.
+impl<T> From<na::Isometry3<T>> for HomogeneousMatrix
+where
+    T: na::RealField + NumCast,
+{
+    fn from(iso: na::Isometry3<T>) -> Self {
+        AffineMatrix::from(iso).to_homogeneous_matrix()
+    }
+}
+
+impl<T> TryFrom<DualQuaternion> for na::UnitDualQuaternion<T>
+where
+    T: na::RealField + NumCast,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(dual_quat: DualQuaternion) -> Result<Self, Self::Error> {
+        let DualQuaternion { real, dual } = dual_quat;
+        let [ri, rj, rk, rw] = real.map(|val| val.raw());
+        let [di, dj, dk, dw] = dual.map(|val| val.raw());
+
+        let real = na::Quaternion::new(rw, ri, rj, rk);
+        let dual = na::Quaternion::new(dw, di, dj, dk);
+        if real.norm() < 1e-8 {
+            anyhow::bail!("the real part of the dual quaternion must not be zero");
+        }
+
+        let dual_quat = na::DualQuaternion::from_real_and_dual(real, dual);
+        Ok(na::UnitDualQuaternion::new_normalize(dual_quat).cast())
+    }
+}
+
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [DualQuaternion::try_from_na_unit_dual_quaternion] to get an error instead.
+impl<T> From<na::UnitDualQuaternion<T>> for DualQuaternion
+where
+    T: na::RealField + NumCast,
+{
+    fn from(dual_quat: na::UnitDualQuaternion<T>) -> Self {
+        macro_rules! cast {
+            ($val:expr) => {
+                <R64 as NumCast>::from($val.clone()).unwrap()
+            };
+        }
+
+        let na::DualQuaternion { real, dual } = dual_quat.into_inner();
+        Self {
+            real: [cast!(real.i), cast!(real.j), cast!(real.k), cast!(real.w)],
+            dual: [cast!(dual.i), cast!(dual.j), cast!(dual.k), cast!(dual.w)],
+        }
+    }
+}
+
+impl<T> TryFrom<DualQuaternion> for na::Isometry3<T>
+where
+    T: na::RealField + NumCast,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(dual_quat: DualQuaternion) -> Result<Self, Self::Error> {
+        let dual_quat: na::UnitDualQuaternion<T> = dual_quat.try_into()?;
+        Ok(dual_quat.to_isometry())
+    }
+}
+
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [DualQuaternion::try_from_na_isometry3] to get an error instead.
+impl<T> From<na::Isometry3<T>> for DualQuaternion
+where
+    T: na::RealField + NumCast,
+{
+    fn from(iso: na::Isometry3<T>) -> Self {
+        na::UnitDualQuaternion::from_isometry(&iso).into()
+    }
+}
+
+impl TryFrom<DualQuaternion> for Transform {
+    type Error = anyhow::Error;
+
+    fn try_from(dual_quat: DualQuaternion) -> Result<Self, Self::Error> {
+        let iso: na::Isometry3<f64> = dual_quat.try_into()?;
+        Ok(iso.into())
+    }
+}
+
+impl From<Transform> for DualQuaternion {
+    fn from(tf: Transform) -> Self {
+        let iso: na::Isometry3<f64> = tf.into();
+        iso.into()
+    }
+}
+
+impl From<Twist> for Transform {
+    fn from(twist: Twist) -> Self {
+        Transform::exp(twist.to_vector())
+    }
+}
+
+impl From<Transform> for Twist {
+    fn from(tf: Transform) -> Self {
+        let [x, y, z, rx, ry, rz] = tf.log().map(r64);
+        Twist {
+            linear: [x, y, z],
+            angular: [rx, ry, rz],
         }
     }
 }
@@ -130,10 +384,16 @@ where
             Rotation::AxisAngle(rot) => rot.into(),
             Rotation::RotationMatrix(rot) => rot.into(),
             Rotation::Rodrigues(rot) => rot.into(),
+            Rotation::Mrp(rot) => rot.into(),
+            Rotation::Gibbs(rot) => rot.into(),
         }
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for Rotation
 where
     T: na::RealField + NumCast,
@@ -151,6 +411,7 @@ where
         let Euler {
             order: EulerAxisOrder(order),
             angles,
+            convention,
         } = euler;
         assert_eq!(order.len(), angles.len());
 
@@ -172,11 +433,18 @@ where
                     }
                 }
             })
-            .reduce(|lhs, rhs| rhs * lhs)
+            .reduce(|lhs, rhs| match convention {
+                EulerConvention::Extrinsic => rhs * lhs,
+                EulerConvention::Intrinsic => lhs * rhs,
+            })
             .unwrap_or_else(|| Self::identity())
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for Euler
 where
     T: na::RealField + ToPrimitive,
@@ -193,16 +461,99 @@ where
         Self {
             order: EulerAxisOrder(vec![EulerAxis::Roll, EulerAxis::Pitch, EulerAxis::Yaw]),
             angles: vec![cast!(r), cast!(p), cast!(y)],
+            convention: EulerConvention::Extrinsic,
         }
     }
 }
 
+/// Decompose a quaternion into three Euler angles in radians.
+///
+/// It implements the method from Bernardes and Viollet, "Quaternion
+/// to Euler angles conversion: A direct, general and computationally
+/// efficient method", 2022. The consecutive axes must be distinct.
+pub(crate) fn quaternion_to_euler_angles(
+    quat: &na::UnitQuaternion<f64>,
+    axes: [EulerAxis; 3],
+    convention: EulerConvention,
+) -> [f64; 3] {
+    const EPSILON: f64 = 1e-7;
+
+    // The method is formulated for extrinsic rotations. The intrinsic
+    // rotations are obtained by reversing both the axis sequence and
+    // the resulting angles.
+    let extrinsic = convention == EulerConvention::Extrinsic;
+    let [first, second, third] = axes;
+    let [i, j, k] = if extrinsic {
+        [first.index(), second.index(), third.index()]
+    } else {
+        [third.index(), second.index(), first.index()]
+    };
+
+    // For proper Euler sequences, use the remaining axis as the third
+    // axis to determine the permutation.
+    let is_proper = i == k;
+    let k = if is_proper { 3 - i - j } else { k };
+
+    // The sign is +1 for even permutations and -1 for odd ones.
+    let sign = ((i as i32 - j as i32) * (j as i32 - k as i32) * (k as i32 - i as i32) / 2) as f64;
+
+    let q = quat.as_ref().coords;
+    let (a, b, c, d) = if is_proper {
+        (q[3], q[i], q[j], q[k] * sign)
+    } else {
+        (
+            q[3] - q[j],
+            q[i] + q[k] * sign,
+            q[j] + q[3],
+            q[k] * sign - q[i],
+        )
+    };
+
+    let mut angles = [0.0; 3];
+    angles[1] = 2.0 * c.hypot(d).atan2(a.hypot(b));
+
+    let half_sum = b.atan2(a);
+    let half_diff = d.atan2(c);
+
+    if angles[1].abs() <= EPSILON {
+        // Gimbal lock. Set the third angle to zero.
+        angles[0] = 2.0 * half_sum;
+    } else if (angles[1] - PI).abs() <= EPSILON {
+        // Gimbal lock. Set the third angle to zero.
+        angles[0] = -2.0 * half_diff;
+    } else {
+        angles[0] = half_sum - half_diff;
+        angles[2] = half_sum + half_diff;
+    }
+
+    if !is_proper {
+        angles[2] *= sign;
+        angles[1] -= FRAC_PI_2;
+    }
+
+    if !extrinsic {
+        angles.swap(0, 2);
+    }
+
+    angles.map(|angle| {
+        if angle < -PI {
+            angle + 2.0 * PI
+        } else if angle > PI {
+            angle - 2.0 * PI
+        } else {
+            angle
+        }
+    })
+}
+
 impl<T> From<Quaternion> for na::UnitQuaternion<T>
 where
     T: na::RealField + NumCast,
 {
     fn from(quat: Quaternion) -> Self {
-        let Quaternion { ijkw: [i, j, k, w] } = quat;
+        let Quaternion {
+            ijkw: [i, j, k, w], ..
+        } = quat.to_convention(QuaternionConvention::Hamilton);
 
         macro_rules! cast {
             ($ang:expr) => {
@@ -214,6 +565,10 @@ where
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for Quaternion
 where
     T: na::RealField + ToPrimitive + Clone,
@@ -231,6 +586,8 @@ where
 
         Self {
             ijkw: [cast!(i), cast!(j), cast!(k), cast!(w)],
+            order: QuaternionOrder::Xyzw,
+            convention: QuaternionConvention::Hamilton,
         }
     }
 }
@@ -258,6 +615,10 @@ where
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for AxisAngle
 where
     T: na::RealField + NumCast + Clone,
@@ -302,6 +663,10 @@ where
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for RotationMatrix
 where
     T: na::RealField + NumCast + Clone,
@@ -353,6 +718,10 @@ where
     }
 }
 
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
 impl<T> From<na::UnitQuaternion<T>> for Rodrigues
 where
     T: na::RealField + NumCast,
@@ -375,3 +744,85 @@ where
         }
     }
 }
+
+impl<T> From<Mrp> for na::UnitQuaternion<T>
+where
+    T: na::RealField + NumCast,
+{
+    fn from(mrp: Mrp) -> Self {
+        // Both the parameters and its shadow map to the same rotation
+        // by the formula.
+        let norm_sq = mrp.norm_squared();
+        let [p1, p2, p3] = mrp.params.map(|val| val.raw());
+        let w = (1.0 - norm_sq) / (1.0 + norm_sq);
+        let scale = 2.0 / (1.0 + norm_sq);
+        let quat = na::Quaternion::new(w, p1 * scale, p2 * scale, p3 * scale);
+        na::UnitQuaternion::from_quaternion(quat).cast()
+    }
+}
+
+/// # Panics
+///
+/// It panics if any component is NaN or infinite. Use
+/// [Rotation::try_from_na_unit_quaternion] to get an error instead.
+impl<T> From<na::UnitQuaternion<T>> for Mrp
+where
+    T: na::RealField + NumCast,
+{
+    fn from(quat: na::UnitQuaternion<T>) -> Self {
+        macro_rules! cast {
+            ($val:expr) => {
+                <f64 as NumCast>::from($val.clone()).unwrap()
+            };
+        }
+
+        let [i, j, k, w] = [cast!(quat.i), cast!(quat.j), cast!(quat.k), cast!(quat.w)];
+
+        // Pick the quaternion sign with w >= 0 so that the parameters
+        // fall in the unit ball.
+        let sign = if w < 0.0 { -1.0 } else { 1.0 };
+        let denom = 1.0 + w * sign;
+        Mrp {
+            params: [i, j, k].map(|val| r64(val * sign / denom)),
+        }
+    }
+}
+
+impl<T> From<Gibbs> for na::UnitQuaternion<T>
+where
+    T: na::RealField + NumCast,
+{
+    fn from(gibbs: Gibbs) -> Self {
+        let [g1, g2, g3] = gibbs.params.map(|val| val.raw());
+        let quat = na::Quaternion::new(1.0, g1, g2, g3);
+        na::UnitQuaternion::from_quaternion(quat).cast()
+    }
+}
+
+impl<T> TryFrom<na::UnitQuaternion<T>> for Gibbs
+where
+    T: na::RealField + NumCast,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(quat: na::UnitQuaternion<T>) -> Result<Self, Self::Error> {
+        macro_rules! cast {
+            ($val:expr) => {
+                <f64 as NumCast>::from($val.clone()).unwrap()
+            };
+        }
+
+        let [i, j, k, w] = [cast!(quat.i), cast!(quat.j), cast!(quat.k), cast!(quat.w)];
+
+        if w.abs() < 1e-8 {
+            anyhow::bail!(
+                "the Gibbs vector is undefined for rotations of 180 degrees, \
+                 consider the MRP format instead"
+            );
+        }
+
+        Ok(Gibbs {
+            params: [i, j, k].map(|val| r64(val / w)),
+        })
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{
+    covariance::{self, Covariance},
+    CoordTransform, MaybeTransform, Rotation, Transform, TransformSet,
+};
+use anyhow::{bail, ensure, Result};
+use nalgebra as na;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// The direction of a coordinate axis relative to the body.
+///
+/// East and north are identified with forward and left as in ROS
+/// REP 103, such that ENU coincides with FLU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisDirection {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl AxisDirection {
+    /// Parse the direction letter. Besides "f", "b", "l", "r", "u"
+    /// and "d", the compass letters "e", "w", "n" and "s" are
+    /// accepted.
+    pub fn from_char(ch: char) -> Result<Self> {
+        let dir = match ch.to_ascii_lowercase() {
+            'f' | 'e' => Self::Forward,
+            'b' | 'w' => Self::Backward,
+            'l' | 'n' => Self::Left,
+            'r' | 's' => Self::Right,
+            'u' => Self::Up,
+            'd' => Self::Down,
+            _ => bail!("invalid axis direction '{ch}'"),
+        };
+        Ok(dir)
+    }
+
+    pub fn to_char(&self) -> char {
+        match self {
+            Self::Forward => 'f',
+            Self::Backward => 'b',
+            Self::Left => 'l',
+            Self::Right => 'r',
+            Self::Up => 'u',
+            Self::Down => 'd',
+        }
+    }
+
+    /// Returns the unit vector of the direction in FLU coordinates.
+    pub fn to_flu_vector(&self) -> na::Vector3<f64> {
+        match self {
+            Self::Forward => na::Vector3::x(),
+            Self::Backward => -na::Vector3::x(),
+            Self::Left => na::Vector3::y(),
+            Self::Right => -na::Vector3::y(),
+            Self::Up => na::Vector3::z(),
+            Self::Down => -na::Vector3::z(),
+        }
+    }
+}
+
+/// The axis convention of a coordinate frame, given by the
+/// directions of the x, y and z axes.
+///
+/// It's parsed from direction letters like "flu" and "rdf", or the
+/// names "enu", "ned" and "unity".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AxisConvention(pub [AxisDirection; 3]);
+
+impl AxisConvention {
+    /// Forward-left-up, the ROS body frame.
+    pub const FLU: Self = Self([
+        AxisDirection::Forward,
+        AxisDirection::Left,
+        AxisDirection::Up,
+    ]);
+
+    /// Forward-right-down, the aerospace body frame.
+    pub const FRD: Self = Self([
+        AxisDirection::Forward,
+        AxisDirection::Right,
+        AxisDirection::Down,
+    ]);
+
+    /// East-north-up, the ROS world frame.
+    pub const ENU: Self = Self::FLU;
+
+    /// North-east-down, the aerospace world frame.
+    pub const NED: Self = Self([
+        AxisDirection::Left,
+        AxisDirection::Forward,
+        AxisDirection::Down,
+    ]);
+
+    /// Right-down-forward, the camera optical frame.
+    pub const RDF: Self = Self([
+        AxisDirection::Right,
+        AxisDirection::Down,
+        AxisDirection::Forward,
+    ]);
+
+    /// Right-up-forward, the left-handed frame of Unity.
+    pub const RUF: Self = Self([
+        AxisDirection::Right,
+        AxisDirection::Up,
+        AxisDirection::Forward,
+    ]);
+
+    pub fn is_right_handed(&self) -> bool {
+        self.to_flu_matrix().determinant() > 0.0
+    }
+
+    /// Returns the matrix whose columns are the axes in FLU
+    /// coordinates. It maps coordinates in this convention to FLU.
+    pub fn to_flu_matrix(&self) -> na::Matrix3<f64> {
+        let [x, y, z] = self.0.map(|dir| dir.to_flu_vector());
+        na::Matrix3::from_columns(&[x, y, z])
+    }
+
+    /// Returns the matrix that maps coordinates in this convention to
+    /// coordinates in the `other` convention. It's a reflection if
+    /// the handedness differs.
+    pub fn change_of_basis(&self, other: &Self) -> na::Matrix3<f64> {
+        other.to_flu_matrix().transpose() * self.to_flu_matrix()
+    }
+}
+
+impl FromStr for AxisConvention {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_ascii_lowercase().as_str() {
+            "enu" => return Ok(Self::ENU),
+            "ned" => return Ok(Self::NED),
+            "unity" => return Ok(Self::RUF),
+            _ => {}
+        }
+
+        let dirs: Vec<_> = text
+            .chars()
+            .map(AxisDirection::from_char)
+            .collect::<Result<_>>()?;
+        let Ok(dirs) = <[AxisDirection; 3]>::try_from(dirs) else {
+            bail!("expect three axis directions, but found '{text}'");
+        };
+
+        let conv = Self(dirs);
+        ensure!(
+            conv.to_flu_matrix().determinant() != 0.0,
+            "the axes of '{text}' are not orthogonal"
+        );
+        Ok(conv)
+    }
+}
+
+impl Display for AxisConvention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for dir in &self.0 {
+            write!(f, "{}", dir.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+impl Rotation {
+    /// Re-express the rotation in another axis convention. The
+    /// rotation format is kept.
+    pub fn change_convention(&self, from: &AxisConvention, to: &AxisConvention) -> Self {
+        let basis = from.change_of_basis(to);
+        let quat = rotation_from_matrix(basis * rotation_matrix(self) * basis.transpose());
+        self.convert_like(&quat)
+    }
+}
+
+impl Transform {
+    /// Re-express the transform with both the source and the
+    /// destination frames changed from the axis convention `from` to
+    /// `to`. The formats and units are kept.
+    pub fn change_convention(&self, from: &AxisConvention, to: &AxisConvention) -> Self {
+        let basis = from.change_of_basis(to);
+        self.change_basis(&basis, &basis)
+            .expect("conjugation must preserve the handedness")
+    }
+
+    /// Re-express the transform with the source frame changed from
+    /// the axis convention `from` to `to`. A transform `T` becomes
+    /// `S * T`, where `S` is the change of basis. It fails if the
+    /// conventions differ in handedness.
+    pub fn change_src_convention(
+        &self,
+        from: &AxisConvention,
+        to: &AxisConvention,
+    ) -> Result<Self> {
+        self.change_basis(&from.change_of_basis(to), &na::Matrix3::identity())
+    }
+
+    /// Re-express the transform with the destination frame changed
+    /// from the axis convention `from` to `to`. A transform `T`
+    /// becomes `T * S⁻¹`, where `S` is the change of basis. It fails
+    /// if the conventions differ in handedness.
+    pub fn change_dst_convention(
+        &self,
+        from: &AxisConvention,
+        to: &AxisConvention,
+    ) -> Result<Self> {
+        self.change_basis(&na::Matrix3::identity(), &from.change_of_basis(to))
+    }
+
+    fn change_basis(&self, src: &na::Matrix3<f64>, dst: &na::Matrix3<f64>) -> Result<Self> {
+        let mat = src * rotation_matrix(&self.r) * dst.transpose();
+        ensure!(
+            mat.determinant() > 0.0,
+            "unable to change the handedness of only one frame of the transform"
+        );
+
+        let trans = src * self.t.to_na_vector3();
+        let tf = Self {
+            r: self.r.convert_like(&rotation_from_matrix(mat)),
+            t: self.t.convert_like(&trans),
+            cov: None,
+        };
+        let cov = self
+            .cov
+            .as_ref()
+            .map(|cov| change_cov_basis(cov, &self.clone().into(), &tf.clone().into(), dst));
+        Ok(Self { cov, ..tf })
+    }
+}
+
+impl MaybeTransform {
+    /// Re-express the transform with both the source and the
+    /// destination frames changed from the axis convention `from` to
+    /// `to`. The formats and units are kept.
+    pub fn change_convention(&self, from: &AxisConvention, to: &AxisConvention) -> Self {
+        let basis = from.change_of_basis(to);
+        let tf = Self {
+            r: self.r.change_convention(from, to),
+            t: self
+                .t
+                .as_ref()
+                .map(|t| t.convert_like(&(basis * t.to_na_vector3()))),
+            cov: None,
+        };
+        let cov = self.cov.as_ref().map(|cov| {
+            let orig = self.to_na_isometry3();
+            change_cov_basis(cov, &orig, &tf.to_na_isometry3(), &basis)
+        });
+        Self { cov, ..tf }
+    }
+}
+
+impl TransformSet {
+    /// Re-express all coordinate frames in the set from the axis
+    /// convention `from` to `to`.
+    pub fn change_convention(&self, from: &AxisConvention, to: &AxisConvention) -> Result<Self> {
+        self.map_transforms(|trans| {
+            let CoordTransform { src, dst, tf } = trans;
+            let tf = tf.change_convention(from, to);
+            Ok(CoordTransform { src, dst, tf })
+        })
+    }
+
+    /// Re-express the coordinate frame `coord` from the axis
+    /// convention `from` to `to`. The transforms from and to the
+    /// frame are updated. It fails if the conventions differ in
+    /// handedness.
+    pub fn change_coord_convention(
+        &self,
+        coord: &str,
+        from: &AxisConvention,
+        to: &AxisConvention,
+    ) -> Result<Self> {
+        ensure!(
+            self.contains_coord(coord),
+            "the coordinate '{coord}' is not found"
+        );
+
+        self.map_transforms(|trans| {
+            let CoordTransform { src, dst, mut tf } = trans;
+            if src == coord {
+                tf = tf.change_src_convention(from, to)?;
+            }
+            if dst == coord {
+                tf = tf.change_dst_convention(from, to)?;
+            }
+            Ok(CoordTransform { src, dst, tf })
+        })
+    }
+
+    fn map_transforms<F>(&self, f: F) -> Result<Self>
+    where
+        F: FnMut(CoordTransform) -> Result<CoordTransform>,
+    {
+        let list: Vec<_> = self.edges.iter().cloned().map(f).collect::<Result<_>>()?;
+        let mut set = Self::try_from_iter(list)?;
+        for coord in self.isolated_coords() {
+            set.insert_coord(coord);
+        }
+        Ok(set)
+    }
+}
+
+/// Change the basis of the destination frame, where the right
+/// perturbation lies, of the covariance. The rotation vector is an
+/// axial vector that flips under reflections.
+fn change_cov_basis(
+    cov: &Covariance,
+    orig: &na::Isometry3<f64>,
+    changed: &na::Isometry3<f64>,
+    dst: &na::Matrix3<f64>,
+) -> Covariance {
+    let mut basis = na::Matrix6::zeros();
+    basis.fixed_view_mut::<3, 3>(0, 0).copy_from(dst);
+    basis
+        .fixed_view_mut::<3, 3>(3, 3)
+        .copy_from(&(dst * dst.determinant()));
+
+    let mat = covariance::congruence(&basis, &cov.to_right_matrix(orig));
+    cov.convert_like(&mat, changed)
+}
+
+fn rotation_matrix(rot: &Rotation) -> na::Matrix3<f64> {
+    let quat: na::UnitQuaternion<f64> = rot.clone().into();
+    quat.to_rotation_matrix().into_inner()
+}
+
+fn rotation_from_matrix(mat: na::Matrix3<f64>) -> na::UnitQuaternion<f64> {
+    na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(mat))
+}
@@ -0,0 +1,204 @@
+use crate::{expr, serde_options};
+use anyhow::{ensure, Result};
+use nalgebra as na;
+use noisy_float::types::{r64, R64};
+use serde::{Deserialize, Serialize};
+
+/// The 6x6 covariance of a transform in the tangent space of SE(3).
+///
+/// The perturbed transform is `T * exp(ξ)` for the right
+/// perturbation, or `exp(ξ) * T` for the left perturbation, where
+/// `exp` is [Transform::exp](crate::Transform::exp). The tangent
+/// vector `ξ` is ordered as `[rho, omega]` or `[omega, rho]`. The
+/// values are in meters and radians regardless of the units of the
+/// transform.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedCovariance", into = "SerializedCovariance")]
+pub struct Covariance {
+    pub perturbation: Perturbation,
+    pub order: TangentOrder,
+    pub matrix: [[R64; 6]; 6],
+}
+
+/// The side on which the tangent-space perturbation is applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Perturbation {
+    /// `T * exp(ξ)`, the perturbation in the destination frame.
+    #[default]
+    Right,
+    /// `exp(ξ) * T`, the perturbation in the source frame.
+    Left,
+}
+
+/// The order of the translational and rotational components of the
+/// tangent vector.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TangentOrder {
+    /// `[rho, omega]`, as in ROS `PoseWithCovariance`.
+    #[default]
+    TranslationFirst,
+    /// `[omega, rho]`, as in GTSAM.
+    RotationFirst,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedCovariance {
+    #[serde(default)]
+    perturbation: Perturbation,
+    #[serde(default)]
+    order: TangentOrder,
+    #[serde(
+        serialize_with = "serde_options::serialize",
+        deserialize_with = "expr::deserialize"
+    )]
+    matrix: [[R64; 6]; 6],
+}
+
+impl TryFrom<SerializedCovariance> for Covariance {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedCovariance) -> Result<Self, Self::Error> {
+        let SerializedCovariance {
+            perturbation,
+            order,
+            matrix,
+        } = from;
+        let cov = Self {
+            perturbation,
+            order,
+            matrix,
+        };
+        cov.validate()?;
+        Ok(cov)
+    }
+}
+
+impl From<Covariance> for SerializedCovariance {
+    fn from(from: Covariance) -> Self {
+        let Covariance {
+            perturbation,
+            order,
+            matrix,
+        } = from;
+        Self {
+            perturbation,
+            order,
+            matrix,
+        }
+    }
+}
+
+impl Covariance {
+    /// The tolerance of the asymmetry relative to the largest entry.
+    pub const TOLERANCE: f64 = 1e-9;
+
+    /// Create a covariance in the right perturbation with `[rho,
+    /// omega]` order.
+    pub fn from_na_matrix6(mat: &na::Matrix6<f64>) -> Self {
+        Self::from_na_matrix6_with(mat, Perturbation::Right, TangentOrder::TranslationFirst)
+    }
+
+    pub fn from_na_matrix6_with(
+        mat: &na::Matrix6<f64>,
+        perturbation: Perturbation,
+        order: TangentOrder,
+    ) -> Self {
+        Self {
+            perturbation,
+            order,
+            matrix: std::array::from_fn(|row| std::array::from_fn(|col| r64(mat[(row, col)]))),
+        }
+    }
+
+    /// Returns the matrix in the perturbation and order of `self`.
+    pub fn to_na_matrix6(&self) -> na::Matrix6<f64> {
+        na::Matrix6::from_fn(|row, col| self.matrix[row][col].raw())
+    }
+
+    /// Check that the matrix is symmetric with a non-negative
+    /// diagonal.
+    pub fn validate(&self) -> Result<()> {
+        let mat = self.to_na_matrix6();
+        let tolerance = Self::TOLERANCE * mat.amax().max(1.0);
+        ensure!(
+            (mat - mat.transpose()).amax() <= tolerance,
+            "the covariance matrix is not symmetric"
+        );
+        ensure!(
+            mat.diagonal().iter().all(|&val| val >= 0.0),
+            "the covariance matrix has negative variances"
+        );
+        Ok(())
+    }
+
+    /// Returns the matrix in the right perturbation with `[rho,
+    /// omega]` order about the transform `iso`.
+    pub(crate) fn to_right_matrix(&self, iso: &na::Isometry3<f64>) -> na::Matrix6<f64> {
+        let mat = match self.order {
+            TangentOrder::TranslationFirst => self.to_na_matrix6(),
+            TangentOrder::RotationFirst => swap_blocks(&self.to_na_matrix6()),
+        };
+        match self.perturbation {
+            Perturbation::Right => mat,
+            Perturbation::Left => congruence(&adjoint(&iso.inverse()), &mat),
+        }
+    }
+
+    /// Convert the matrix in the right perturbation with `[rho,
+    /// omega]` order about the transform `iso` to the perturbation
+    /// and order of `self`.
+    pub(crate) fn convert_like(&self, mat: &na::Matrix6<f64>, iso: &na::Isometry3<f64>) -> Self {
+        let mat = match self.perturbation {
+            Perturbation::Right => *mat,
+            Perturbation::Left => congruence(&adjoint(iso), mat),
+        };
+        let mat = match self.order {
+            TangentOrder::TranslationFirst => mat,
+            TangentOrder::RotationFirst => swap_blocks(&mat),
+        };
+        Self::from_na_matrix6_with(&mat, self.perturbation, self.order)
+    }
+}
+
+/// The adjoint of SE(3) acting on `[rho, omega]`, such that `T *
+/// exp(ξ) = exp(Ad(T) ξ) * T`.
+pub(crate) fn adjoint(iso: &na::Isometry3<f64>) -> na::Matrix6<f64> {
+    let rot = iso.rotation.to_rotation_matrix().into_inner();
+    let skew = iso.translation.vector.cross_matrix();
+
+    let mut adj = na::Matrix6::zeros();
+    adj.fixed_view_mut::<3, 3>(0, 0).copy_from(&rot);
+    adj.fixed_view_mut::<3, 3>(0, 3).copy_from(&(skew * rot));
+    adj.fixed_view_mut::<3, 3>(3, 3).copy_from(&rot);
+    adj
+}
+
+/// Compute `A * M * Aᵀ`.
+pub(crate) fn congruence(lhs: &na::Matrix6<f64>, mat: &na::Matrix6<f64>) -> na::Matrix6<f64> {
+    lhs * mat * lhs.transpose()
+}
+
+/// The covariance of the inverse transform in the right perturbation.
+pub(crate) fn inverse_cov(iso: &na::Isometry3<f64>, cov: &na::Matrix6<f64>) -> na::Matrix6<f64> {
+    congruence(&adjoint(iso), cov)
+}
+
+/// The covariance of the product `lhs * rhs` of independent
+/// transforms in the right perturbation.
+pub(crate) fn product_cov(
+    rhs: &na::Isometry3<f64>,
+    lhs_cov: Option<&na::Matrix6<f64>>,
+    rhs_cov: Option<&na::Matrix6<f64>>,
+) -> na::Matrix6<f64> {
+    let lhs_cov = match lhs_cov {
+        Some(cov) => congruence(&adjoint(&rhs.inverse()), cov),
+        None => na::Matrix6::zeros(),
+    };
+    lhs_cov + rhs_cov.copied().unwrap_or_else(na::Matrix6::zeros)
+}
+
+fn swap_blocks(mat: &na::Matrix6<f64>) -> na::Matrix6<f64> {
+    na::Matrix6::from_fn(|row, col| mat[((row + 3) % 6, (col + 3) % 6)])
+}
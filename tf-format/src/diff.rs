@@ -0,0 +1,55 @@
+use crate::{Rotation, Transform};
+use nalgebra as na;
+
+/// The difference between two rotations.
+#[derive(Debug, Clone)]
+pub struct RotationDiff {
+    /// The geodesic angle in radians in `[0, pi]`.
+    pub angle: f64,
+    /// The unit rotation axis of the relative rotation expressed in
+    /// the frame of the first rotation. It's `None` if the rotations
+    /// are identical.
+    pub axis: Option<[f64; 3]>,
+}
+
+/// The difference between two transforms.
+#[derive(Debug, Clone)]
+pub struct TransformDiff {
+    pub rotation: RotationDiff,
+    /// The per-axis translation difference, which is the second
+    /// translation subtracted by the first one.
+    pub translation: [f64; 3],
+    /// The Euclidean norm of the translation difference.
+    pub translation_norm: f64,
+}
+
+impl Rotation {
+    /// Compute the difference to the other rotation regardless of
+    /// their formats. It's the relative rotation `self^-1 * other`
+    /// in the axis-angle form.
+    pub fn diff(&self, other: &Self) -> RotationDiff {
+        let lhs: na::UnitQuaternion<f64> = self.clone().into();
+        let rhs: na::UnitQuaternion<f64> = other.clone().into();
+        let delta = lhs.inverse() * rhs;
+
+        RotationDiff {
+            angle: delta.angle(),
+            axis: delta.axis().map(|axis| axis.into_inner().into()),
+        }
+    }
+}
+
+impl Transform {
+    /// Compute the difference to the other transform regardless of
+    /// their rotation formats. The rotation difference is computed as
+    /// in [Rotation::diff].
+    pub fn diff(&self, other: &Self) -> TransformDiff {
+        let delta = other.t.to_na_vector3() - self.t.to_na_vector3();
+
+        TransformDiff {
+            rotation: self.r.diff(&other.r),
+            translation: delta.into(),
+            translation_norm: delta.norm(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use nalgebra as na;
+
+/// The error returned when a matrix is not a valid rotation matrix.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum RotationMatrixError {
+    #[error(
+        "the rotation matrix has negative determinant {determinant}, which is a reflection \
+         rather than a rotation"
+    )]
+    Reflection { determinant: f64 },
+    #[error(
+        "the rotation matrix is not orthonormal, the orthogonality error |R^T R - I| is \
+         {error}, which exceeds the tolerance {tolerance}"
+    )]
+    NotOrthonormal { error: f64, tolerance: f64 },
+}
+
+/// The error returned when a value converted from `nalgebra` types
+/// is NaN or infinite.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("the {component} component has invalid value {value}")]
+pub struct NonFiniteError {
+    /// The name of the offending component, such as "translation.x"
+    /// or "rotation.w".
+    pub component: &'static str,
+    pub value: f64,
+}
+
+impl NonFiniteError {
+    /// Check that all named values are finite.
+    pub(crate) fn check<T, const N: usize>(values: [(&'static str, &T); N]) -> Result<(), Self>
+    where
+        T: num::NumCast + Clone,
+    {
+        for (component, value) in values {
+            let value = <f64 as num::NumCast>::from(value.clone()).unwrap_or(f64::NAN);
+            if !value.is_finite() {
+                return Err(Self { component, value });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the quaternion components are finite.
+    pub(crate) fn check_unit_quaternion<T>(quat: &na::UnitQuaternion<T>) -> Result<(), Self>
+    where
+        T: na::RealField + num::NumCast,
+    {
+        let coords = &quat.coords;
+        Self::check([
+            ("rotation.i", &coords[0]),
+            ("rotation.j", &coords[1]),
+            ("rotation.k", &coords[2]),
+            ("rotation.w", &coords[3]),
+        ])
+    }
+
+    /// Check that the translation and rotation components are finite.
+    pub(crate) fn check_isometry<T>(iso: &na::Isometry3<T>) -> Result<(), Self>
+    where
+        T: na::RealField + num::NumCast,
+    {
+        let vec = &iso.translation.vector;
+        Self::check([
+            ("translation.x", &vec[0]),
+            ("translation.y", &vec[1]),
+            ("translation.z", &vec[2]),
+        ])?;
+        Self::check_unit_quaternion(&iso.rotation)
+    }
+}
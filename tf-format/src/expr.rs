@@ -0,0 +1,661 @@
+//! A small evaluator of constant arithmetic expressions, such as
+//! `pi/2`, `-90d + 1.5d` or `sqrt(2)/2`.
+//!
+//! It supports `+`, `-`, `*`, `/`, `^`, parentheses, the constants
+//! `pi`, `tau` and `e`, and the functions `sqrt`, `abs`, `sin`,
+//! `cos`, `tan`, `asin`, `acos`, `atan` and `atan2`. Angle
+//! expressions additionally accept numbers with angle unit suffixes.
+//!
+//! The expressions are evaluated on input and the values are written
+//! on output. [restore_expressions] writes them back on request.
+
+use crate::unit::{Angle, AngleUnit, Length};
+use anyhow::{bail, ensure, Result};
+use noisy_float::types::R64;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{
+    f64::consts::{E, PI, TAU},
+    iter::Peekable,
+    str::CharIndices,
+};
+
+/// Evaluate a dimensionless expression.
+pub(crate) fn eval(text: &str) -> Result<f64> {
+    let tokens = tokenize(text)?;
+    ensure!(
+        tokens
+            .iter()
+            .all(|token| !matches!(token, Token::Angle(..))),
+        "unexpected angle unit in expression '{text}'"
+    );
+
+    let value = Parser::new(text, tokens, None).parse()?;
+    Ok(value.value)
+}
+
+/// Evaluate an angle expression. The result is in the unit of the
+/// angle literals if they share the same unit, or otherwise in
+/// radians.
+///
+/// The expression must be an angle. Besides the literals with unit
+/// suffixes, the constants `pi` and `tau` and the inverse
+/// trigonometric functions are angles in radians. Expressions of plain
+/// numbers, such as `2` or `2 * 1`, are rejected.
+pub(crate) fn eval_angle(text: &str) -> Result<(AngleUnit, f64)> {
+    let tokens = tokenize(text)?;
+
+    let mut units = tokens.iter().filter_map(|token| match token {
+        Token::Angle(_, unit) => Some(*unit),
+        _ => None,
+    });
+    let unit = match units.next() {
+        Some(first) if units.all(|unit| unit == first) => first,
+        _ => AngleUnit::Radian,
+    };
+
+    let value = Parser::new(text, tokens, Some(unit)).parse()?;
+    ensure!(
+        value.is_angle,
+        "expect an angle, but expression '{text}' is a plain number without an angle unit"
+    );
+    Ok((unit, value.value))
+}
+
+/// A number deserialized from either a number or a string
+/// expression.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Scalar(pub R64);
+
+impl<'de> Deserialize<'de> for Scalar {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Serialized {
+            Number(R64),
+            Text(String),
+        }
+
+        let value = match Serialized::deserialize(deserializer)? {
+            Serialized::Number(value) => value,
+            Serialized::Text(text) => {
+                let value = eval(&text).map_err(|err| D::Error::custom(format!("{err}")))?;
+                R64::try_new(value).ok_or_else(|| {
+                    D::Error::custom(format!("invalid value '{value}' from '{text}'"))
+                })?
+            }
+        };
+        Ok(Self(value))
+    }
+}
+
+/// Types whose numbers can be written in expressions. It's used with
+/// `#[serde(deserialize_with = "crate::expr::deserialize")]`.
+pub(crate) trait FromScalars {
+    type Repr;
+
+    fn from_scalars(repr: Self::Repr) -> Self;
+}
+
+impl<const N: usize> FromScalars for [R64; N] {
+    type Repr = [Scalar; N];
+
+    fn from_scalars(repr: Self::Repr) -> Self {
+        repr.map(|Scalar(val)| val)
+    }
+}
+
+impl<const N: usize, const M: usize> FromScalars for [[R64; N]; M] {
+    type Repr = [[Scalar; N]; M];
+
+    fn from_scalars(repr: Self::Repr) -> Self {
+        repr.map(|row| row.map(|Scalar(val)| val))
+    }
+}
+
+impl<T> FromScalars for Option<T>
+where
+    T: FromScalars,
+{
+    type Repr = Option<T::Repr>;
+
+    fn from_scalars(repr: Self::Repr) -> Self {
+        repr.map(T::from_scalars)
+    }
+}
+
+pub(crate) fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromScalars,
+    T::Repr: Deserialize<'de>,
+{
+    let repr = T::Repr::deserialize(deserializer)?;
+    Ok(T::from_scalars(repr))
+}
+
+/// Put the expressions of the `source` document back into the
+/// serialized `output`.
+///
+/// The expressions are evaluated on input, so the output only has
+/// values. A string in `source` replaces the value at the same place in
+/// `output` if it evaluates to that value, which means values changed
+/// by conversions or rounding are kept as they are.
+pub fn restore_expressions(output: &mut serde_json::Value, source: &serde_json::Value) {
+    use serde_json::Value as Json;
+
+    match (output, source) {
+        (Json::Object(output), Json::Object(source)) => {
+            for (key, output) in output {
+                if let Some(source) = source.get(key) {
+                    restore_expressions(output, source);
+                }
+            }
+        }
+        (Json::Array(output), Json::Array(source)) => {
+            for (output, source) in output.iter_mut().zip(source) {
+                restore_expressions(output, source);
+            }
+        }
+        (output, Json::String(text)) if same_value(text, output) => {
+            *output = Json::String(text.clone());
+        }
+        _ => {}
+    }
+}
+
+/// Check if the expression evaluates to the serialized value.
+fn same_value(text: &str, value: &serde_json::Value) -> bool {
+    fn approx_eq(lhs: f64, rhs: f64) -> bool {
+        (lhs - rhs).abs() <= 1e-9 * lhs.abs().max(rhs.abs()).max(1.0)
+    }
+
+    match value {
+        serde_json::Value::Number(number) => {
+            let Some(number) = number.as_f64() else {
+                return false;
+            };
+            let value = eval(text).or_else(|_| eval_angle(text).map(|(_, value)| value));
+            value.is_ok_and(|value| approx_eq(value, number))
+        }
+        serde_json::Value::String(output) => {
+            if let (Ok(lhs), Ok(rhs)) = (text.parse::<Angle>(), output.parse::<Angle>()) {
+                return lhs.unit == rhs.unit && approx_eq(lhs.value.raw(), rhs.value.raw());
+            }
+            if let (Ok(lhs), Ok(rhs)) = (text.parse::<Length>(), output.parse::<Length>()) {
+                return lhs.unit == rhs.unit && approx_eq(lhs.value.raw(), rhs.value.raw());
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Angle(f64, AngleUnit),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut chars = text.char_indices().peekable();
+    let mut tokens = vec![];
+
+    while let Some(&(start, ch)) = chars.peek() {
+        let token = match ch {
+            _ if ch.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '0'..='9' | '.' => {
+                let end = scan_number(&mut chars, text.len());
+                let value: f64 = text[start..end].parse()?;
+
+                let suffix_start = end;
+                while chars
+                    .next_if(|&(_, ch)| ch.is_alphabetic() || ch == '°')
+                    .is_some()
+                {}
+                let suffix_end = chars.peek().map(|&(idx, _)| idx).unwrap_or(text.len());
+
+                match &text[suffix_start..suffix_end] {
+                    "" => Token::Number(value),
+                    suffix => {
+                        let Some(unit) = angle_unit_from_suffix(suffix) else {
+                            bail!("unknown unit '{suffix}' in expression '{text}'");
+                        };
+                        Token::Angle(value, unit)
+                    }
+                }
+            }
+            _ if ch.is_alphabetic() || ch == '_' => {
+                while chars
+                    .next_if(|&(_, ch)| ch.is_alphanumeric() || ch == '_')
+                    .is_some()
+                {}
+                let end = chars.peek().map(|&(idx, _)| idx).unwrap_or(text.len());
+                Token::Ident(text[start..end].to_string())
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                chars.next();
+                Token::Op(ch)
+            }
+            '(' => {
+                chars.next();
+                Token::LParen
+            }
+            ')' => {
+                chars.next();
+                Token::RParen
+            }
+            ',' => {
+                chars.next();
+                Token::Comma
+            }
+            _ => bail!("unexpected character '{ch}' in expression '{text}'"),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Consume a number literal and return its end position.
+fn scan_number(chars: &mut Peekable<CharIndices>, len: usize) -> usize {
+    while chars
+        .next_if(|&(_, ch)| ch.is_ascii_digit() || ch == '.')
+        .is_some()
+    {}
+
+    // The exponent is consumed only if digits follow.
+    let mut lookahead = chars.clone();
+    if lookahead
+        .next_if(|&(_, ch)| ch == 'e' || ch == 'E')
+        .is_some()
+    {
+        lookahead.next_if(|&(_, ch)| ch == '+' || ch == '-');
+        if lookahead.peek().is_some_and(|(_, ch)| ch.is_ascii_digit()) {
+            while lookahead.next_if(|&(_, ch)| ch.is_ascii_digit()).is_some() {}
+            *chars = lookahead;
+        }
+    }
+
+    chars.peek().map(|&(idx, _)| idx).unwrap_or(len)
+}
+
+fn angle_unit_from_suffix(suffix: &str) -> Option<AngleUnit> {
+    let unit = match suffix {
+        "r" | "rad" => AngleUnit::Radian,
+        "d" | "deg" | "°" => AngleUnit::Degree,
+        "grad" | "gon" => AngleUnit::Gradian,
+        "turn" | "rev" => AngleUnit::Turn,
+        "arcmin" => AngleUnit::ArcMinute,
+        "arcsec" => AngleUnit::ArcSecond,
+        _ => return None,
+    };
+    Some(unit)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Value {
+    value: f64,
+    is_angle: bool,
+}
+
+impl Value {
+    fn number(value: f64) -> Self {
+        Self {
+            value,
+            is_angle: false,
+        }
+    }
+}
+
+/// The maximum nesting depth of parentheses, signs and powers, which
+/// keeps the recursion from overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+/// A recursive descent parser of the grammar
+///
+/// ```text
+/// expr   = term (("+" | "-") term)*
+/// term   = unary (("*" | "/") unary)*
+/// unary  = ("+" | "-") unary | power
+/// power  = atom ("^" unary)?
+/// atom   = number | angle | ident | ident "(" args ")" | "(" expr ")"
+/// ```
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    /// The unit of angle values, or `None` if angles are not allowed.
+    unit: Option<AngleUnit>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, tokens: Vec<Token>, unit: Option<AngleUnit>) -> Self {
+        Self {
+            text,
+            tokens,
+            pos: 0,
+            depth: 0,
+            unit,
+        }
+    }
+
+    fn parse(mut self) -> Result<Value> {
+        let value = self.expr()?;
+        if let Some(token) = self.peek() {
+            bail!("unexpected {token:?} in expression '{}'", self.text);
+        }
+        Ok(value)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expect: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expect => Ok(()),
+            _ => bail!("expect {expect:?} in expression '{}'", self.text),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Value> {
+        let mut lhs = self.term()?;
+
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = *op;
+            self.next();
+            let rhs = self.term()?;
+            ensure!(
+                lhs.is_angle == rhs.is_angle,
+                "unable to add an angle and a number in expression '{}'",
+                self.text
+            );
+            lhs.value = if op == '+' {
+                lhs.value + rhs.value
+            } else {
+                lhs.value - rhs.value
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Value> {
+        let mut lhs = self.unary()?;
+
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = *op;
+            self.next();
+            let rhs = self.unary()?;
+
+            lhs = match (op, lhs.is_angle, rhs.is_angle) {
+                ('*', true, true) => {
+                    bail!(
+                        "unable to multiply two angles in expression '{}'",
+                        self.text
+                    )
+                }
+                ('*', _, _) => Value {
+                    value: lhs.value * rhs.value,
+                    is_angle: lhs.is_angle || rhs.is_angle,
+                },
+                (_, false, true) => {
+                    bail!("unable to divide by an angle in expression '{}'", self.text)
+                }
+                (_, _, _) => Value {
+                    value: lhs.value / rhs.value,
+                    is_angle: lhs.is_angle && !rhs.is_angle,
+                },
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    /// Every level of nesting goes through `unary`, so the depth is
+    /// counted here.
+    fn unary(&mut self) -> Result<Value> {
+        ensure!(
+            self.depth < MAX_DEPTH,
+            "expression '{}' is nested too deeply",
+            self.text
+        );
+        self.depth += 1;
+        let value = self.unary_inner();
+        self.depth -= 1;
+        value
+    }
+
+    fn unary_inner(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.next();
+                let value = self.unary()?;
+                Ok(Value {
+                    value: -value.value,
+                    ..value
+                })
+            }
+            Some(Token::Op('+')) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Value> {
+        let base = self.atom()?;
+        if self.peek() != Some(&Token::Op('^')) {
+            return Ok(base);
+        }
+        self.next();
+
+        let exp = self.unary()?;
+        ensure!(
+            !base.is_angle && !exp.is_angle,
+            "unable to raise angles to a power in expression '{}'",
+            self.text
+        );
+        Ok(Value::number(base.value.powf(exp.value)))
+    }
+
+    fn atom(&mut self) -> Result<Value> {
+        let value = match self.next() {
+            Some(Token::Number(value)) => Value::number(value),
+            Some(Token::Angle(value, unit)) => {
+                let Some(target) = self.unit else {
+                    bail!("unexpected angle unit in expression '{}'", self.text);
+                };
+                let value = if unit == target {
+                    value
+                } else {
+                    value * unit.to_radians_factor() / target.to_radians_factor()
+                };
+                Value {
+                    value,
+                    is_angle: true,
+                }
+            }
+            Some(Token::LParen) => {
+                let value = self.expr()?;
+                self.expect(Token::RParen)?;
+                value
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                self.next();
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                self.call(&name, &args)?
+            }
+            // The constants are angles in angle expressions.
+            Some(Token::Ident(name)) => match name.as_str() {
+                "pi" => self.angle(PI),
+                "tau" => self.angle(TAU),
+                "e" => Value::number(E),
+                _ => bail!("unknown constant '{name}' in expression '{}'", self.text),
+            },
+            _ => bail!("unexpected end of expression '{}'", self.text),
+        };
+        Ok(value)
+    }
+
+    fn call(&self, name: &str, args: &[Value]) -> Result<Value> {
+        let value = match (name, args) {
+            ("abs", [arg]) => Value {
+                value: arg.value.abs(),
+                ..*arg
+            },
+            ("sqrt", [arg]) => Value::number(self.number(arg)?.sqrt()),
+            ("sin", [arg]) => Value::number(self.radians(arg).sin()),
+            ("cos", [arg]) => Value::number(self.radians(arg).cos()),
+            ("tan", [arg]) => Value::number(self.radians(arg).tan()),
+            ("asin", [arg]) => self.angle(self.number(arg)?.asin()),
+            ("acos", [arg]) => self.angle(self.number(arg)?.acos()),
+            ("atan", [arg]) => self.angle(self.number(arg)?.atan()),
+            ("atan2", [y, x]) => self.angle(self.number(y)?.atan2(self.number(x)?)),
+            ("abs" | "sqrt" | "sin" | "cos" | "tan" | "asin" | "acos" | "atan", _) => {
+                bail!("'{name}' expects 1 argument in expression '{}'", self.text)
+            }
+            ("atan2", _) => bail!("'atan2' expects 2 arguments in expression '{}'", self.text),
+            _ => bail!("unknown function '{name}' in expression '{}'", self.text),
+        };
+        Ok(value)
+    }
+
+    fn number(&self, value: &Value) -> Result<f64> {
+        ensure!(
+            !value.is_angle,
+            "expect a number, but found an angle in expression '{}'",
+            self.text
+        );
+        Ok(value.value)
+    }
+
+    /// Convert the value to radians. Plain numbers are regarded as
+    /// radians.
+    fn radians(&self, value: &Value) -> f64 {
+        match (value.is_angle, self.unit) {
+            (true, Some(unit)) => value.value * unit.to_radians_factor(),
+            _ => value.value,
+        }
+    }
+
+    /// Make an angle from radians. It's a plain number if angles are
+    /// not allowed.
+    fn angle(&self, radians: f64) -> Value {
+        match self.unit {
+            Some(unit) => Value {
+                value: radians / unit.to_radians_factor(),
+                is_angle: true,
+            },
+            None => Value::number(radians),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, eval_angle};
+    use crate::unit::AngleUnit;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::{FRAC_PI_2, PI, SQRT_2, TAU};
+
+    #[test]
+    fn eval_number() {
+        let cases = [
+            ("1 + 2 * 3", 7.0),
+            ("(1 + 2) * 3", 9.0),
+            ("-2^2", -4.0),
+            ("2^3^2", 512.0),
+            ("sqrt(2)/2", SQRT_2 / 2.0),
+            ("1.5e-3 * 2", 3e-3),
+            ("tau / 4", FRAC_PI_2),
+            ("cos(pi)", -1.0),
+            ("atan2(1, 1)", PI / 4.0),
+        ];
+
+        for (text, expect) in cases {
+            assert_abs_diff_eq!(eval(text).unwrap(), expect, epsilon = 1e-12);
+        }
+
+        for text in [
+            "",
+            "1 +",
+            "(1",
+            "foo",
+            "bar(1)",
+            "sqrt(1, 2)",
+            "90d",
+            "1 $ 2",
+        ] {
+            assert!(eval(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn eval_angle_expr() {
+        let cases = [
+            ("pi/2", AngleUnit::Radian, FRAC_PI_2),
+            ("-90d + 1.5d", AngleUnit::Degree, -88.5),
+            ("90deg / 2", AngleUnit::Degree, 45.0),
+            ("2 * 100grad", AngleUnit::Gradian, 200.0),
+            ("90d + pi/2", AngleUnit::Degree, 180.0),
+            ("tau - 1r", AngleUnit::Radian, TAU - 1.0),
+            ("sin(pi/6) * 1d", AngleUnit::Degree, 0.5),
+            ("asin(1) - 45d", AngleUnit::Degree, 45.0),
+            ("sin(30d) * 1d", AngleUnit::Degree, 0.5),
+        ];
+
+        for (text, unit, expect) in cases {
+            let (actual_unit, value) = eval_angle(text).unwrap();
+            assert_eq!(actual_unit, unit, "{text}");
+            assert_abs_diff_eq!(value, expect, epsilon = 1e-12);
+        }
+
+        for text in [
+            "90d + 1", "90d * 1d", "1 / 90d", "90d / 1d", "90km", "2", "2 * 1", "sqrt(2)",
+            "pi * pi", "sin(pi)",
+        ] {
+            assert!(eval_angle(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn eval_nested() {
+        let nest = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nest(32)).unwrap(), 1.0);
+
+        // Deep nesting is an error rather than a stack overflow.
+        for text in [
+            nest(100_000),
+            "-".repeat(100_000) + "1",
+            "2^".repeat(100_000) + "1",
+        ] {
+            assert!(eval(&text).is_err());
+            assert!(eval_angle(&text).is_err());
+        }
+    }
+}
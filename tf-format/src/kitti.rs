@@ -0,0 +1,227 @@
+use crate::{CoordTransform, Rotation, RotationMatrix, Transform, TransformSet, Translation};
+use anyhow::{bail, ensure, Context, Result};
+use noisy_float::types::r64;
+use std::{fmt::Write, fs, path::Path, str::FromStr};
+
+/// The matrix layout of a KITTI calibration entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KittiMatrix {
+    /// A 3x4 `[R|t]` matrix in row-major order.
+    Affine,
+    /// A 3x3 rotation matrix in row-major order, such as `R0_rect`.
+    Rotation,
+}
+
+/// A KITTI calibration entry. The matrix maps points in the `from`
+/// frame to the `to` frame, so `Tr_velo_to_cam` has `from` "velo"
+/// and `to` "cam0".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KittiKey {
+    pub key: String,
+    pub from: String,
+    pub to: String,
+    pub matrix: KittiMatrix,
+}
+
+/// The entries of KITTI calibration files to read and write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittiOptions {
+    pub keys: Vec<KittiKey>,
+}
+
+impl Default for KittiOptions {
+    /// The entries of the KITTI object detection calibration.
+    fn default() -> Self {
+        let key = |key: &str, from: &str, to: &str, matrix| KittiKey {
+            key: key.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            matrix,
+        };
+        Self {
+            keys: vec![
+                key("R0_rect", "cam0", "cam0_rect", KittiMatrix::Rotation),
+                key("Tr_velo_to_cam", "velo", "cam0", KittiMatrix::Affine),
+                key("Tr_imu_to_velo", "imu", "velo", KittiMatrix::Affine),
+            ],
+        }
+    }
+}
+
+impl KittiOptions {
+    /// Set the frames of the entry. A new entry is added as a 3x4
+    /// matrix if the key is not present.
+    pub fn set_frames(&mut self, key: &str, from: &str, to: &str) {
+        match self.keys.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => {
+                entry.from = from.to_string();
+                entry.to = to.to_string();
+            }
+            None => self.keys.push(KittiKey {
+                key: key.to_string(),
+                from: from.to_string(),
+                to: to.to_string(),
+                matrix: KittiMatrix::Affine,
+            }),
+        }
+    }
+}
+
+impl FromStr for KittiKey {
+    type Err = anyhow::Error;
+
+    /// Parse the "KEY=FROM:TO" text of a 3x4 matrix entry.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parsed = text
+            .split_once('=')
+            .and_then(|(key, frames)| Some((key, frames.split_once(':')?)));
+        let Some((key, (from, to))) = parsed else {
+            bail!("expect KEY=FROM:TO, but found '{text}'");
+        };
+        Ok(Self {
+            key: key.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            matrix: KittiMatrix::Affine,
+        })
+    }
+}
+
+impl TransformSet {
+    /// Build a transform set from a KITTI calibration file, where each
+    /// line is a key followed by a row-major matrix. The matrix of an
+    /// entry becomes the transform from its `to` frame to its `from`
+    /// frame. Keys not in `options`, such as the `P0` projection
+    /// matrices, are ignored.
+    pub fn from_kitti_str(text: &str, options: &KittiOptions) -> Result<Self> {
+        let mut list = vec![];
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, values)) = line.split_once(':') else {
+                bail!("expect 'KEY: VALUES' at line {}", idx + 1);
+            };
+            let Some(entry) = options.keys.iter().find(|entry| entry.key == key.trim()) else {
+                continue;
+            };
+
+            let tf = parse_matrix(values, entry.matrix)
+                .with_context(|| format!("unable to parse '{key}' at line {}", idx + 1))?;
+            list.push(CoordTransform {
+                src: entry.to.clone(),
+                dst: entry.from.clone(),
+                tf,
+            });
+        }
+
+        Ok(Self::try_from_iter(list)?)
+    }
+
+    pub fn from_kitti_file<P>(path: P, options: &KittiOptions) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let text = fs::read_to_string(path)?;
+        Self::from_kitti_str(&text, options)
+    }
+
+    /// Write the entries of `options` whose frames are connected in
+    /// the set as a KITTI calibration file. The rotations are written
+    /// as orthonormal matrices, which may differ slightly from the
+    /// values that were read.
+    pub fn to_kitti_string(&self, options: &KittiOptions) -> Result<String> {
+        let mut text = String::new();
+
+        for entry in &options.keys {
+            let Some(iso) = self.get(&entry.to, &entry.from) else {
+                continue;
+            };
+            let rot = iso.rotation.to_rotation_matrix().into_inner();
+            let trans = iso.translation.vector;
+
+            let values: Vec<f64> = match entry.matrix {
+                KittiMatrix::Affine => (0..3)
+                    .flat_map(|row| [rot[(row, 0)], rot[(row, 1)], rot[(row, 2)], trans[row]])
+                    .collect(),
+                KittiMatrix::Rotation => {
+                    ensure!(
+                        trans.norm() <= 1e-9,
+                        "the transform of '{}' has a non-zero translation",
+                        entry.key
+                    );
+                    rot.transpose().iter().copied().collect()
+                }
+            };
+
+            write!(text, "{}:", entry.key)?;
+            for value in values {
+                write!(text, " {}", format_number(value))?;
+            }
+            writeln!(text)?;
+        }
+
+        ensure!(
+            !text.is_empty(),
+            "none of the calibration entries are found in the transform set"
+        );
+        Ok(text)
+    }
+}
+
+fn parse_matrix(text: &str, matrix: KittiMatrix) -> Result<Transform> {
+    let values: Vec<f64> = text
+        .split_whitespace()
+        .map(|token| {
+            let value: f64 = token
+                .parse()
+                .with_context(|| format!("invalid number '{token}'"))?;
+            ensure!(value.is_finite(), "invalid number '{token}'");
+            Ok(value)
+        })
+        .collect::<Result<_>>()?;
+
+    let (rot, trans) = match (matrix, values.len()) {
+        (KittiMatrix::Affine, 12) => (
+            [0, 1, 2].map(|row| [0, 1, 2].map(|col| r64(values[row * 4 + col]))),
+            [0, 1, 2].map(|row| r64(values[row * 4 + 3])),
+        ),
+        (KittiMatrix::Rotation, 9) => (
+            [0, 1, 2].map(|row| [0, 1, 2].map(|col| r64(values[row * 3 + col]))),
+            [r64(0.0); 3],
+        ),
+        (KittiMatrix::Affine, len) => bail!("expect 12 numbers, but found {len}"),
+        (KittiMatrix::Rotation, len) => bail!("expect 9 numbers, but found {len}"),
+    };
+
+    Ok(Transform {
+        r: Rotation::RotationMatrix(RotationMatrix { matrix: rot }),
+        t: Translation::from_meters(trans),
+        cov: None,
+    })
+}
+
+/// Format the number like "%.12e" in C, as in the KITTI files.
+fn format_number(value: f64) -> String {
+    // Avoid writing "-0".
+    let value = if value == 0.0 { 0.0 } else { value };
+    let text = format!("{value:.12e}");
+    let (mantissa, exponent) = text.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{mantissa}e{sign}{:02}", exponent.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_number;
+
+    #[test]
+    fn kitti_number_format() {
+        assert_eq!(format_number(7.533745e-3), "7.533745000000e-03");
+        assert_eq!(format_number(-0.0), "0.000000000000e+00");
+        assert_eq!(format_number(123.0), "1.230000000000e+02");
+    }
+}
@@ -1,15 +1,44 @@
+mod average;
 mod conv_impl;
+mod convention;
+mod covariance;
+mod diff;
+mod error;
+mod expr;
+mod kitti;
+mod lie;
+mod nuscenes;
+mod ops;
+mod ros_msg;
 mod rotation;
+mod serde_options;
 mod transform;
 mod transform_set;
 mod unit;
+mod urdf;
 
 pub use crate::{
+    average::{RotationMean, TransformMean},
+    convention::{AxisConvention, AxisDirection},
+    covariance::{Covariance, Perturbation, TangentOrder},
+    diff::{RotationDiff, TransformDiff},
+    error::{NonFiniteError, RotationMatrixError},
+    expr::restore_expressions,
+    kitti::{KittiKey, KittiMatrix, KittiOptions},
+    nuscenes::{CalibratedSensor, EgoPose, EgoPoseSequence, NuScenes, Scene},
+    ros_msg::{
+        RosHeader, RosQuaternion, RosTime, RosTransform, RosVector3, TfMessage, TransformStamped,
+    },
     rotation::{
-        AxisAngle, Euler, EulerAxis, EulerAxisOrder, Quaternion, Rodrigues, Rotation,
-        RotationMatrix,
+        AxisAngle, Euler, EulerAxis, EulerAxisOrder, EulerConvention, Gibbs, Mrp, Quaternion,
+        QuaternionConvention, QuaternionOrder, Rodrigues, Rotation, RotationMatrix,
+        RotationMatrixRepair,
+    },
+    serde_options::{with_serde_options, Precision, SerdeOptions},
+    transform::{
+        AffineMatrix, DualQuaternion, HomogeneousMatrix, MaybeTransform, MaybeTransformSeed,
+        Transform, TransformMatrix, Translation, Twist,
     },
-    transform::{MaybeTransform, Transform, Translation},
     transform_set::{CoordTransform, TransformSet},
 };
-pub use unit::{Angle, Length};
+pub use unit::{Angle, AngleUnit, Length, LengthUnit};
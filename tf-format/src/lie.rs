@@ -0,0 +1,97 @@
+use nalgebra as na;
+
+/// Below the rotation angle, the Taylor expansions are used to avoid
+/// dividing by near-zero values.
+const SMALL_ANGLE: f64 = 1e-6;
+
+pub(crate) fn so3_exp(omega: &na::Vector3<f64>) -> na::UnitQuaternion<f64> {
+    na::UnitQuaternion::from_scaled_axis(*omega)
+}
+
+pub(crate) fn so3_log(rot: &na::UnitQuaternion<f64>) -> na::Vector3<f64> {
+    rot.scaled_axis()
+}
+
+pub(crate) fn se3_exp(twist: &na::Vector6<f64>) -> na::Isometry3<f64> {
+    let rho: na::Vector3<f64> = twist.fixed_rows::<3>(0).into();
+    let omega: na::Vector3<f64> = twist.fixed_rows::<3>(3).into();
+
+    let rot = so3_exp(&omega);
+    let trans = left_jacobian(&omega) * rho;
+    na::Isometry3::from_parts(trans.into(), rot)
+}
+
+pub(crate) fn se3_log(iso: &na::Isometry3<f64>) -> na::Vector6<f64> {
+    let omega = so3_log(&iso.rotation);
+    let rho = inverse_left_jacobian(&omega) * iso.translation.vector;
+
+    let mut twist = na::Vector6::zeros();
+    twist.fixed_rows_mut::<3>(0).copy_from(&rho);
+    twist.fixed_rows_mut::<3>(3).copy_from(&omega);
+    twist
+}
+
+/// The left Jacobian of SO(3), `V = I + (1 - cos θ) / θ² W + (θ - sin θ) / θ³ W²`.
+fn left_jacobian(omega: &na::Vector3<f64>) -> na::Matrix3<f64> {
+    let theta = omega.norm();
+    let w = omega.cross_matrix();
+    let w2 = w * w;
+
+    let (a, b) = if theta < SMALL_ANGLE {
+        let theta2 = theta * theta;
+        (0.5 - theta2 / 24.0, 1.0 / 6.0 - theta2 / 120.0)
+    } else {
+        let theta2 = theta * theta;
+        (
+            (1.0 - theta.cos()) / theta2,
+            (theta - theta.sin()) / (theta2 * theta),
+        )
+    };
+
+    na::Matrix3::identity() + w * a + w2 * b
+}
+
+/// The inverse of [left_jacobian],
+/// `V⁻¹ = I - W / 2 + (1 - θ sin θ / (2 (1 - cos θ))) / θ² W²`.
+fn inverse_left_jacobian(omega: &na::Vector3<f64>) -> na::Matrix3<f64> {
+    let theta = omega.norm();
+    let w = omega.cross_matrix();
+    let w2 = w * w;
+
+    let c = if theta < SMALL_ANGLE {
+        1.0 / 12.0 + theta * theta / 720.0
+    } else {
+        let theta2 = theta * theta;
+        (1.0 - theta * theta.sin() / (2.0 * (1.0 - theta.cos()))) / theta2
+    };
+
+    na::Matrix3::identity() - w * 0.5 + w2 * c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{se3_exp, se3_log};
+    use approx::assert_abs_diff_eq;
+    use nalgebra as na;
+
+    #[test]
+    fn se3_round_trip() {
+        let twists = [
+            na::Vector6::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+            na::Vector6::new(1.0, -2.0, 0.5, 0.0, 0.0, 0.0),
+            na::Vector6::new(1.0, -2.0, 0.5, 1e-9, -2e-9, 0.0),
+            na::Vector6::new(0.3, 0.2, -0.1, 0.4, -0.5, 1.2),
+            na::Vector6::new(-3.0, 0.0, 2.0, 0.0, 3.0, 0.0),
+        ];
+
+        for twist in twists {
+            let iso = se3_exp(&twist);
+            assert_abs_diff_eq!(se3_log(&iso), twist, epsilon = 1e-6);
+        }
+
+        // Pure rotation about an axis through a point
+        let rot = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 1.0);
+        let iso = na::Isometry3::from_parts(na::Translation3::new(1.0, 2.0, 3.0), rot);
+        assert_abs_diff_eq!(se3_exp(&se3_log(&iso)), iso, epsilon = 1e-9);
+    }
+}
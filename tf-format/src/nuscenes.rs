@@ -0,0 +1,505 @@
+use crate::{
+    CoordTransform, QuaternionConvention, QuaternionOrder, Rotation, Transform, TransformSet,
+    Translation,
+};
+use anyhow::{bail, ensure, Context, Result};
+use approx::abs_diff_eq;
+use nalgebra as na;
+use noisy_float::types::r64;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// The sensor extrinsics and ego poses of a nuScenes dataset.
+#[derive(Debug, Clone)]
+pub struct NuScenes {
+    pub calibrated_sensors: Vec<CalibratedSensor>,
+    pub ego_poses: EgoPoseSequence,
+    /// The table directory to load the other tables from on demand.
+    dir: PathBuf,
+    /// The scenes loaded from `scene.json` and `log.json`.
+    scenes: OnceLock<Vec<Scene>>,
+    /// The samples loaded from `sample.json` and `sample_data.json`.
+    samples: OnceLock<Samples>,
+}
+
+/// The samples, which are only used to select the calibrations.
+#[derive(Debug, Clone)]
+struct Samples {
+    /// The time stamps and scene tokens of the samples.
+    timestamps: Vec<(i64, String)>,
+    /// The calibrated sensor tokens used by the sample data of each
+    /// scene token.
+    scene_sensors: HashMap<String, Vec<String>>,
+}
+
+/// A record of `scene.json` along with the name of its log.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub token: String,
+    /// The name such as "scene-0061".
+    pub name: String,
+    pub log_token: String,
+    /// The log file name such as "n015-2018-07-24-11-22-45+0800".
+    pub logfile: String,
+}
+
+/// A record of `calibrated_sensor.json`, the pose of the sensor in
+/// the ego vehicle frame.
+#[derive(Debug, Clone)]
+pub struct CalibratedSensor {
+    pub token: String,
+    /// The channel of the sensor such as "CAM_FRONT" or "LIDAR_TOP".
+    pub channel: String,
+    pub tf: Transform,
+}
+
+/// A record of `ego_pose.json`, the pose of the ego vehicle in the
+/// global frame.
+#[derive(Debug, Clone)]
+pub struct EgoPose {
+    pub token: String,
+    /// The Unix time stamp in microseconds.
+    pub timestamp: i64,
+    pub tf: Transform,
+}
+
+/// The ego poses sorted by time stamp.
+#[derive(Debug, Clone, Default)]
+pub struct EgoPoseSequence {
+    poses: Vec<EgoPose>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SensorRecord {
+    token: String,
+    channel: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CalibratedSensorRecord {
+    token: String,
+    sensor_token: String,
+    translation: [f64; 3],
+    rotation: [f64; 4],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SceneRecord {
+    token: String,
+    log_token: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LogRecord {
+    token: String,
+    logfile: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SampleRecord {
+    token: String,
+    timestamp: i64,
+    scene_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SampleDataRecord {
+    sample_token: String,
+    calibrated_sensor_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EgoPoseRecord {
+    token: String,
+    timestamp: i64,
+    translation: [f64; 3],
+    rotation: [f64; 4],
+}
+
+impl NuScenes {
+    /// The frame of the ego vehicle.
+    pub const EGO_FRAME: &'static str = "ego";
+
+    /// The global frame of the ego poses.
+    pub const GLOBAL_FRAME: &'static str = "global";
+
+    /// Load the tables from the table directory of a dataset version,
+    /// such as "v1.0-mini". Only the sensor, calibrated sensor and ego
+    /// pose tables are read here. The scene, log, sample and sample
+    /// data tables are read when the calibrations are selected by
+    /// scene, log or time stamp, so they can be missing otherwise.
+    pub fn from_dir<P>(dir: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        let sensors: Vec<SensorRecord> = load_table(&dir.join("sensor.json"))?;
+        let calibrated_sensors: Vec<CalibratedSensorRecord> =
+            load_table(&dir.join("calibrated_sensor.json"))?;
+        let ego_poses: Vec<EgoPoseRecord> = load_table(&dir.join("ego_pose.json"))?;
+
+        let channels: HashMap<_, _> = sensors
+            .iter()
+            .map(|sensor| (sensor.token.as_str(), sensor.channel.as_str()))
+            .collect();
+
+        let calibrated_sensors: Vec<_> = calibrated_sensors
+            .into_iter()
+            .map(|record| {
+                let CalibratedSensorRecord {
+                    token,
+                    sensor_token,
+                    translation,
+                    rotation,
+                } = record;
+                let Some(&channel) = channels.get(sensor_token.as_str()) else {
+                    bail!(
+                        "the calibrated sensor '{token}' refers to unknown sensor '{sensor_token}'"
+                    );
+                };
+                let tf = make_transform(translation, rotation)
+                    .with_context(|| format!("invalid calibrated sensor '{token}'"))?;
+                Ok(CalibratedSensor {
+                    token,
+                    channel: channel.to_string(),
+                    tf,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let ego_poses: Vec<_> = ego_poses
+            .into_iter()
+            .map(|record| {
+                let EgoPoseRecord {
+                    token,
+                    timestamp,
+                    translation,
+                    rotation,
+                } = record;
+                let tf = make_transform(translation, rotation)
+                    .with_context(|| format!("invalid ego pose '{token}'"))?;
+                Ok(EgoPose {
+                    token,
+                    timestamp,
+                    tf,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            calibrated_sensors,
+            ego_poses: EgoPoseSequence::new(ego_poses),
+            dir: dir.to_path_buf(),
+            scenes: OnceLock::new(),
+            samples: OnceLock::new(),
+        })
+    }
+
+    /// The scenes along with the names of their logs. The scene and
+    /// log tables are read on the first call.
+    pub fn scenes(&self) -> Result<&[Scene]> {
+        if let Some(scenes) = self.scenes.get() {
+            return Ok(scenes);
+        }
+        let scenes = load_scenes(&self.dir)?;
+        Ok(self.scenes.get_or_init(|| scenes))
+    }
+
+    /// The samples. The sample and sample data tables are read on the
+    /// first call.
+    fn samples(&self) -> Result<&Samples> {
+        if let Some(samples) = self.samples.get() {
+            return Ok(samples);
+        }
+        let samples = load_samples(&self.dir)?;
+        Ok(self.samples.get_or_init(|| samples))
+    }
+
+    /// Build the set of transforms from the ego frame to the sensor
+    /// channels. It fails if a channel has different calibrations,
+    /// which happens across the logs of a dataset. Use
+    /// [sensor_set_for_scene](Self::sensor_set_for_scene),
+    /// [sensor_set_for_log](Self::sensor_set_for_log) or
+    /// [sensor_set_at](Self::sensor_set_at) to select the calibrations
+    /// in that case.
+    pub fn sensor_set(&self) -> Result<TransformSet> {
+        build_sensor_set(select_unique(self.calibrated_sensors.iter())?)
+    }
+
+    /// Build the set of transforms from the ego frame to the sensor
+    /// channels with the calibrations used in the scene, given by the
+    /// name such as "scene-0061" or the token.
+    pub fn sensor_set_for_scene(&self, scene: &str) -> Result<TransformSet> {
+        let scene = self
+            .scenes()?
+            .iter()
+            .find(|record| record.name == scene || record.token == scene)
+            .with_context(|| format!("the scene '{scene}' is not found"))?;
+        self.sensor_set_for_scenes([scene.token.as_str()])
+    }
+
+    /// Build the set of transforms from the ego frame to the sensor
+    /// channels with the calibrations used in the log, given by the
+    /// log file name or the token.
+    pub fn sensor_set_for_log(&self, log: &str) -> Result<TransformSet> {
+        let scenes: Vec<_> = self
+            .scenes()?
+            .iter()
+            .filter(|scene| scene.logfile == log || scene.log_token == log)
+            .map(|scene| scene.token.as_str())
+            .collect();
+        ensure!(!scenes.is_empty(), "no scene is found in the log '{log}'");
+        self.sensor_set_for_scenes(scenes)
+    }
+
+    /// Build the set of transforms from the ego frame to the sensor
+    /// channels with the calibrations used in the scene of the sample
+    /// nearest to the time stamp in microseconds.
+    pub fn sensor_set_at(&self, timestamp: i64) -> Result<TransformSet> {
+        let Some((_, scene_token)) = self
+            .samples()?
+            .timestamps
+            .iter()
+            .min_by_key(|(sample_time, _)| sample_time.abs_diff(timestamp))
+        else {
+            bail!("the dataset has no samples");
+        };
+        self.sensor_set_for_scenes([scene_token.as_str()])
+    }
+
+    /// Build the set of transforms from the ego frame to the sensor
+    /// channels of the calibrated sensor tokens.
+    pub fn sensor_set_from_tokens<'a, I>(&self, tokens: I) -> Result<TransformSet>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let selected: Vec<_> = tokens
+            .into_iter()
+            .map(|token| {
+                self.calibrated_sensors
+                    .iter()
+                    .find(|sensor| sensor.token == token)
+                    .with_context(|| format!("the calibrated sensor '{token}' is not found"))
+            })
+            .collect::<Result<_>>()?;
+        build_sensor_set(selected)
+    }
+}
+
+impl EgoPoseSequence {
+    /// Create the sequence, sorting the poses by time stamp.
+    pub fn new(mut poses: Vec<EgoPose>) -> Self {
+        poses.sort_by_key(|pose| pose.timestamp);
+        Self { poses }
+    }
+
+    pub fn len(&self) -> usize {
+        self.poses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.poses.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, EgoPose> {
+        self.poses.iter()
+    }
+
+    /// Find the pose with the time stamp closest to `timestamp`.
+    pub fn nearest(&self, timestamp: i64) -> Option<&EgoPose> {
+        self.poses
+            .iter()
+            .min_by_key(|pose| pose.timestamp.abs_diff(timestamp))
+    }
+
+    /// Interpolate the pose at `timestamp` between the adjacent poses
+    /// by [Transform::try_interpolate]. It returns `None` outside the
+    /// time range of the sequence.
+    pub fn interpolate(&self, timestamp: i64) -> Result<Option<Transform>> {
+        let idx = self
+            .poses
+            .partition_point(|pose| pose.timestamp < timestamp);
+        let Some(next) = self.poses.get(idx) else {
+            return Ok(None);
+        };
+        if next.timestamp == timestamp {
+            return Ok(Some(next.tf.clone()));
+        }
+
+        let Some(prev) = idx.checked_sub(1).map(|idx| &self.poses[idx]) else {
+            return Ok(None);
+        };
+        let ratio = (timestamp - prev.timestamp) as f64 / (next.timestamp - prev.timestamp) as f64;
+        Ok(Some(prev.tf.try_interpolate(&next.tf, ratio)?))
+    }
+}
+
+impl<'a> IntoIterator for &'a EgoPoseSequence {
+    type Item = &'a EgoPose;
+    type IntoIter = std::slice::Iter<'a, EgoPose>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl NuScenes {
+    /// Select the calibrations used in the scenes of the tokens.
+    fn sensor_set_for_scenes<'a, I>(&self, scene_tokens: I) -> Result<TransformSet>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let scene_sensors = &self.samples()?.scene_sensors;
+        let tokens: HashSet<&str> = scene_tokens
+            .into_iter()
+            .flat_map(|token| scene_sensors.get(token))
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        ensure!(!tokens.is_empty(), "no sample data is found in the scenes");
+
+        let sensors = self
+            .calibrated_sensors
+            .iter()
+            .filter(|sensor| tokens.contains(sensor.token.as_str()));
+        build_sensor_set(select_unique(sensors)?)
+    }
+}
+
+/// Keep one calibration for each channel. It fails if a channel has
+/// different calibrations.
+fn select_unique<'a, I>(sensors: I) -> Result<Vec<&'a CalibratedSensor>>
+where
+    I: IntoIterator<Item = &'a CalibratedSensor>,
+{
+    let mut selected: Vec<&CalibratedSensor> = vec![];
+
+    for sensor in sensors {
+        match selected.iter().find(|prev| prev.channel == sensor.channel) {
+            Some(prev) => {
+                let lhs: na::Isometry3<f64> = prev.tf.clone().into();
+                let rhs: na::Isometry3<f64> = sensor.tf.clone().into();
+                ensure!(
+                    abs_diff_eq!(lhs, rhs, epsilon = 1e-6),
+                    "the channel '{}' has different calibrations '{}' and '{}'",
+                    sensor.channel,
+                    prev.token,
+                    sensor.token
+                );
+            }
+            None => selected.push(sensor),
+        }
+    }
+
+    Ok(selected)
+}
+
+fn build_sensor_set(sensors: Vec<&CalibratedSensor>) -> Result<TransformSet> {
+    let list = sensors.into_iter().map(|sensor| CoordTransform {
+        src: NuScenes::EGO_FRAME.to_string(),
+        dst: sensor.channel.clone(),
+        tf: sensor.tf.clone(),
+    });
+    Ok(TransformSet::try_from_iter(list)?)
+}
+
+/// Load the scenes from `scene.json` along with the log names from
+/// `log.json`.
+fn load_scenes(dir: &Path) -> Result<Vec<Scene>> {
+    let scenes: Vec<SceneRecord> = load_table(&dir.join("scene.json"))?;
+    let logs: Vec<LogRecord> = load_table(&dir.join("log.json"))?;
+
+    let logfiles: HashMap<_, _> = logs
+        .iter()
+        .map(|log| (log.token.as_str(), log.logfile.as_str()))
+        .collect();
+    scenes
+        .into_iter()
+        .map(|record| {
+            let SceneRecord {
+                token,
+                log_token,
+                name,
+            } = record;
+            let Some(&logfile) = logfiles.get(log_token.as_str()) else {
+                bail!("the scene '{name}' refers to unknown log '{log_token}'");
+            };
+            Ok(Scene {
+                token,
+                name,
+                log_token,
+                logfile: logfile.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Load the samples from `sample.json` and the calibrations used in
+/// each scene from `sample_data.json`.
+fn load_samples(dir: &Path) -> Result<Samples> {
+    let samples: Vec<SampleRecord> = load_table(&dir.join("sample.json"))?;
+    let sample_data: Vec<SampleDataRecord> = load_table(&dir.join("sample_data.json"))?;
+
+    let sample_scenes: HashMap<_, _> = samples
+        .iter()
+        .map(|sample| (sample.token.as_str(), sample.scene_token.as_str()))
+        .collect();
+    let mut scene_sensors: HashMap<String, Vec<String>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for record in &sample_data {
+        let SampleDataRecord {
+            sample_token,
+            calibrated_sensor_token,
+        } = record;
+        let Some(&scene_token) = sample_scenes.get(sample_token.as_str()) else {
+            bail!("the sample data refers to unknown sample '{sample_token}'");
+        };
+        if seen.insert((scene_token, calibrated_sensor_token.as_str())) {
+            scene_sensors
+                .entry(scene_token.to_string())
+                .or_default()
+                .push(calibrated_sensor_token.clone());
+        }
+    }
+
+    let timestamps = samples
+        .into_iter()
+        .map(|sample| (sample.timestamp, sample.scene_token))
+        .collect();
+
+    Ok(Samples {
+        timestamps,
+        scene_sensors,
+    })
+}
+
+/// Create the transform from the translation and the quaternion in
+/// w, x, y, z order, keeping the quaternion order.
+fn make_transform(translation: [f64; 3], rotation: [f64; 4]) -> Result<Transform> {
+    let [w, x, y, z] = rotation;
+    let quat = na::UnitQuaternion::new_normalize(na::Quaternion::new(w, x, y, z));
+    let rot = Rotation::try_from_na_unit_quaternion(&quat)?
+        .into_quaternion_format_with(QuaternionOrder::Wxyz, QuaternionConvention::Hamilton);
+    Ok(Transform {
+        r: rot,
+        t: Translation::from_meters(translation.map(r64)),
+        cov: None,
+    })
+}
+
+fn load_table<T>(path: &Path) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    let file = File::open(path).with_context(|| format!("unable to open '{}'", path.display()))?;
+    let records = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("unable to parse '{}'", path.display()))?;
+    Ok(records)
+}
@@ -0,0 +1,182 @@
+use crate::{
+    covariance::{self, Covariance},
+    MaybeTransform, Rotation, Transform,
+};
+use nalgebra as na;
+use std::ops::Mul;
+
+impl Rotation {
+    /// Rotate a point given as `[f64; 3]` or `na::Point3<f64>`.
+    pub fn transform_point<P>(&self, point: P) -> P
+    where
+        P: Into<na::Point3<f64>> + From<na::Point3<f64>>,
+    {
+        let quat: na::UnitQuaternion<f64> = self.clone().into();
+        quat.transform_point(&point.into()).into()
+    }
+
+    /// Rotate a vector given as `[f64; 3]` or `na::Vector3<f64>`.
+    pub fn transform_vector<V>(&self, vec: V) -> V
+    where
+        V: Into<na::Vector3<f64>> + From<na::Vector3<f64>>,
+    {
+        let quat: na::UnitQuaternion<f64> = self.clone().into();
+        quat.transform_vector(&vec.into()).into()
+    }
+}
+
+impl Transform {
+    /// Transform a point given as `[f64; 3]` or `na::Point3<f64>`
+    /// in meters.
+    pub fn transform_point<P>(&self, point: P) -> P
+    where
+        P: Into<na::Point3<f64>> + From<na::Point3<f64>>,
+    {
+        let iso: na::Isometry3<f64> = self.clone().into();
+        iso.transform_point(&point.into()).into()
+    }
+
+    /// Transform a vector given as `[f64; 3]` or `na::Vector3<f64>`.
+    /// The translation does not apply to vectors.
+    pub fn transform_vector<V>(&self, vec: V) -> V
+    where
+        V: Into<na::Vector3<f64>> + From<na::Vector3<f64>>,
+    {
+        self.r.transform_vector(vec)
+    }
+}
+
+impl MaybeTransform {
+    /// Transform a point given as `[f64; 3]` or `na::Point3<f64>`
+    /// in meters.
+    pub fn transform_point<P>(&self, point: P) -> P
+    where
+        P: Into<na::Point3<f64>> + From<na::Point3<f64>>,
+    {
+        let iso: na::Isometry3<f64> = self.to_na_isometry3();
+        iso.transform_point(&point.into()).into()
+    }
+
+    /// Transform a vector given as `[f64; 3]` or `na::Vector3<f64>`.
+    /// The translation does not apply to vectors.
+    pub fn transform_vector<V>(&self, vec: V) -> V
+    where
+        V: Into<na::Vector3<f64>> + From<na::Vector3<f64>>,
+    {
+        self.r.transform_vector(vec)
+    }
+}
+
+/// The product is in the rotation format and angle unit of the left
+/// operand.
+impl Mul<&Rotation> for &Rotation {
+    type Output = Rotation;
+
+    fn mul(self, rhs: &Rotation) -> Self::Output {
+        let lhs: na::UnitQuaternion<f64> = self.clone().into();
+        let rhs: na::UnitQuaternion<f64> = rhs.clone().into();
+        self.convert_like(&(lhs * rhs))
+    }
+}
+
+/// The product is in the rotation format, angle unit and length
+/// units of the left operand. The covariances are propagated assuming
+/// the operands are independent.
+impl Mul<&Transform> for &Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: &Transform) -> Self::Output {
+        let lhs_iso: na::Isometry3<f64> = self.clone().into();
+        let rhs_iso: na::Isometry3<f64> = rhs.clone().into();
+        let prod = lhs_iso * rhs_iso;
+        let cov = product_cov(
+            self.cov.as_ref().or(rhs.cov.as_ref()),
+            self.right_cov(),
+            rhs.right_cov(),
+            &rhs_iso,
+            &prod,
+        );
+
+        Transform {
+            r: self.r.convert_like(&prod.rotation),
+            t: self.t.convert_like(&prod.translation.vector),
+            cov,
+        }
+    }
+}
+
+/// The product is in the rotation format, angle unit and length
+/// units of the left operand. It has a translation if either operand
+/// has one. The covariances are propagated assuming the operands are
+/// independent.
+impl Mul<&MaybeTransform> for &MaybeTransform {
+    type Output = MaybeTransform;
+
+    fn mul(self, rhs: &MaybeTransform) -> Self::Output {
+        let rhs_iso = rhs.to_na_isometry3::<f64>();
+        let prod = self.to_na_isometry3::<f64>() * rhs_iso;
+        let cov = product_cov(
+            self.cov.as_ref().or(rhs.cov.as_ref()),
+            self.right_cov(),
+            rhs.right_cov(),
+            &rhs_iso,
+            &prod,
+        );
+        let t = match (&self.t, &rhs.t) {
+            (Some(t), _) => Some(t.convert_like(&prod.translation.vector)),
+            (None, Some(t)) => Some(t.convert_like(&prod.translation.vector)),
+            (None, None) => None,
+        };
+
+        MaybeTransform {
+            r: self.r.convert_like(&prod.rotation),
+            t,
+            cov,
+        }
+    }
+}
+
+/// Compute the covariance of the product in the convention of `like`.
+fn product_cov(
+    like: Option<&Covariance>,
+    lhs_cov: Option<na::Matrix6<f64>>,
+    rhs_cov: Option<na::Matrix6<f64>>,
+    rhs: &na::Isometry3<f64>,
+    prod: &na::Isometry3<f64>,
+) -> Option<Covariance> {
+    let like = like?;
+    let mat = covariance::product_cov(rhs, lhs_cov.as_ref(), rhs_cov.as_ref());
+    Some(like.convert_like(&mat, prod))
+}
+
+macro_rules! forward_mul {
+    ($ty:ty) => {
+        impl Mul<$ty> for $ty {
+            type Output = $ty;
+
+            fn mul(self, rhs: $ty) -> Self::Output {
+                &self * &rhs
+            }
+        }
+
+        impl Mul<&$ty> for $ty {
+            type Output = $ty;
+
+            fn mul(self, rhs: &$ty) -> Self::Output {
+                &self * rhs
+            }
+        }
+
+        impl Mul<$ty> for &$ty {
+            type Output = $ty;
+
+            fn mul(self, rhs: $ty) -> Self::Output {
+                self * &rhs
+            }
+        }
+    };
+}
+
+forward_mul!(Rotation);
+forward_mul!(Transform);
+forward_mul!(MaybeTransform);
@@ -0,0 +1,155 @@
+use crate::{CoordTransform, NonFiniteError, Transform, TransformSet};
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+
+/// The ROS `geometry_msgs/TransformStamped` message. The header
+/// frame is the parent frame and the child frame is the transformed
+/// frame, corresponding to the `src` and `dst` of a
+/// [CoordTransform].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformStamped {
+    pub header: RosHeader,
+    pub child_frame_id: String,
+    pub transform: RosTransform,
+}
+
+/// The ROS `tf2_msgs/TFMessage` message, as published on `/tf` and
+/// `/tf_static`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TfMessage {
+    pub transforms: Vec<TransformStamped>,
+}
+
+/// The ROS `std_msgs/Header` message. The ROS 1 `seq` field is
+/// ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RosHeader {
+    #[serde(default)]
+    pub stamp: RosTime,
+    pub frame_id: String,
+}
+
+/// The ROS time stamp. It's written in ROS 2 field names, while the
+/// ROS 1 names "secs" and "nsecs" are accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RosTime {
+    #[serde(alias = "secs")]
+    pub sec: i64,
+    #[serde(alias = "nsecs")]
+    pub nanosec: u32,
+}
+
+/// The ROS `geometry_msgs/Transform` message.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RosTransform {
+    pub translation: RosVector3,
+    pub rotation: RosQuaternion,
+}
+
+/// The ROS `geometry_msgs/Vector3` message in meters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RosVector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// The ROS `geometry_msgs/Quaternion` message.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RosQuaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+impl From<&Transform> for RosTransform {
+    fn from(tf: &Transform) -> Self {
+        let iso: na::Isometry3<f64> = tf.clone().into();
+        let [x, y, z] = iso.translation.vector.into();
+        let quat = iso.rotation.quaternion();
+        Self {
+            translation: RosVector3 { x, y, z },
+            rotation: RosQuaternion {
+                x: quat.i,
+                y: quat.j,
+                z: quat.k,
+                w: quat.w,
+            },
+        }
+    }
+}
+
+impl TryFrom<&RosTransform> for Transform {
+    type Error = NonFiniteError;
+
+    /// Convert the message, normalizing the quaternion.
+    fn try_from(from: &RosTransform) -> Result<Self, Self::Error> {
+        let RosTransform {
+            translation: RosVector3 { x, y, z },
+            rotation: quat,
+        } = *from;
+        let rot =
+            na::UnitQuaternion::new_normalize(na::Quaternion::new(quat.w, quat.x, quat.y, quat.z));
+        Transform::try_from_na_isometry3(&na::Isometry3::from_parts(
+            na::Translation3::new(x, y, z),
+            rot,
+        ))
+    }
+}
+
+impl From<&CoordTransform> for TransformStamped {
+    /// Convert to the message with a zero time stamp. The covariance
+    /// is discarded.
+    fn from(from: &CoordTransform) -> Self {
+        let CoordTransform { src, dst, tf } = from;
+        Self {
+            header: RosHeader {
+                stamp: RosTime::default(),
+                frame_id: src.clone(),
+            },
+            child_frame_id: dst.clone(),
+            transform: tf.into(),
+        }
+    }
+}
+
+impl TryFrom<&TransformStamped> for CoordTransform {
+    type Error = NonFiniteError;
+
+    /// Convert the message, discarding the time stamp.
+    fn try_from(from: &TransformStamped) -> Result<Self, Self::Error> {
+        let TransformStamped {
+            header,
+            child_frame_id,
+            transform,
+        } = from;
+        Ok(Self {
+            src: header.frame_id.clone(),
+            dst: child_frame_id.clone(),
+            tf: transform.try_into()?,
+        })
+    }
+}
+
+impl From<&TransformSet> for TfMessage {
+    /// Convert the inserted transforms to messages.
+    fn from(set: &TransformSet) -> Self {
+        Self {
+            transforms: set.transforms().iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<&TfMessage> for TransformSet {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: &TfMessage) -> Result<Self, Self::Error> {
+        let list: Vec<CoordTransform> = msg
+            .transforms
+            .iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        Ok(Self::try_from_iter(list)?)
+    }
+}
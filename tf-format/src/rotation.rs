@@ -1,9 +1,19 @@
-use crate::unit::{Angle, Length};
+use crate::{
+    error::{NonFiniteError, RotationMatrixError},
+    expr, lie,
+    serde_options::{self, serde_options},
+    unit::{Angle, AngleUnit},
+};
 use anyhow::{bail, Result};
 use nalgebra as na;
 use noisy_float::types::{r64, R64};
+use num::NumCast;
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
-use std::{f64::consts::PI, str::FromStr};
+use std::{
+    f64::consts::PI,
+    fmt::{self, Display},
+    str::FromStr,
+};
 
 /// Generic rotation data type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +24,8 @@ pub enum Rotation {
     AxisAngle(AxisAngle),
     RotationMatrix(RotationMatrix),
     Rodrigues(Rodrigues),
+    Mrp(Mrp),
+    Gibbs(Gibbs),
 }
 
 impl Rotation {
@@ -24,6 +36,8 @@ impl Rotation {
             Rotation::AxisAngle(rot) => rot.normalize().into(),
             Rotation::RotationMatrix(rot) => rot.clone().into(),
             Rotation::Rodrigues(rot) => rot.normalize().into(),
+            Rotation::Mrp(rot) => rot.normalize().into(),
+            Rotation::Gibbs(rot) => rot.clone().into(),
         }
     }
 
@@ -34,6 +48,8 @@ impl Rotation {
             Rotation::AxisAngle(rot) => rot.inverse().into(),
             Rotation::RotationMatrix(rot) => rot.inverse().into(),
             Rotation::Rodrigues(rot) => rot.inverse().into(),
+            Rotation::Mrp(rot) => rot.inverse().into(),
+            Rotation::Gibbs(rot) => rot.inverse().into(),
         }
     }
 
@@ -44,6 +60,8 @@ impl Rotation {
             Rotation::AxisAngle(rot) => rot.into_degrees().into(),
             Rotation::RotationMatrix(rot) => rot.into(),
             Rotation::Rodrigues(rot) => rot.into(),
+            Rotation::Mrp(rot) => rot.into(),
+            Rotation::Gibbs(rot) => rot.into(),
         }
     }
 
@@ -54,13 +72,144 @@ impl Rotation {
             Rotation::AxisAngle(rot) => rot.into_radians().into(),
             Rotation::RotationMatrix(rot) => rot.into(),
             Rotation::Rodrigues(rot) => rot.into(),
+            Rotation::Mrp(rot) => rot.into(),
+            Rotation::Gibbs(rot) => rot.into(),
         }
     }
 
+    /// Convert the angles to the unit. Only Euler and axis-angle
+    /// formats are affected.
+    pub fn into_angle_unit(self, unit: AngleUnit) -> Self {
+        match self {
+            Rotation::Euler(rot) => rot.into_angle_unit(unit).into(),
+            Rotation::AxisAngle(rot) => rot.into_angle_unit(unit).into(),
+            rot => rot,
+        }
+    }
+
+    /// Check if the rotation is valid. Only the rotation matrix
+    /// format can be invalid.
+    pub fn validate(&self) -> Result<(), RotationMatrixError> {
+        match self {
+            Rotation::RotationMatrix(rot) => rot.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Project the rotation matrix onto the nearest valid rotation.
+    /// The report is returned only for the rotation matrix format.
+    pub fn repair(&self) -> (Self, Option<RotationMatrixRepair>) {
+        match self {
+            Rotation::RotationMatrix(rot) => {
+                let (rot, report) = rot.repair();
+                (rot.into(), Some(report))
+            }
+            _ => (self.clone(), None),
+        }
+    }
+
+    /// Spherical linear interpolation (slerp) between two rotations
+    /// along the shortest path. The result is in the format of
+    /// `self`.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the result is NaN or infinite. Use
+    /// [Rotation::try_slerp] to get an error instead.
+    pub fn slerp(&self, other: &Self, t: f64) -> Self {
+        self.try_slerp(other, t).unwrap()
+    }
+
+    /// Like [Rotation::slerp], but it returns an error instead of
+    /// panicking if the result is NaN or infinite.
+    pub fn try_slerp(&self, other: &Self, t: f64) -> Result<Self, NonFiniteError> {
+        let lhs: na::UnitQuaternion<f64> = self.clone().into();
+        let rhs: na::UnitQuaternion<f64> = other.clone().into();
+        let delta = lie::so3_log(&(lhs.inverse() * rhs));
+        let quat = lhs * lie::so3_exp(&(delta * t));
+        self.try_convert_like(&quat)
+    }
+
+    /// Convert from a unit quaternion. Unlike the `From`
+    /// conversion, it returns an error instead of panicking if any
+    /// component is NaN or infinite.
+    pub fn try_from_na_unit_quaternion<T>(
+        quat: &na::UnitQuaternion<T>,
+    ) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        NonFiniteError::check_unit_quaternion(quat)?;
+        Ok(quat.clone().into())
+    }
+
+    /// Like [Rotation::convert_like], but it checks that the
+    /// quaternion is finite.
+    pub(crate) fn try_convert_like(
+        &self,
+        quat: &na::UnitQuaternion<f64>,
+    ) -> Result<Self, NonFiniteError> {
+        NonFiniteError::check_unit_quaternion(quat)?;
+        Ok(self.convert_like(quat))
+    }
+
+    /// Convert the quaternion to the same format and angle unit as
+    /// `self`. The Gibbs vector falls back to quaternion format for
+    /// 180 degree rotations.
+    pub(crate) fn convert_like(&self, quat: &na::UnitQuaternion<f64>) -> Self {
+        match self {
+            Rotation::Euler(euler) => {
+                let rot = euler.decompose_like(quat);
+                match euler.angles.first() {
+                    Some(angle) => rot.into_angle_unit(angle.unit),
+                    None => rot,
+                }
+                .into()
+            }
+            Rotation::Quaternion(orig) => {
+                Quaternion::from_unit_quaternion(quat, orig.order, orig.convention).into()
+            }
+            Rotation::AxisAngle(orig) => AxisAngle::from(*quat)
+                .into_angle_unit(orig.angle.unit)
+                .into(),
+            Rotation::RotationMatrix(_) => RotationMatrix::from(*quat).into(),
+            Rotation::Rodrigues(_) => Rodrigues::from(*quat).into(),
+            Rotation::Mrp(_) => Mrp::from(*quat).into(),
+            Rotation::Gibbs(_) => match Gibbs::try_from(*quat) {
+                Ok(gibbs) => gibbs.into(),
+                Err(_) => Quaternion::from(*quat).into(),
+            },
+        }
+    }
+
+    /// The logarithm map of SO(3). It returns the rotation vector
+    /// `axis * angle` with the angle in `[0, pi]`.
+    pub fn log(&self) -> [f64; 3] {
+        let quat: na::UnitQuaternion<f64> = self.clone().into();
+        lie::so3_log(&quat).into()
+    }
+
+    /// The exponential map of SO(3). The rotation vector `axis *
+    /// angle` is converted to a rotation in quaternion format.
+    pub fn exp(omega: [f64; 3]) -> Self {
+        lie::so3_exp(&omega.into()).into()
+    }
+
     pub fn into_euler_format(self) -> Self {
         Euler::from(self).into()
     }
 
+    /// Convert to Euler format in the specified axis order and
+    /// convention.
+    pub fn into_euler_format_with(
+        self,
+        order: EulerAxisOrder,
+        convention: EulerConvention,
+    ) -> Result<Self> {
+        let quat: na::UnitQuaternion<f64> = self.into();
+        Ok(Euler::from_quaternion(&quat, order, convention)?.into())
+    }
+
     pub fn into_axis_angle_format(self) -> Self {
         AxisAngle::from(self).into()
     }
@@ -69,72 +218,502 @@ impl Rotation {
         Quaternion::from(self).into()
     }
 
+    /// Convert to quaternion format in the specified component order
+    /// and convention.
+    pub fn into_quaternion_format_with(
+        self,
+        order: QuaternionOrder,
+        convention: QuaternionConvention,
+    ) -> Self {
+        let quat: na::UnitQuaternion<f64> = self.into();
+        Quaternion::from_unit_quaternion(&quat, order, convention).into()
+    }
+
     pub fn into_rodrigues_format(self) -> Self {
         Rodrigues::from(self).into()
     }
 
+    pub fn into_mrp_format(self) -> Self {
+        Mrp::from(self).into()
+    }
+
+    /// Convert to Gibbs vector format. It fails if the rotation angle
+    /// is 180 degrees, where the Gibbs vector is infinite.
+    pub fn into_gibbs_format(self) -> Result<Self> {
+        Ok(Gibbs::try_from(self)?.into())
+    }
+
     pub fn into_rotation_matrix_format(self) -> Self {
         RotationMatrix::from(self).into()
     }
 }
 
 /// Rotation represented as Euler angles.
+///
+/// The angles can be serialized as plain numbers along with an
+/// `angle_unit` field. See [SerdeOptions](crate::SerdeOptions).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedEuler", into = "SerializedEuler")]
 pub struct Euler {
     pub order: EulerAxisOrder,
     pub angles: Vec<Angle>,
+    pub convention: EulerConvention,
 }
 
 impl Euler {
+    /// Decompose a quaternion into Euler angles in the given axis
+    /// order and convention.
+    ///
+    /// The order must be a three-axis sequence without consecutive
+    /// repeated axes. It can be either a Tait-Bryan sequence like
+    /// "rpy" or a proper Euler sequence like "zxz".
+    pub fn from_quaternion(
+        quat: &na::UnitQuaternion<f64>,
+        order: EulerAxisOrder,
+        convention: EulerConvention,
+    ) -> Result<Self> {
+        if !order.is_valid_sequence() {
+            bail!("Euler order '{order}' is not a valid three-axis sequence");
+        }
+        let [first, second, third] = order.0[..] else {
+            unreachable!();
+        };
+
+        let angles =
+            crate::conv_impl::quaternion_to_euler_angles(quat, [first, second, third], convention);
+
+        Ok(Self {
+            order,
+            angles: angles
+                .into_iter()
+                .map(|ang| Angle::from_radians(r64(ang)))
+                .collect(),
+            convention,
+        })
+    }
+
     pub fn normalize(&self) -> Self {
         let quat: na::UnitQuaternion<f64> = self.clone().into();
-        let Euler { order, angles } = quat.into();
+        let Euler {
+            order,
+            angles,
+            convention,
+        } = self.decompose_like(&quat);
         let angles: Vec<_> = angles.into_iter().map(|ang| ang.normalize()).collect();
-        Self { order, angles }
+        Self {
+            order,
+            angles,
+            convention,
+        }
     }
 
     pub fn inverse(&self) -> Self {
         let quat: na::UnitQuaternion<f64> = self.clone().into();
-        quat.inverse().into()
+        self.decompose_like(&quat.inverse())
     }
 
     pub fn into_degrees(self) -> Self {
-        let Self { order, angles } = self;
+        let Self {
+            order,
+            angles,
+            convention,
+        } = self;
         Self {
             order,
             angles: angles.into_iter().map(|ang| ang.to_degrees()).collect(),
+            convention,
         }
     }
 
     pub fn into_radians(self) -> Self {
-        let Self { order, angles } = self;
+        let Self {
+            order,
+            angles,
+            convention,
+        } = self;
         Self {
             order,
             angles: angles.into_iter().map(|ang| ang.to_radians()).collect(),
+            convention,
+        }
+    }
+
+    pub fn into_angle_unit(self, unit: AngleUnit) -> Self {
+        let Self {
+            order,
+            angles,
+            convention,
+        } = self;
+        Self {
+            order,
+            angles: angles.into_iter().map(|ang| ang.to_unit(unit)).collect(),
+            convention,
+        }
+    }
+
+    /// Decompose a quaternion into roll-pitch-yaw angles using the
+    /// convention of this rotation.
+    pub(crate) fn decompose_like(&self, quat: &na::UnitQuaternion<f64>) -> Self {
+        let order = [EulerAxis::Roll, EulerAxis::Pitch, EulerAxis::Yaw];
+        let angles = crate::conv_impl::quaternion_to_euler_angles(quat, order, self.convention);
+
+        Self {
+            order: EulerAxisOrder(order.to_vec()),
+            angles: angles
+                .into_iter()
+                .map(|ang| Angle::from_radians(r64(ang)))
+                .collect(),
+            convention: self.convention,
         }
     }
 }
 
+/// The convention how Euler angles are chained.
+///
+/// The extrinsic convention rotates about the fixed axes of the
+/// original frame in the listed order, while the intrinsic convention
+/// rotates about the axes of the rotating frame. For example, "rpy"
+/// in extrinsic convention equals to "ypr" in intrinsic convention
+/// with reversed angles. The extrinsic convention is used by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EulerConvention {
+    Intrinsic,
+    #[default]
+    Extrinsic,
+}
+
+impl EulerConvention {
+    pub fn is_extrinsic(&self) -> bool {
+        *self == Self::Extrinsic
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedEuler {
+    order: EulerAxisOrder,
+    angles: Vec<SerializedAngle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    angle_unit: Option<AngleUnit>,
+    #[serde(default, skip_serializing_if = "EulerConvention::is_extrinsic")]
+    convention: EulerConvention,
+}
+
+impl TryFrom<SerializedEuler> for Euler {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedEuler) -> Result<Self, Self::Error> {
+        let SerializedEuler {
+            order,
+            angles,
+            angle_unit,
+            convention,
+        } = from;
+        let angles = angles
+            .into_iter()
+            .map(|angle| angle.resolve(angle_unit))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            order,
+            angles,
+            convention,
+        })
+    }
+}
+
+impl From<Euler> for SerializedEuler {
+    fn from(from: Euler) -> Self {
+        let Euler {
+            order,
+            angles,
+            convention,
+        } = from;
+        let first = angles.first().map(|angle| angle.unit);
+        let (angles, angle_unit) = SerializedAngle::from_angles(&angles, first);
+
+        Self {
+            order,
+            angles,
+            angle_unit,
+            convention,
+        }
+    }
+}
+
+/// Give the serialized rotation the angle unit unless it has its own
+/// `angle_unit` field. It's how a document-level `angle_unit` field
+/// applies to the rotations in the document.
+pub(crate) fn insert_angle_unit(rot: &mut serde_json::Value, unit: AngleUnit) {
+    if let Some(fields) = rot.as_object_mut() {
+        fields
+            .entry("angle_unit")
+            .or_insert_with(|| serde_json::json!(unit));
+    }
+}
+
+/// An angle written as either a suffixed string or a plain number in
+/// the unit given by the sibling `angle_unit` field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum SerializedAngle {
+    Number(R64),
+    Angle(Angle),
+}
+
+impl<'de> Deserialize<'de> for SerializedAngle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Serialized {
+            Number(R64),
+            Text(String),
+        }
+
+        match Serialized::deserialize(deserializer)? {
+            Serialized::Number(value) => Ok(Self::Number(value)),
+            Serialized::Text(text) => text
+                .parse()
+                .map(Self::Angle)
+                .map_err(|err| D::Error::custom(format!("{err}"))),
+        }
+    }
+}
+
+impl SerializedAngle {
+    fn resolve(self, angle_unit: Option<AngleUnit>) -> Result<Angle> {
+        match self {
+            SerializedAngle::Angle(angle) => Ok(angle),
+            SerializedAngle::Number(value) => {
+                let Some(unit) = angle_unit else {
+                    bail!("the unit of the numeric angle '{value}' is unknown, please specify 'angle_unit'");
+                };
+                Ok(Angle { unit, value })
+            }
+        }
+    }
+
+    /// Convert angles to the serialized form. The angles are written
+    /// as numbers in the unit if numeric angles are enabled.
+    fn from_angles(angles: &[Angle], unit: Option<AngleUnit>) -> (Vec<Self>, Option<AngleUnit>) {
+        if !serde_options().numeric_angles {
+            let angles = angles.iter().map(|&angle| Self::Angle(angle)).collect();
+            return (angles, None);
+        }
+
+        // Numeric DMS angles are plain degrees.
+        let unit = match unit.unwrap_or(AngleUnit::Radian) {
+            AngleUnit::Dms => AngleUnit::Degree,
+            unit => unit,
+        };
+        let angles = angles
+            .iter()
+            .map(|angle| Self::Number(serde_options::round_angle(angle.to_unit(unit)).value))
+            .collect();
+        (angles, Some(unit))
+    }
+}
+
 /// Rotation represented as a quaternion.
+///
+/// The components are always stored in i, j, k, w order. The `order`
+/// field only determines the component layout when serialized.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedQuaternion", into = "SerializedQuaternion")]
 pub struct Quaternion {
     pub ijkw: [R64; 4],
+    pub order: QuaternionOrder,
+    pub convention: QuaternionConvention,
 }
 
 impl Quaternion {
+    /// Create a quaternion with the specified component order and
+    /// convention.
+    pub fn from_unit_quaternion(
+        quat: &na::UnitQuaternion<f64>,
+        order: QuaternionOrder,
+        convention: QuaternionConvention,
+    ) -> Self {
+        Self::from(*quat)
+            .with_order(order)
+            .to_convention(convention)
+    }
+
     pub fn inverse(&self) -> Self {
         let quat: na::UnitQuaternion<f64> = self.clone().into();
-        quat.inverse().into()
+        Self::from_unit_quaternion(&quat.inverse(), self.order, self.convention)
+    }
+
+    /// Get the components in w, i, j, k order.
+    pub fn wijk(&self) -> [R64; 4] {
+        let [i, j, k, w] = self.ijkw;
+        [w, i, j, k]
+    }
+
+    /// Change the component order used in serialization.
+    pub fn with_order(self, order: QuaternionOrder) -> Self {
+        Self { order, ..self }
+    }
+
+    /// Convert the components to the specified convention. It
+    /// conjugates the quaternion if the convention is changed.
+    pub fn to_convention(&self, convention: QuaternionConvention) -> Self {
+        let ijkw = if self.convention == convention {
+            self.ijkw
+        } else {
+            let [i, j, k, w] = self.ijkw;
+            [-i, -j, -k, w]
+        };
+
+        Self {
+            ijkw,
+            order: self.order,
+            convention,
+        }
+    }
+}
+
+/// The component order of a quaternion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QuaternionOrder {
+    /// The vector part comes first, used by ROS and Eigen storage.
+    #[default]
+    Xyzw,
+    /// The scalar part comes first, used by COLMAP and Eigen
+    /// constructors.
+    Wxyz,
+}
+
+/// The quaternion convention.
+///
+/// The Hamilton convention is used by default. The JPL quaternion
+/// with the same components represents the inverse rotation of the
+/// Hamilton quaternion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuaternionConvention {
+    #[default]
+    Hamilton,
+    Jpl,
+}
+
+impl QuaternionConvention {
+    pub fn is_hamilton(&self) -> bool {
+        *self == Self::Hamilton
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedQuaternion {
+    #[serde(
+        default,
+        alias = "xyzw",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_options::serialize",
+        deserialize_with = "expr::deserialize"
+    )]
+    ijkw: Option<[R64; 4]>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serde_options::serialize",
+        deserialize_with = "expr::deserialize"
+    )]
+    wxyz: Option<[R64; 4]>,
+    #[serde(default, skip_serializing_if = "QuaternionConvention::is_hamilton")]
+    convention: QuaternionConvention,
+}
+
+impl TryFrom<SerializedQuaternion> for Quaternion {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedQuaternion) -> Result<Self, Self::Error> {
+        let SerializedQuaternion {
+            ijkw,
+            wxyz,
+            convention,
+        } = from;
+
+        let (ijkw, order) = match (ijkw, wxyz) {
+            (Some(ijkw), None) => (ijkw, QuaternionOrder::Xyzw),
+            (None, Some([w, i, j, k])) => ([i, j, k, w], QuaternionOrder::Wxyz),
+            (Some(_), Some(_)) => bail!("only one of 'ijkw' and 'wxyz' can be specified"),
+            (None, None) => bail!("expect either 'ijkw' or 'wxyz' field"),
+        };
+
+        Ok(Self {
+            ijkw,
+            order,
+            convention,
+        })
+    }
+}
+
+impl From<Quaternion> for SerializedQuaternion {
+    fn from(from: Quaternion) -> Self {
+        let (ijkw, wxyz) = match from.order {
+            QuaternionOrder::Xyzw => (Some(from.ijkw), None),
+            QuaternionOrder::Wxyz => (None, Some(from.wijk())),
+        };
+
+        Self {
+            ijkw,
+            wxyz,
+            convention: from.convention,
+        }
     }
 }
 
 /// Rotation represented in axis-angle format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedAxisAngle", into = "SerializedAxisAngle")]
 pub struct AxisAngle {
-    pub axis: [Length; 3],
+    pub axis: [R64; 3],
     pub angle: Angle,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedAxisAngle {
+    #[serde(serialize_with = "serde_options::serialize")]
+    axis: [R64; 3],
+    angle: SerializedAngle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    angle_unit: Option<AngleUnit>,
+}
+
+impl TryFrom<SerializedAxisAngle> for AxisAngle {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedAxisAngle) -> Result<Self, Self::Error> {
+        let SerializedAxisAngle {
+            axis,
+            angle,
+            angle_unit,
+        } = from;
+
+        Ok(Self {
+            axis,
+            angle: angle.resolve(angle_unit)?,
+        })
+    }
+}
+
+impl From<AxisAngle> for SerializedAxisAngle {
+    fn from(from: AxisAngle) -> Self {
+        let AxisAngle { axis, angle } = from;
+        let (mut angles, angle_unit) = SerializedAngle::from_angles(&[angle], Some(angle.unit));
+
+        Self {
+            axis,
+            angle: angles.remove(0),
+            angle_unit,
+        }
+    }
+}
+
 impl AxisAngle {
     pub fn normalize(&self) -> Self {
         let Self { axis, angle } = *self;
@@ -164,23 +743,139 @@ impl AxisAngle {
             angle: angle.to_radians(),
         }
     }
+
+    pub fn into_angle_unit(self, unit: AngleUnit) -> Self {
+        let Self { axis, angle } = self;
+        Self {
+            axis,
+            angle: angle.to_unit(unit),
+        }
+    }
 }
 
+/// Rotation represented as a 3x3 matrix in row-major order.
+///
+/// The matrix is validated by [RotationMatrix::validate] when it is
+/// deserialized.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedRotationMatrix")]
 pub struct RotationMatrix {
+    #[serde(serialize_with = "serde_options::serialize")]
     pub matrix: [[R64; 3]; 3],
 }
 
+/// The rotation matrix before validation.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct SerializedRotationMatrix {
+    #[serde(deserialize_with = "expr::deserialize")]
+    pub(crate) matrix: [[R64; 3]; 3],
+}
+
+impl TryFrom<SerializedRotationMatrix> for RotationMatrix {
+    type Error = RotationMatrixError;
+
+    fn try_from(from: SerializedRotationMatrix) -> Result<Self, Self::Error> {
+        let rot = Self {
+            matrix: from.matrix,
+        };
+        rot.validate()?;
+        Ok(rot)
+    }
+}
+
 impl RotationMatrix {
+    /// The default tolerance of the orthogonality error. It accepts
+    /// matrices typed with 4 decimals, such as 0.7071 for the cosine
+    /// of 45 degrees.
+    ///
+    /// It was 1e-5 before, which rejected such matrices. Matrices
+    /// within 1e-3 that were rejected are now accepted as they are.
+    pub const TOLERANCE: f64 = 1e-3;
+
     pub fn inverse(&self) -> Self {
         let quat: na::UnitQuaternion<f64> = self.clone().into();
         quat.inverse().into()
     }
+
+    /// Check if the matrix is orthonormal with determinant +1 within
+    /// the default tolerance.
+    pub fn validate(&self) -> Result<(), RotationMatrixError> {
+        self.validate_with_tolerance(Self::TOLERANCE)
+    }
+
+    /// Check if the matrix is orthonormal with determinant +1. The
+    /// orthogonality error is measured by the Frobenius norm of
+    /// `R^T R - I`.
+    pub fn validate_with_tolerance(&self, tolerance: f64) -> Result<(), RotationMatrixError> {
+        let determinant = self.determinant();
+        if determinant < 0.0 {
+            return Err(RotationMatrixError::Reflection { determinant });
+        }
+
+        let error = self.orthogonality_error();
+        if error > tolerance {
+            return Err(RotationMatrixError::NotOrthonormal { error, tolerance });
+        }
+
+        Ok(())
+    }
+
+    /// Project the matrix onto the nearest rotation matrix in
+    /// Frobenius norm using SVD, and report how far the input was from
+    /// a valid rotation.
+    pub fn repair(&self) -> (Self, RotationMatrixRepair) {
+        let mat = self.to_na_matrix();
+        let na::SVD { u, v_t, .. } = mat.svd(true, true);
+        let u = u.unwrap();
+        let v_t = v_t.unwrap();
+
+        // Flip the least significant axis to avoid reflection.
+        let sign = (u * v_t).determinant().signum();
+        let repaired = u * na::Matrix3::from_diagonal(&na::Vector3::new(1.0, 1.0, sign)) * v_t;
+
+        let report = RotationMatrixRepair {
+            orthogonality_error: self.orthogonality_error(),
+            determinant: self.determinant(),
+            distance: (repaired - mat).norm(),
+        };
+        let matrix = [0, 1, 2].map(|row| [0, 1, 2].map(|col| r64(repaired[(row, col)])));
+
+        (Self { matrix }, report)
+    }
+
+    /// The Frobenius norm of `R^T R - I`.
+    pub fn orthogonality_error(&self) -> f64 {
+        let mat = self.to_na_matrix();
+        (mat.transpose() * mat - na::Matrix3::identity()).norm()
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.to_na_matrix().determinant()
+    }
+
+    fn to_na_matrix(&self) -> na::Matrix3<f64> {
+        na::Matrix3::from_row_iterator(self.matrix.iter().flatten().map(|elem| elem.raw()))
+    }
+}
+
+/// The report of [RotationMatrix::repair].
+#[derive(Debug, Clone, Copy)]
+pub struct RotationMatrixRepair {
+    /// The Frobenius norm of `R^T R - I` of the input matrix.
+    pub orthogonality_error: f64,
+    /// The determinant of the input matrix.
+    pub determinant: f64,
+    /// The Frobenius distance from the input to the repaired matrix.
+    pub distance: f64,
 }
 
 /// Rotation represented in Rodrigues format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rodrigues {
+    #[serde(
+        serialize_with = "serde_options::serialize",
+        deserialize_with = "expr::deserialize"
+    )]
     pub params: [R64; 3],
 }
 
@@ -205,24 +900,131 @@ impl Rodrigues {
     }
 }
 
+/// Rotation represented in modified Rodrigues parameters (MRP).
+///
+/// The parameters are `axis * tan(angle / 4)`. Every rotation has two
+/// MRP representations, the parameters `p` and its shadow `-p /
+/// |p|^2`. The normalized form is the one with `|p| <= 1`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mrp {
+    #[serde(serialize_with = "serde_options::serialize")]
+    pub params: [R64; 3],
+}
+
+impl Mrp {
+    /// Returns the squared norm of the parameters.
+    pub fn norm_squared(&self) -> f64 {
+        self.params.iter().map(|val| val.raw().powi(2)).sum()
+    }
+
+    /// Returns the shadow parameters `-p / |p|^2` representing the
+    /// same rotation. The zero vector is its own shadow.
+    pub fn shadow(&self) -> Self {
+        let norm_sq = self.norm_squared();
+        if norm_sq == 0.0 {
+            return self.clone();
+        }
+        Self {
+            params: self.params.map(|val| -val / norm_sq),
+        }
+    }
+
+    /// Switch to the shadow set if `|p| > 1`.
+    pub fn normalize(&self) -> Self {
+        if self.norm_squared() > 1.0 {
+            self.shadow()
+        } else {
+            self.clone()
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            params: self.params.map(|val| -val),
+        }
+    }
+}
+
+/// Rotation represented in Gibbs vector, also known as the Cayley or
+/// classical Rodrigues parameters.
+///
+/// The parameters are `axis * tan(angle / 2)`, which is undefined for
+/// rotations of 180 degrees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gibbs {
+    #[serde(serialize_with = "serde_options::serialize")]
+    pub params: [R64; 3],
+}
+
+impl Gibbs {
+    pub fn inverse(&self) -> Self {
+        Self {
+            params: self.params.map(|val| -val),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EulerAxisOrder(pub Vec<EulerAxis>);
 
-impl ToString for EulerAxisOrder {
-    fn to_string(&self) -> String {
-        self.0.iter().map(|axis| axis.to_char()).collect()
+/// Write the order in "r", "p", "y" letters, or in "x", "y", "z" axis
+/// letters if it's a proper Euler sequence. The notation of the parsed
+/// text is not kept, so "xyz" is written as "rpy". Either way the text
+/// parses back to the same order.
+impl Display for EulerAxisOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Roll, pitch and yaw are names for Tait-Bryan angles. The
+        // proper Euler sequences, which repeat an axis, are written
+        // in axis letters instead.
+        let text: String = if self.is_proper() {
+            self.0.iter().map(|axis| axis.to_axis_char()).collect()
+        } else {
+            self.0.iter().map(|axis| axis.to_char()).collect()
+        };
+        f.write_str(&text)
     }
 }
 
 impl FromStr for EulerAxisOrder {
     type Err = anyhow::Error;
 
+    /// Parse the axis order written in either "r", "p", "y" letters
+    /// or "x", "y", "z" axis letters. One notation is used for the
+    /// whole text: it's in axis letters if it contains "x" or "z", and
+    /// in "r", "p", "y" letters otherwise. So "y" alone is yaw, while
+    /// the "y" in "yxy" is the pitch axis. Mixing the notations, as
+    /// in "xpy", is an error.
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let result: Result<Vec<_>, _> = text.chars().map(EulerAxis::from_char).collect();
+        let is_axis_letters = text.contains(['x', 'z']);
+        if is_axis_letters && text.contains(['r', 'p']) {
+            bail!(
+                "Euler order '{text}' mixes 'r', 'p', 'y' letters and 'x', 'y', 'z' axis letters"
+            );
+        }
+
+        let result: Result<Vec<_>, _> = if is_axis_letters {
+            text.chars().map(EulerAxis::from_axis_char).collect()
+        } else {
+            text.chars().map(EulerAxis::from_char).collect()
+        };
         Ok(Self(result?))
     }
 }
 
+impl EulerAxisOrder {
+    /// Check if the order is a proper Euler sequence, such as "zxz",
+    /// in which the first and the last axes are identical.
+    pub fn is_proper(&self) -> bool {
+        matches!(self.0[..], [first, second, third] if first == third && first != second)
+    }
+
+    /// Check if the order is a valid three-axis sequence, in which
+    /// no two consecutive axes are identical.
+    pub fn is_valid_sequence(&self) -> bool {
+        matches!(self.0[..], [first, second, third] if first != second && second != third)
+    }
+}
+
 impl Serialize for EulerAxisOrder {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -261,6 +1063,17 @@ impl EulerAxis {
         Ok(axis)
     }
 
+    /// Parse the axis letter "x", "y" or "z".
+    pub fn from_axis_char(code: char) -> Result<Self> {
+        let axis = match code {
+            'x' => Self::Roll,
+            'y' => Self::Pitch,
+            'z' => Self::Yaw,
+            _ => bail!("unexpected axis letter '{code}'"),
+        };
+        Ok(axis)
+    }
+
     pub fn to_char(&self) -> char {
         match self {
             EulerAxis::Roll => 'r',
@@ -268,14 +1081,37 @@ impl EulerAxis {
             EulerAxis::Yaw => 'y',
         }
     }
+
+    pub fn to_axis_char(&self) -> char {
+        match self {
+            EulerAxis::Roll => 'x',
+            EulerAxis::Pitch => 'y',
+            EulerAxis::Yaw => 'z',
+        }
+    }
+
+    /// The index of the axis in x, y, z order.
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            EulerAxis::Roll => 0,
+            EulerAxis::Pitch => 1,
+            EulerAxis::Yaw => 2,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Angle, Euler, EulerAxis, EulerAxisOrder, Rotation};
+    use super::{
+        Angle, Euler, EulerAxis, EulerAxisOrder, EulerConvention, Rotation, RotationMatrix,
+    };
+    use crate::error::RotationMatrixError;
     use crate::unit::AngleUnit;
     use approx::assert_abs_diff_eq;
+    use itertools::iproduct;
+    use nalgebra as na;
     use noisy_float::types::r64;
+    use std::f64::consts::{FRAC_PI_2, PI};
 
     #[test]
     fn rotation_convert() {
@@ -285,6 +1121,7 @@ mod tests {
                 unit: AngleUnit::Degree,
                 value: r64(10.0),
             }],
+            convention: EulerConvention::Extrinsic,
         }
         .into();
 
@@ -308,6 +1145,7 @@ mod tests {
         let Rotation::Euler(Euler {
             order: EulerAxisOrder(order),
             angles,
+            ..
         }) = rot
         else {
             panic!("expect Euler variant");
@@ -333,4 +1171,100 @@ mod tests {
         assert_abs_diff_eq!(pitch, Angle::zero(), epsilon = 1e-5);
         assert_abs_diff_eq!(yaw, Angle::zero(), epsilon = 1e-5);
     }
+
+    #[test]
+    fn euler_decomposition() {
+        let quats = [
+            na::UnitQuaternion::identity(),
+            na::UnitQuaternion::from_euler_angles(0.3, -1.2, 2.5),
+            na::UnitQuaternion::from_euler_angles(-2.1, 0.7, -0.4),
+            na::UnitQuaternion::from_euler_angles(0.5, FRAC_PI_2, 0.2),
+            na::UnitQuaternion::from_euler_angles(-0.5, -FRAC_PI_2, 1.2),
+            na::UnitQuaternion::from_euler_angles(FRAC_PI_2, 0.3, 0.2),
+            na::UnitQuaternion::from_euler_angles(0.1, -0.3, -FRAC_PI_2),
+            na::UnitQuaternion::from_euler_angles(PI, 0.0, 0.4),
+            na::UnitQuaternion::from_euler_angles(0.0, PI, -0.7),
+        ];
+        let orders = [
+            "rpy", "ryp", "pry", "pyr", "yrp", "ypr", "xyx", "xzx", "yxy", "yzy", "zxz", "zyz",
+        ];
+        let conventions = [EulerConvention::Extrinsic, EulerConvention::Intrinsic];
+
+        for (quat, order, convention) in iproduct!(quats, orders, conventions) {
+            let order: EulerAxisOrder = order.parse().unwrap();
+            let euler = Euler::from_quaternion(&quat, order.clone(), convention).unwrap();
+            assert_eq!(euler.order, order);
+            assert_eq!(euler.convention, convention);
+
+            let quat2: na::UnitQuaternion<f64> = euler.into();
+            assert_abs_diff_eq!(quat.angle_to(&quat2), 0.0, epsilon = 1e-6);
+        }
+
+        for order in ["rp", "rrp", "xyzx"] {
+            let order: EulerAxisOrder = order.parse().unwrap();
+            assert!(Euler::from_quaternion(&quats[1], order, EulerConvention::Extrinsic).is_err());
+        }
+    }
+
+    #[test]
+    fn parse_euler_order() {
+        use EulerAxis::{Pitch, Roll, Yaw};
+
+        let order: EulerAxisOrder = "rpy".parse().unwrap();
+        assert_eq!(order.0, vec![Roll, Pitch, Yaw]);
+        assert_eq!(order.to_string(), "rpy");
+
+        let order: EulerAxisOrder = "xyz".parse().unwrap();
+        assert_eq!(order.0, vec![Roll, Pitch, Yaw]);
+        assert_eq!(order.to_string(), "rpy");
+
+        let order: EulerAxisOrder = "zyz".parse().unwrap();
+        assert_eq!(order.0, vec![Yaw, Pitch, Yaw]);
+        assert!(order.is_proper());
+        assert_eq!(order.to_string(), "zyz");
+
+        let order: EulerAxisOrder = "y".parse().unwrap();
+        assert_eq!(order.0, vec![Yaw]);
+        let order: EulerAxisOrder = "yxy".parse().unwrap();
+        assert_eq!(order.0, vec![Pitch, Roll, Pitch]);
+        assert_eq!(order.to_string(), "yxy");
+
+        // Every written order parses back to itself.
+        for text in ["rpy", "ypr", "y", "yp", "xyz", "zyx", "zyz", "yxy", "xzx"] {
+            let order: EulerAxisOrder = text.parse().unwrap();
+            assert_eq!(order.to_string().parse::<EulerAxisOrder>().unwrap(), order);
+        }
+
+        assert!("xpy".parse::<EulerAxisOrder>().is_err());
+        assert!("rpz".parse::<EulerAxisOrder>().is_err());
+    }
+
+    #[test]
+    fn rotation_matrix_repair() {
+        let to_matrix = |rows: [[f64; 3]; 3]| RotationMatrix {
+            matrix: rows.map(|row| row.map(r64)),
+        };
+
+        let identity = to_matrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert!(identity.validate().is_ok());
+
+        let reflection = to_matrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]]);
+        assert!(matches!(
+            reflection.validate(),
+            Err(RotationMatrixError::Reflection { .. })
+        ));
+
+        let skewed = to_matrix([[1.0, 0.01, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert!(matches!(
+            skewed.validate(),
+            Err(RotationMatrixError::NotOrthonormal { .. })
+        ));
+
+        for input in [reflection, skewed] {
+            let (output, report) = input.repair();
+            assert!(output.validate().is_ok());
+            assert!(report.distance > 0.0);
+            assert_abs_diff_eq!(output.determinant(), 1.0, epsilon = 1e-9);
+        }
+    }
 }
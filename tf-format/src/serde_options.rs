@@ -0,0 +1,175 @@
+use crate::Angle;
+use noisy_float::types::R64;
+use serde::{Serialize, Serializer};
+use std::{cell::Cell, num::NonZeroUsize};
+
+/// Options of how values are serialized. They don't affect
+/// deserialization, which takes the angle unit from the document or
+/// from [MaybeTransformSeed](crate::MaybeTransformSeed).
+///
+/// The options take effect on serde calls within
+/// [with_serde_options] on the current thread.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeOptions {
+    /// Write angles as plain numbers along with a sibling
+    /// `angle_unit` field instead of suffixed strings.
+    pub numeric_angles: bool,
+    /// Round numbers and angles to the precision on output.
+    pub precision: Option<Precision>,
+    /// Snap numbers within the distance to 0 or ±1, and angles within
+    /// the distance to multiples of 15 degrees, on output. The
+    /// distance is measured in the unit of the value.
+    pub snap_eps: Option<f64>,
+}
+
+/// The precision of numbers on output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// The number of digits after the decimal point.
+    Decimals(usize),
+    /// The number of significant digits, which is at least 1.
+    Significant(NonZeroUsize),
+}
+
+impl Precision {
+    pub fn round(&self, value: f64) -> f64 {
+        // Round through the decimal text, which is correctly rounded
+        // unlike scaling by powers of 10.
+        let text = match *self {
+            Precision::Decimals(digits) => format!("{value:.digits$}"),
+            Precision::Significant(digits) => {
+                format!("{value:.prec$e}", prec = digits.get() - 1)
+            }
+        };
+        text.parse().unwrap_or(value)
+    }
+}
+
+thread_local! {
+    static OPTIONS: Cell<SerdeOptions> = Cell::new(SerdeOptions::default());
+}
+
+/// Run the closure with the serde options. The previous options are
+/// restored afterwards.
+pub fn with_serde_options<T>(options: SerdeOptions, f: impl FnOnce() -> T) -> T {
+    struct Guard(SerdeOptions);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            OPTIONS.with(|opts| opts.set(self.0));
+        }
+    }
+
+    let _guard = Guard(OPTIONS.with(|opts| opts.replace(options)));
+    f()
+}
+
+pub(crate) fn serde_options() -> SerdeOptions {
+    OPTIONS.with(|opts| opts.get())
+}
+
+impl SerdeOptions {
+    /// Round the number as it's written on output.
+    pub fn round_number(&self, value: f64) -> f64 {
+        let Self {
+            precision,
+            snap_eps,
+            ..
+        } = *self;
+        let mut value = value;
+
+        if let Some(eps) = snap_eps {
+            if let Some(target) = [0.0, 1.0, -1.0]
+                .into_iter()
+                .find(|target| (value - target).abs() <= eps)
+            {
+                value = target;
+            }
+        }
+
+        finish(value, precision)
+    }
+
+    /// Round the angle as it's written on output.
+    pub fn round_angle(&self, angle: Angle) -> Angle {
+        let Self {
+            precision,
+            snap_eps,
+            ..
+        } = *self;
+        let Angle { unit, value } = angle;
+        let mut value = value.raw();
+
+        if let Some(eps) = snap_eps {
+            let step = unit.full_turn() / 24.0;
+            let target = (value / step).round() * step;
+            if (value - target).abs() <= eps {
+                value = target;
+            }
+        }
+
+        Angle {
+            unit,
+            value: R64::try_new(finish(value, precision)).unwrap(),
+        }
+    }
+}
+
+/// Round the number according to the current options.
+pub(crate) fn round_number(value: R64) -> R64 {
+    R64::try_new(serde_options().round_number(value.raw())).unwrap()
+}
+
+/// Round the angle according to the current options.
+pub(crate) fn round_angle(angle: Angle) -> Angle {
+    serde_options().round_angle(angle)
+}
+
+fn finish(value: f64, precision: Option<Precision>) -> f64 {
+    let value = match precision {
+        Some(precision) => precision.round(value),
+        None => value,
+    };
+
+    // Avoid writing "-0.0".
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Types whose numbers are rounded on output. It's used with
+/// `#[serde(serialize_with = "crate::serde_options::serialize")]`.
+pub(crate) trait RoundScalars {
+    fn round_scalars(&self) -> Self;
+}
+
+impl<const N: usize> RoundScalars for [R64; N] {
+    fn round_scalars(&self) -> Self {
+        self.map(round_number)
+    }
+}
+
+impl<const N: usize, const M: usize> RoundScalars for [[R64; N]; M] {
+    fn round_scalars(&self) -> Self {
+        self.map(|row| row.map(round_number))
+    }
+}
+
+impl<T> RoundScalars for Option<T>
+where
+    T: RoundScalars,
+{
+    fn round_scalars(&self) -> Self {
+        self.as_ref().map(T::round_scalars)
+    }
+}
+
+pub(crate) fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: RoundScalars + Serialize,
+{
+    value.round_scalars().serialize(serializer)
+}
@@ -1,122 +1,288 @@
-use crate::{AxisAngle, Euler, Quaternion, Rodrigues, Rotation, RotationMatrix};
+use crate::{
+    covariance, lie,
+    rotation::{insert_angle_unit, SerializedRotationMatrix},
+    serde_options,
+    unit::{AngleUnit, Length, LengthUnit},
+    Covariance, NonFiniteError, Rotation, RotationMatrix, RotationMatrixError,
+    RotationMatrixRepair,
+};
+use anyhow::{bail, ensure, Result};
+use itertools::izip;
 use nalgebra as na;
-use noisy_float::types::R64;
-use num::NumCast;
-use serde::{Deserialize, Serialize};
+use noisy_float::types::{r64, R64};
+use num::{NumCast, One, Zero};
+use serde::{de::DeserializeSeed, de::Error as _, Deserialize, Deserializer, Serialize};
 
 /// Represent a rigid transformation in 3D Euclidean space.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transform {
     pub r: Rotation,
     pub t: Translation,
+    /// The optional uncertainty of the transform.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cov: Option<Covariance>,
 }
 
 impl Transform {
+    /// Convert from an isometry. Unlike the `From` conversion, it
+    /// returns an error instead of panicking if any component is NaN
+    /// or infinite.
+    ///
+    /// It's an inherent method rather than a `TryFrom` impl, because
+    /// the infallible `From` impl already implies `TryFrom` through
+    /// the blanket impl in `core`, and the two would conflict. The
+    /// same goes for the other `try_from_na_*` methods.
+    pub fn try_from_na_isometry3<T>(iso: &na::Isometry3<T>) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        NonFiniteError::check_isometry(iso)?;
+        Ok(iso.clone().into())
+    }
+
+    /// Attach the covariance to the transform.
+    pub fn with_covariance(self, cov: Covariance) -> Self {
+        Self {
+            cov: Some(cov),
+            ..self
+        }
+    }
+
     pub fn normalize_rotation(&self) -> Self {
-        let Self { r, t } = self;
+        let Self { r, t, cov } = self;
         Self {
             r: r.normalize(),
             t: *t,
+            cov: cov.clone(),
         }
     }
 
+    /// Check if the rotation is valid.
+    pub fn validate(&self) -> Result<(), RotationMatrixError> {
+        self.r.validate()
+    }
+
+    /// Project the rotation onto the nearest valid rotation. See
+    /// [Rotation::repair].
+    pub fn repair_rotation(&self) -> (Self, Option<RotationMatrixRepair>) {
+        let (r, report) = self.r.repair();
+        let tf = Self {
+            r,
+            t: self.t,
+            cov: self.cov.clone(),
+        };
+        (tf, report)
+    }
+
+    /// Interpolate between two transforms. The rotation is
+    /// interpolated by [Rotation::slerp] and the translation is
+    /// linearly interpolated. The result is in the rotation format and
+    /// length units of `self` without the covariance.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the result is NaN or infinite. Use
+    /// [Transform::try_interpolate] to get an error instead.
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self.try_interpolate(other, t).unwrap()
+    }
+
+    /// Like [Transform::interpolate], but it returns an error instead
+    /// of panicking if the result is NaN or infinite.
+    pub fn try_interpolate(&self, other: &Self, t: f64) -> Result<Self, NonFiniteError> {
+        let lhs = self.t.to_na_vector3();
+        let rhs = other.t.to_na_vector3();
+
+        Ok(Self {
+            r: self.r.try_slerp(&other.r, t)?,
+            t: self.t.try_convert_like(&lhs.lerp(&rhs, t))?,
+            cov: None,
+        })
+    }
+
+    /// Screw linear interpolation (ScLERP) between two transforms,
+    /// which moves along the constant screw motion from `self` to
+    /// `other`. The result is in the rotation format and length units
+    /// of `self` without the covariance.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the result is NaN or infinite. Use
+    /// [Transform::try_sclerp] to get an error instead.
+    pub fn sclerp(&self, other: &Self, t: f64) -> Self {
+        self.try_sclerp(other, t).unwrap()
+    }
+
+    /// Like [Transform::sclerp], but it returns an error instead of
+    /// panicking if the result is NaN or infinite.
+    pub fn try_sclerp(&self, other: &Self, t: f64) -> Result<Self, NonFiniteError> {
+        let lhs: na::Isometry3<f64> = self.clone().into();
+        let rhs: na::Isometry3<f64> = other.clone().into();
+        let delta = lie::se3_log(&(lhs.inverse() * rhs));
+        let na::Isometry3 {
+            rotation,
+            translation,
+        } = lhs * lie::se3_exp(&(delta * t));
+
+        Ok(Self {
+            r: self.r.try_convert_like(&rotation)?,
+            t: self.t.try_convert_like(&translation.vector)?,
+            cov: None,
+        })
+    }
+
+    /// The logarithm map of SE(3). It returns the twist `[rho,
+    /// omega]`, where `omega` is the rotation vector and `rho` is the
+    /// translational part.
+    pub fn log(&self) -> [f64; 6] {
+        let iso: na::Isometry3<f64> = self.clone().into();
+        lie::se3_log(&iso).into()
+    }
+
+    /// The exponential map of SE(3). The twist `[rho, omega]` is
+    /// converted to a transform with quaternion rotation.
+    pub fn exp(twist: [f64; 6]) -> Self {
+        lie::se3_exp(&twist.into()).into()
+    }
+
+    /// Returns the inverse transform. The covariance, if any, is
+    /// propagated to the inverse.
     pub fn inverse(&self) -> Self {
         let iso: na::Isometry3<f64> = self.clone().into();
-        let na::Isometry3 {
-            rotation: rot,
-            translation: trans,
-        } = iso.inverse();
-
-        let rot: Rotation = match self.r {
-            Rotation::Euler(_) => Euler::from(rot).into(),
-            Rotation::Quaternion(_) => Quaternion::from(rot).into(),
-            Rotation::AxisAngle(_) => AxisAngle::from(rot).into(),
-            Rotation::RotationMatrix(_) => RotationMatrix::from(rot).into(),
-            Rotation::Rodrigues(_) => Rodrigues::from(rot).into(),
-        };
+        let inv = iso.inverse();
+        let cov = self.cov.as_ref().map(|cov| {
+            let mat = covariance::inverse_cov(&iso, &cov.to_right_matrix(&iso));
+            cov.convert_like(&mat, &inv)
+        });
 
         Self {
-            r: rot,
-            t: trans.into(),
+            r: self.r.convert_like(&inv.rotation),
+            t: self.t.convert_like(&inv.translation.vector),
+            cov,
         }
     }
 
+    /// Returns the covariance in the right perturbation with `[rho,
+    /// omega]` order.
+    pub(crate) fn right_cov(&self) -> Option<na::Matrix6<f64>> {
+        let cov = self.cov.as_ref()?;
+        let iso: na::Isometry3<f64> = self.clone().into();
+        Some(cov.to_right_matrix(&iso))
+    }
+
     pub fn into_degrees(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_degrees(),
+            cov,
+        }
+    }
+
+    /// Convert the rotation angles to the unit. See
+    /// [Rotation::into_angle_unit].
+    pub fn into_angle_unit(self, unit: AngleUnit) -> Self {
+        let Self { t, r, cov } = self;
+        Self {
+            t,
+            r: r.into_angle_unit(unit),
+            cov,
+        }
+    }
+
+    /// Convert the translation to the length unit.
+    pub fn into_length_unit(self, unit: LengthUnit) -> Self {
+        let Self { t, r, cov } = self;
+        Self {
+            t: t.to_unit(unit),
+            r,
+            cov,
         }
     }
 
     pub fn into_radians(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_radians(),
+            cov,
         }
     }
 
     pub fn into_euler_format(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_euler_format(),
+            cov,
         }
     }
 
     pub fn into_axis_angle_format(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_axis_angle_format(),
+            cov,
         }
     }
 
     pub fn into_quaternion_format(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_quaternion_format(),
+            cov,
         }
     }
 
     pub fn into_rodrigues_format(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_rodrigues_format(),
+            cov,
         }
     }
 
     pub fn into_rotation_matrix_format(self) -> Self {
-        let Self { t, r } = self;
+        let Self { t, r, cov } = self;
         Self {
             t,
             r: r.into_rotation_matrix_format(),
+            cov,
         }
     }
 }
 
 /// Represent either a rigid transformation or a rotation in 3D
 /// Euclidean space.
+///
+/// A rotation with a covariance is written as the rotation in `r`
+/// along with `cov`, without the `t` field.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(from = "SerializedTransform", into = "SerializedTransform")]
+#[serde(try_from = "SerializedTransform", into = "SerializedTransform")]
 pub struct MaybeTransform {
     pub r: Rotation,
     pub t: Option<Translation>,
+    /// The optional uncertainty of the transform.
+    pub cov: Option<Covariance>,
 }
 
 impl From<Transform> for MaybeTransform {
     fn from(tf: Transform) -> Self {
-        let Transform { t, r } = tf;
-        Self { r, t: Some(t) }
+        let Transform { t, r, cov } = tf;
+        Self { r, t: Some(t), cov }
     }
 }
 
 impl From<Rotation> for MaybeTransform {
     fn from(rot: Rotation) -> Self {
-        Self { r: rot, t: None }
+        Self {
+            r: rot,
+            t: None,
+            cov: None,
+        }
     }
 }
 
@@ -124,11 +290,11 @@ impl TryFrom<MaybeTransform> for Transform {
     type Error = MaybeTransform;
 
     fn try_from(tf: MaybeTransform) -> Result<Self, Self::Error> {
-        let MaybeTransform { r, t } = tf;
+        let MaybeTransform { r, t, cov } = tf;
         let Some(t) = t else {
-            return Err(MaybeTransform { t, r });
+            return Err(MaybeTransform { t, r, cov });
         };
-        Ok(Self { t, r })
+        Ok(Self { t, r, cov })
     }
 }
 
@@ -136,20 +302,84 @@ impl TryFrom<MaybeTransform> for Rotation {
     type Error = MaybeTransform;
 
     fn try_from(tf: MaybeTransform) -> Result<Self, Self::Error> {
-        let MaybeTransform { r, t } = tf;
-        if t.is_some() {
-            return Err(MaybeTransform { t, r });
+        let MaybeTransform { r, t, cov } = tf;
+        if t.is_some() || cov.is_some() {
+            return Err(MaybeTransform { t, r, cov });
         }
         Ok(r)
     }
 }
 
 impl MaybeTransform {
+    /// Convert the translation, if any, to the length unit.
+    pub fn into_length_unit(self, unit: LengthUnit) -> Self {
+        let Self { r, t, cov } = self;
+        Self {
+            r,
+            t: t.map(|t| t.to_unit(unit)),
+            cov,
+        }
+    }
+
+    /// Check if the rotation is valid.
+    pub fn validate(&self) -> Result<(), RotationMatrixError> {
+        self.r.validate()
+    }
+
+    /// Project the rotation onto the nearest valid rotation. See
+    /// [Rotation::repair].
+    pub fn repair_rotation(&self) -> (Self, Option<RotationMatrixRepair>) {
+        let (r, report) = self.r.repair();
+        let tf = Self {
+            r,
+            t: self.t,
+            cov: self.cov.clone(),
+        };
+        (tf, report)
+    }
+
+    /// Deserialize the transform, projecting an invalid rotation
+    /// matrix onto the nearest valid rotation instead of rejecting it.
+    /// The report is returned if the rotation is in a matrix format.
+    /// See [Rotation::repair] and [MaybeTransformSeed].
+    pub fn deserialize_repaired<'de, D>(
+        deserializer: D,
+    ) -> Result<(Self, Option<RotationMatrixRepair>), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let seed = MaybeTransformSeed {
+            repair_rotation: true,
+            ..Default::default()
+        };
+        seed.deserialize(deserializer)
+    }
+
+    /// Returns the inverse. The covariance, if any, is propagated to
+    /// the inverse.
+    pub fn inverse(&self) -> Self {
+        let iso = self.to_na_isometry3::<f64>();
+        let inv = iso.inverse();
+        let cov = self.cov.as_ref().map(|cov| {
+            let mat = covariance::inverse_cov(&iso, &cov.to_right_matrix(&iso));
+            cov.convert_like(&mat, &inv)
+        });
+
+        Self {
+            r: self.r.convert_like(&inv.rotation),
+            t: self
+                .t
+                .as_ref()
+                .map(|t| t.convert_like(&inv.translation.vector)),
+            cov,
+        }
+    }
+
     pub fn to_na_isometry3<T>(&self) -> na::Isometry3<T>
     where
         T: na::RealField + NumCast,
     {
-        let Self { r, t } = self;
+        let Self { r, t, .. } = self;
         let rot: na::UnitQuaternion<T> = r.clone().into();
         let trans: na::Translation3<T> = match t {
             Some(t) => (*t).into(),
@@ -157,41 +387,449 @@ impl MaybeTransform {
         };
         na::Isometry3::from_parts(trans, rot)
     }
+
+    /// Returns the covariance in the right perturbation with `[rho,
+    /// omega]` order.
+    pub(crate) fn right_cov(&self) -> Option<na::Matrix6<f64>> {
+        let cov = self.cov.as_ref()?;
+        Some(cov.to_right_matrix(&self.to_na_isometry3()))
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The options to deserialize a [MaybeTransform], which are passed
+/// explicitly as a [DeserializeSeed]. It produces the transform along
+/// with the report of the rotation matrix repair, if any.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaybeTransformSeed {
+    /// The unit of plain numeric angles if the document has no
+    /// `angle_unit` field. It works as if the document had the field.
+    pub default_angle_unit: Option<AngleUnit>,
+    /// Project an invalid rotation matrix onto the nearest valid
+    /// rotation instead of rejecting it. See [Rotation::repair].
+    pub repair_rotation: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for MaybeTransformSeed {
+    type Value = (MaybeTransform, Option<RotationMatrixRepair>);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Self {
+            default_angle_unit,
+            repair_rotation,
+        } = self;
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+
+        if let Some(unit) = default_angle_unit {
+            insert_angle_unit(&mut value, unit);
+        }
+        let report = if repair_rotation {
+            repair_value(&mut value).map_err(D::Error::custom)?
+        } else {
+            None
+        };
+
+        let tf = SerializedTransform::from_value(value).map_err(D::Error::custom)?;
+        let tf = MaybeTransform::try_from(tf).map_err(D::Error::custom)?;
+        Ok((tf, report))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 enum SerializedTransform {
-    Transform(Transform),
+    Transform(Box<Transform>),
+    RotationWithCovariance(Box<SerializedRotationWithCovariance>),
     Rotation(Rotation),
+    Matrix(TransformMatrix),
+    DualQuaternion(DualQuaternion),
+    Twist(Twist),
+}
+
+/// A rotation with a covariance, written like a transform without the
+/// translation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SerializedRotationWithCovariance {
+    r: Rotation,
+    cov: Covariance,
+}
+
+impl<'de> Deserialize<'de> for SerializedTransform {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Self::from_value(value).map_err(D::Error::custom)
+    }
 }
 
-impl From<SerializedTransform> for MaybeTransform {
-    fn from(from: SerializedTransform) -> Self {
-        let (r, t) = match from {
-            SerializedTransform::Transform(Transform { t, r }) => (r, Some(t)),
-            SerializedTransform::Rotation(r) => (r, None),
+impl SerializedTransform {
+    /// Pick the variant by the "r" field or the "format" tag, so that
+    /// the error of the intended variant is reported.
+    fn from_value(mut value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if value.get("r").is_some() {
+            // The document-level "angle_unit" applies to the rotation
+            // unless it has its own.
+            if let Some(unit) = value.get("angle_unit") {
+                let unit: AngleUnit = serde_json::from_value(unit.clone())?;
+                insert_angle_unit(&mut value["r"], unit);
+            }
+            if value.get("t").is_none() && value.get("cov").is_some() {
+                return Ok(Self::RotationWithCovariance(serde_json::from_value(value)?));
+            }
+            return Ok(Self::Transform(serde_json::from_value(value)?));
+        }
+
+        let tf = match value.get("format").and_then(|format| format.as_str()) {
+            Some("homogeneous-matrix" | "affine-matrix") => {
+                Self::Matrix(serde_json::from_value(value)?)
+            }
+            Some("dual-quaternion") => Self::DualQuaternion(serde_json::from_value(value)?),
+            Some("twist") => Self::Twist(serde_json::from_value(value)?),
+            _ => Self::Rotation(serde_json::from_value(value)?),
         };
+        Ok(tf)
+    }
+}
 
-        Self { t, r }
+impl TryFrom<SerializedTransform> for MaybeTransform {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedTransform) -> Result<Self, Self::Error> {
+        let tf = match from {
+            SerializedTransform::Transform(tf) => (*tf).into(),
+            SerializedTransform::RotationWithCovariance(rot) => {
+                let SerializedRotationWithCovariance { r, cov } = *rot;
+                MaybeTransform {
+                    r,
+                    t: None,
+                    cov: Some(cov),
+                }
+            }
+            SerializedTransform::Rotation(r) => r.into(),
+            SerializedTransform::Matrix(mat) => {
+                let tf = Transform::try_from(mat)?;
+                tf.validate()?;
+                tf.into()
+            }
+            SerializedTransform::DualQuaternion(dual_quat) => {
+                Transform::try_from(dual_quat)?.into()
+            }
+            SerializedTransform::Twist(twist) => Transform::from(twist).into(),
+        };
+        Ok(tf)
     }
 }
 
 impl From<MaybeTransform> for SerializedTransform {
     fn from(from: MaybeTransform) -> Self {
-        let MaybeTransform { r, t } = from;
+        let MaybeTransform { r, t, cov } = from;
 
-        match t {
-            Some(t) => Self::Transform(Transform { r, t }),
-            None => Self::Rotation(r),
+        match (t, cov) {
+            (Some(t), cov) => Self::Transform(Box::new(Transform { r, t, cov })),
+            (None, Some(cov)) => {
+                Self::RotationWithCovariance(Box::new(SerializedRotationWithCovariance { r, cov }))
+            }
+            (None, None) => Self::Rotation(r),
         }
     }
 }
 
-/// Represent a translation in 3D Euclidean space.
+/// Repair the rotation matrix of the serialized rotation or transform
+/// in place.
+fn repair_value(value: &mut serde_json::Value) -> Result<Option<RotationMatrixRepair>> {
+    if let Some(rot) = value.get_mut("r") {
+        return repair_value(rot);
+    }
+
+    match value.get("format").and_then(|format| format.as_str()) {
+        Some("rotation-matrix") => {
+            let SerializedRotationMatrix { matrix } = serde_json::from_value(value.clone())?;
+            let (rot, report) = RotationMatrix { matrix }.repair();
+            value["matrix"] = serde_json::to_value(rot.matrix)?;
+            Ok(Some(report))
+        }
+        Some("affine-matrix" | "homogeneous-matrix") => {
+            let mut rows: Vec<[R64; 4]> = serde_json::from_value(value["matrix"].clone())?;
+            ensure!(rows.len() >= 3, "expect at least 3 rows in the matrix");
+            let matrix = [0, 1, 2].map(|row| [0, 1, 2].map(|col| rows[row][col]));
+            let (rot, report) = RotationMatrix { matrix }.repair();
+            for (row, values) in izip!(&mut rows, rot.matrix) {
+                row[..3].copy_from_slice(&values);
+            }
+            value["matrix"] = serde_json::to_value(rows)?;
+            Ok(Some(report))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Represent a rigid transformation in matrix form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "kebab-case")]
+pub enum TransformMatrix {
+    HomogeneousMatrix(HomogeneousMatrix),
+    AffineMatrix(AffineMatrix),
+}
+
+/// Rigid transformation represented as a 4x4 homogeneous matrix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HomogeneousMatrix {
+    #[serde(serialize_with = "serde_options::serialize")]
+    pub matrix: [[R64; 4]; 4],
+}
+
+impl HomogeneousMatrix {
+    /// Convert from an isometry. See
+    /// [Transform::try_from_na_isometry3].
+    pub fn try_from_na_isometry3<T>(iso: &na::Isometry3<T>) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        NonFiniteError::check_isometry(iso)?;
+        Ok(iso.clone().into())
+    }
+
+    /// Get the upper 3x4 `[R|t]` part of the matrix. It fails if the
+    /// last row is not `[0, 0, 0, 1]`.
+    pub fn to_affine_matrix(&self) -> Result<AffineMatrix> {
+        const EPSILON: f64 = 1e-6;
+
+        let [r1, r2, r3, last] = self.matrix;
+        let is_valid = last
+            .iter()
+            .zip([0.0, 0.0, 0.0, 1.0])
+            .all(|(val, expect)| (val.raw() - expect).abs() <= EPSILON);
+        if !is_valid {
+            bail!("expect the last row of the homogeneous matrix to be [0, 0, 0, 1], but found {last:?}");
+        }
+
+        Ok(AffineMatrix {
+            matrix: [r1, r2, r3],
+        })
+    }
+}
+
+/// Rigid transformation represented as a 3x4 `[R|t]` matrix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffineMatrix {
+    #[serde(serialize_with = "serde_options::serialize")]
+    pub matrix: [[R64; 4]; 3],
+}
+
+impl AffineMatrix {
+    /// Convert from an isometry. See
+    /// [Transform::try_from_na_isometry3].
+    pub fn try_from_na_isometry3<T>(iso: &na::Isometry3<T>) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        NonFiniteError::check_isometry(iso)?;
+        Ok(iso.clone().into())
+    }
+
+    pub fn to_homogeneous_matrix(&self) -> HomogeneousMatrix {
+        let [r1, r2, r3] = self.matrix;
+        let z = R64::zero();
+        let last = [z, z, z, R64::one()];
+        HomogeneousMatrix {
+            matrix: [r1, r2, r3, last],
+        }
+    }
+}
+
+/// Rigid transformation represented as a unit dual quaternion.
+///
+/// Both the real and the dual parts are written in x, y, z, w
+/// order. The real part is the rotation quaternion `r`, and the dual
+/// part is `t * r / 2`, where `t` is the pure quaternion of the
+/// translation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SerializedDualQuaternion", into = "SerializedDualQuaternion")]
+pub struct DualQuaternion {
+    pub real: [R64; 4],
+    pub dual: [R64; 4],
+}
+
+impl DualQuaternion {
+    /// Convert from an isometry. See
+    /// [Transform::try_from_na_isometry3].
+    pub fn try_from_na_isometry3<T>(iso: &na::Isometry3<T>) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        NonFiniteError::check_isometry(iso)?;
+        Ok(iso.clone().into())
+    }
+
+    /// Convert from a unit dual quaternion. See
+    /// [Transform::try_from_na_isometry3].
+    pub fn try_from_na_unit_dual_quaternion<T>(
+        dual_quat: &na::UnitDualQuaternion<T>,
+    ) -> Result<Self, NonFiniteError>
+    where
+        T: na::RealField + NumCast,
+    {
+        let na::DualQuaternion { real, dual } = dual_quat.as_ref();
+        NonFiniteError::check([
+            ("real.i", &real.coords[0]),
+            ("real.j", &real.coords[1]),
+            ("real.k", &real.coords[2]),
+            ("real.w", &real.coords[3]),
+            ("dual.i", &dual.coords[0]),
+            ("dual.j", &dual.coords[1]),
+            ("dual.k", &dual.coords[2]),
+            ("dual.w", &dual.coords[3]),
+        ])?;
+        Ok(dual_quat.clone().into())
+    }
+}
+
+/// The dual quaternion tagged with its format, so that the tag is
+/// checked on input like the other formats.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "kebab-case")]
+enum SerializedDualQuaternion {
+    DualQuaternion {
+        #[serde(serialize_with = "serde_options::serialize")]
+        real: [R64; 4],
+        #[serde(serialize_with = "serde_options::serialize")]
+        dual: [R64; 4],
+    },
+}
+
+impl From<SerializedDualQuaternion> for DualQuaternion {
+    fn from(from: SerializedDualQuaternion) -> Self {
+        let SerializedDualQuaternion::DualQuaternion { real, dual } = from;
+        Self { real, dual }
+    }
+}
+
+impl From<DualQuaternion> for SerializedDualQuaternion {
+    fn from(from: DualQuaternion) -> Self {
+        let DualQuaternion { real, dual } = from;
+        Self::DualQuaternion { real, dual }
+    }
+}
+
+/// Rigid transformation represented as a twist in the Lie algebra
+/// se(3), which is mapped to the transform by [Transform::exp].
+///
+/// The angular part is the rotation vector, and the linear part is
+/// the translational part of the twist. It's not the translation of
+/// the transform unless the angular part is zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SerializedTwist", into = "SerializedTwist")]
+pub struct Twist {
+    pub linear: [R64; 3],
+    pub angular: [R64; 3],
+}
+
+/// The twist tagged with its format, so that the tag is checked on
+/// input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "kebab-case")]
+enum SerializedTwist {
+    Twist {
+        #[serde(serialize_with = "serde_options::serialize")]
+        linear: [R64; 3],
+        #[serde(serialize_with = "serde_options::serialize")]
+        angular: [R64; 3],
+    },
+}
+
+impl From<SerializedTwist> for Twist {
+    fn from(from: SerializedTwist) -> Self {
+        let SerializedTwist::Twist { linear, angular } = from;
+        Self { linear, angular }
+    }
+}
+
+impl From<Twist> for SerializedTwist {
+    fn from(from: Twist) -> Self {
+        let Twist { linear, angular } = from;
+        Self::Twist { linear, angular }
+    }
+}
+
+impl Twist {
+    /// Returns the twist vector `[linear, angular]`.
+    pub fn to_vector(&self) -> [f64; 6] {
+        let [x, y, z] = self.linear.map(|val| val.raw());
+        let [rx, ry, rz] = self.angular.map(|val| val.raw());
+        [x, y, z, rx, ry, rz]
+    }
+
+    /// Build from the twist vector `[linear, angular]`. It fails if
+    /// any value is NaN or infinite.
+    pub fn from_vector(twist: [f64; 6]) -> Result<Self> {
+        let [x, y, z, rx, ry, rz] = twist;
+        let to_r64 = |val: f64| match R64::try_new(val) {
+            Some(val) => Ok(val),
+            None => bail!("invalid twist value '{val}'"),
+        };
+        Ok(Self {
+            linear: [to_r64(x)?, to_r64(y)?, to_r64(z)?],
+            angular: [to_r64(rx)?, to_r64(ry)?, to_r64(rz)?],
+        })
+    }
+}
+
+/// Represent a translation in 3D Euclidean space. Each component
+/// carries its own length unit.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[repr(transparent)]
-pub struct Translation(pub [R64; 3]);
+pub struct Translation(pub [Length; 3]);
+
+impl Translation {
+    pub fn zero() -> Self {
+        Self([Length::zero(); 3])
+    }
+
+    pub fn from_meters(values: [R64; 3]) -> Self {
+        Self(values.map(Length::from_meters))
+    }
+
+    /// Returns the components in meters.
+    pub fn as_meters_values(&self) -> [R64; 3] {
+        self.0.map(|val| val.as_meters_value())
+    }
+
+    pub fn to_meters(&self) -> Self {
+        self.to_unit(LengthUnit::Meter)
+    }
+
+    /// Convert all components to the length unit.
+    pub fn to_unit(&self, unit: LengthUnit) -> Self {
+        Self(self.0.map(|val| val.to_unit(unit)))
+    }
+
+    pub(crate) fn to_na_vector3(self) -> na::Vector3<f64> {
+        self.as_meters_values().map(|val| val.raw()).into()
+    }
+
+    /// Like [Translation::convert_like], but it checks that the
+    /// vector is finite.
+    pub(crate) fn try_convert_like(&self, vec: &na::Vector3<f64>) -> Result<Self, NonFiniteError> {
+        NonFiniteError::check([
+            ("translation.x", &vec[0]),
+            ("translation.y", &vec[1]),
+            ("translation.z", &vec[2]),
+        ])?;
+        Ok(self.convert_like(vec))
+    }
+
+    /// Convert the vector in meters to a translation with the same
+    /// component units as `self`.
+    pub(crate) fn convert_like(&self, vec: &na::Vector3<f64>) -> Self {
+        Self(std::array::from_fn(|idx| {
+            Length::from_meters(r64(vec[idx])).to_unit(self.0[idx].unit)
+        }))
+    }
+}
 
 impl<T> From<Translation> for na::Translation3<T>
 where
@@ -204,7 +842,7 @@ where
             };
         }
 
-        let Translation([x, y, z]) = trans;
+        let [x, y, z] = trans.as_meters_values();
         na::Translation3::new(cast!(x), cast!(y), cast!(z)).cast()
     }
 }
@@ -221,14 +859,16 @@ where
         }
 
         let [x, y, z] = trans.vector.into();
-        Self([cast!(x), cast!(y), cast!(z)])
+        Self::from_meters([cast!(x), cast!(y), cast!(z)])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{Transform, Translation};
-    use crate::{unit::AngleUnit, Angle, Euler, EulerAxis, EulerAxisOrder, Rotation};
+    use crate::{
+        unit::AngleUnit, Angle, Euler, EulerAxis, EulerAxisOrder, EulerConvention, Rotation,
+    };
     use approx::assert_abs_diff_eq;
     use noisy_float::types::r64;
 
@@ -241,9 +881,11 @@ mod tests {
                     unit: AngleUnit::Degree,
                     value: r64(10.0),
                 }],
+                convention: EulerConvention::Extrinsic,
             }
             .into(),
-            t: Translation([r64(-10.0), r64(20.0), r64(30.0)]),
+            t: Translation::from_meters([r64(-10.0), r64(20.0), r64(30.0)]),
+            cov: None,
         };
 
         let trans = trans.into_radians().into_degrees();
@@ -265,8 +907,10 @@ mod tests {
 
         let Transform {
             r: rot,
-            t: Translation([x, y, z]),
+            t: translation,
+            ..
         } = trans;
+        let [x, y, z] = translation.as_meters_values();
 
         assert_abs_diff_eq!(x.raw(), -10.0, epsilon = 1e-6);
         assert_abs_diff_eq!(y.raw(), 20.0, epsilon = 1e-6);
@@ -275,6 +919,7 @@ mod tests {
         let Rotation::Euler(Euler {
             order: EulerAxisOrder(order),
             angles,
+            ..
         }) = rot
         else {
             panic!("expect Euler variant");
@@ -1,16 +1,21 @@
 mod error;
 pub(crate) mod mutual_set;
-mod serialized;
+pub(crate) mod serialized;
 mod topo_sort;
 mod tset;
 
+use self::serialized::SerializedCoordTransform;
 use crate::Transform;
 use serde::{Deserialize, Serialize};
 
 pub use self::tset::TransformSet;
 
 /// Represent coordinate transformation in 3D Euclidean space.
+///
+/// It's also deserialized from the layout of the ROS
+/// `TransformStamped` message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedCoordTransform")]
 pub struct CoordTransform {
     pub src: String,
     pub dst: String,
@@ -1,4 +1,4 @@
-use crate::Transform;
+use crate::{RotationMatrixError, Transform};
 use nalgebra as na;
 use num::NumCast;
 
@@ -17,6 +17,12 @@ pub enum InsertionError {
     },
     #[error("Unable to insert disjoint coordinates '{src}' and '{dst}'")]
     DisjointCoordinates { src: String, dst: String },
+    #[error("invalid rotation from '{src}' to '{dst}': {error}")]
+    InvalidRotation {
+        src: String,
+        dst: String,
+        error: RotationMatrixError,
+    },
 }
 
 impl InsertionError {
@@ -90,10 +90,17 @@ impl MutualSet {
         Ok(())
     }
 
+    /// Add the coordinate to an empty set.
+    pub fn insert_coord(&mut self, coord: &str) {
+        debug_assert!(self.lookup.is_empty());
+        self.lookup.insert(coord.to_string(), vec![]);
+    }
+
     pub fn coord_iter(&self) -> impl Iterator<Item = &str> {
         self.lookup.keys().map(|coord| coord.as_str())
     }
 
+    #[allow(clippy::result_large_err)]
     pub fn merge(mut self, other: Self) -> Result<Self, (Self, Self)> {
         let common_coord = {
             let lcoords: HashSet<_> = self.lookup.keys().collect();
@@ -159,12 +166,6 @@ impl MutualSet {
 
         let Self { lookup, .. } = self;
 
-        let bases: Vec<_> = lookup
-            .iter()
-            .enumerate()
-            .map(|(idx, (base, _))| (idx, base))
-            .collect();
-
         for (idx, (base1, list1)) in izip!(0.., lookup) {
             let max_pow = (0..).find(|pow| {
                 let offset = 1 << pow;
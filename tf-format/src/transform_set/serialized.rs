@@ -1,13 +1,169 @@
 use super::{error::InsertionError, TransformSet};
-use crate::CoordTransform;
-use anyhow::Result;
-use itertools::Itertools;
-use serde::{Deserialize, Serialize};
+use crate::{
+    ros_msg::{RosHeader, RosTransform, TransformStamped},
+    rotation::insert_angle_unit,
+    AngleUnit, CoordTransform, Transform,
+};
+use anyhow::{bail, Result};
+use itertools::chain;
+use nalgebra as na;
+use serde::{
+    de::{Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The list of transforms. It's also deserialized from the layout of
+/// the ROS `TFMessage`, a map with a "transforms" list, which may have
+/// a set-level "angle_unit" field.
+#[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
 pub(crate) struct SerializedTransformSet(pub Vec<CoordTransform>);
 
+impl<'de> Deserialize<'de> for SerializedTransformSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ListVisitor;
+
+        impl<'de> Visitor<'de> for ListVisitor {
+            type Value = SerializedTransformSet;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a list of transforms or a map with a \"transforms\" list"
+                )
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut list = vec![];
+                while let Some(trans) = seq.next_element()? {
+                    list.push(trans);
+                }
+                Ok(SerializedTransformSet(list))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut list: Option<Vec<serde_json::Value>> = None;
+                let mut angle_unit: Option<AngleUnit> = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "transforms" => list = Some(map.next_value()?),
+                        "angle_unit" => angle_unit = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                let Some(list) = list else {
+                    return Err(serde::de::Error::missing_field("transforms"));
+                };
+
+                // The set-level "angle_unit" applies to the rotations
+                // without their own.
+                let list = list
+                    .into_iter()
+                    .map(|mut value| {
+                        let rot = value.get_mut("tf").and_then(|tf| tf.get_mut("r"));
+                        if let (Some(rot), Some(unit)) = (rot, angle_unit) {
+                            insert_angle_unit(rot, unit);
+                        }
+                        serde_json::from_value(value).map_err(A::Error::custom)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(SerializedTransformSet(list))
+            }
+        }
+
+        deserializer.deserialize_any(ListVisitor)
+    }
+}
+
+/// The fields of either a [CoordTransform] or a ROS
+/// `TransformStamped` message.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct SerializedCoordTransform {
+    src: Option<String>,
+    dst: Option<String>,
+    tf: Option<Transform>,
+    header: Option<RosHeader>,
+    child_frame_id: Option<String>,
+    transform: Option<RosTransform>,
+}
+
+impl TryFrom<SerializedCoordTransform> for CoordTransform {
+    type Error = anyhow::Error;
+
+    fn try_from(from: SerializedCoordTransform) -> Result<Self, Self::Error> {
+        let SerializedCoordTransform {
+            src,
+            dst,
+            tf,
+            header,
+            child_frame_id,
+            transform,
+        } = from;
+
+        let trans = match (src, dst, tf, header, child_frame_id, transform) {
+            (Some(src), Some(dst), Some(tf), None, None, None) => Self { src, dst, tf },
+            (None, None, None, Some(header), Some(child_frame_id), Some(transform)) => {
+                let msg = TransformStamped {
+                    header,
+                    child_frame_id,
+                    transform,
+                };
+                (&msg).try_into()?
+            }
+            _ => bail!(
+                "expect either \"src\", \"dst\" and \"tf\" fields or a TransformStamped \
+                 message with \"header\", \"child_frame_id\" and \"transform\" fields"
+            ),
+        };
+        Ok(trans)
+    }
+}
+impl TransformSet {
+    /// Build the set from a stream of documents, each a transform set
+    /// or a `TFMessage`, such as the YAML documents separated by "---"
+    /// in a recording of `ros2 topic echo /tf_static`. Empty documents
+    /// are skipped.
+    ///
+    /// Recordings may repeat transforms. A later transform replaces
+    /// the earlier one between the same frames in either direction.
+    pub fn from_documents<'de, I, D>(documents: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = D>,
+        D: Deserializer<'de>,
+        D::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let mut list: Vec<CoordTransform> = vec![];
+
+        for document in documents {
+            let Some(SerializedTransformSet(set)) = Option::deserialize(document)? else {
+                continue;
+            };
+            for trans in set {
+                list.retain(|prev| {
+                    let same = prev.src == trans.src && prev.dst == trans.dst;
+                    let reversed = prev.src == trans.dst && prev.dst == trans.src;
+                    !(same || reversed)
+                });
+                list.push(trans);
+            }
+        }
+
+        Ok(Self::try_from_iter(list)?)
+    }
+}
+
 impl TryFrom<SerializedTransformSet> for TransformSet {
     type Error = InsertionError;
 
@@ -16,23 +172,19 @@ impl TryFrom<SerializedTransformSet> for TransformSet {
     }
 }
 
+/// The inserted transforms are written as they are, followed by an
+/// identity transform from each frame without transforms to itself.
 impl From<TransformSet> for SerializedTransformSet {
     fn from(tset: TransformSet) -> Self {
-        let vec: Vec<_> = tset
-            .mid_to_set
-            .values()
-            .flat_map(|mset| {
-                mset.lookup
-                    .iter()
-                    .tuple_windows()
-                    .map(|((src, tf_list), (dst, _))| CoordTransform {
-                        src: src.to_string(),
-                        dst: dst.to_string(),
-                        tf: tf_list[0].into(),
-                    })
-            })
-            .collect();
-
+        let isolated = tset
+            .isolated_coords()
+            .into_iter()
+            .map(|coord| CoordTransform {
+                src: coord.to_string(),
+                dst: coord.to_string(),
+                tf: na::Isometry3::<f64>::identity().into(),
+            });
+        let vec: Vec<_> = chain!(tset.edges.clone(), isolated).collect();
         Self(vec)
     }
 }
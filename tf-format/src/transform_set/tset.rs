@@ -1,11 +1,11 @@
 use super::{error::InsertionError, mutual_set::MutualSet, serialized::SerializedTransformSet};
-use crate::{transform_set::topo_sort::TopologicalSort, CoordTransform};
+use crate::{transform_set::topo_sort::TopologicalSort, CoordTransform, Transform};
 use approx::abs_diff_eq;
 use itertools::chain;
 use nalgebra as na;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     fs::{self, File},
     io::BufReader,
     path::Path,
@@ -13,12 +13,17 @@ use std::{
 };
 
 /// Represent a set of related or disjoint coordinate transformations.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(try_from = "SerializedTransformSet", into = "SerializedTransformSet")]
 pub struct TransformSet {
     mid: usize,
     coord_to_mid: HashMap<String, usize>,
     pub(crate) mid_to_set: HashMap<usize, MutualSet>,
+    /// The inserted transforms, which keep the covariances.
+    pub(crate) edges: Vec<CoordTransform>,
+    /// The indices of the inserted transforms at each coordinate and
+    /// whether the coordinate is the source.
+    adj: HashMap<String, Vec<(usize, bool)>>,
 }
 
 impl TransformSet {
@@ -26,6 +31,12 @@ impl TransformSet {
         Self::default()
     }
 
+    /// Get the isometry from `src` to `dst`.
+    ///
+    /// It stays a plain isometry, which can't carry a covariance, so
+    /// that existing callers keep working. Use
+    /// [`get_transform`](Self::get_transform) for the combined
+    /// covariance.
     pub fn get(&self, src: &str, dst: &str) -> Option<na::Isometry3<f64>> {
         let src_mid = self.coord_to_mid.get(src)?;
         let dst_mid = self.coord_to_mid.get(dst)?;
@@ -37,15 +48,136 @@ impl TransformSet {
         self.mid_to_set[src_mid].get(src, dst)
     }
 
+    /// Get the transform along with the covariance, which is combined
+    /// from the covariances of the inserted transforms on the path
+    /// from `src` to `dst`. The transforms are assumed independent.
+    ///
+    /// The result is in the rotation format, angle unit and length
+    /// units of the first transform on the path.
+    pub fn get_transform(&self, src: &str, dst: &str) -> Option<Transform> {
+        let iso = self.get(src, dst)?;
+
+        let prod = self
+            .find_path(src, dst)?
+            .into_iter()
+            .map(|(idx, forward)| {
+                let tf = &self.edges[idx].tf;
+                if forward {
+                    tf.clone()
+                } else {
+                    tf.inverse()
+                }
+            })
+            .reduce(|prod, tf| &prod * &tf);
+
+        let Some(prod) = prod else {
+            return Some(iso.into());
+        };
+        Some(Transform {
+            r: prod.r.convert_like(&iso.rotation),
+            t: prod.t.convert_like(&iso.translation.vector),
+            cov: prod.cov,
+        })
+    }
+
+    /// The inserted transforms in insertion order.
+    pub fn transforms(&self) -> &[CoordTransform] {
+        &self.edges
+    }
+
     pub fn contains_coord(&self, coord: &str) -> bool {
         self.coord_to_mid.contains_key(coord)
     }
 
+    /// Add a coordinate frame that is not related to any other frame
+    /// yet. It does nothing if the frame already exists. The frame is
+    /// written as an identity transform from the frame to itself.
+    pub fn insert_coord(&mut self, coord: &str) {
+        if self.contains_coord(coord) {
+            return;
+        }
+
+        let new_mid = self.next_mid();
+        let mut new_set = MutualSet::new();
+        new_set.insert_coord(coord);
+        self.mid_to_set.insert(new_mid, new_set);
+        self.coord_to_mid.insert(coord.to_string(), new_mid);
+    }
+
     pub fn insert(
         &mut self,
         src: &str,
         dst: &str,
         tf: na::Isometry3<f64>,
+    ) -> Result<(), InsertionError> {
+        self.insert_transform(src, dst, tf.into())
+    }
+
+    /// Insert the transform, which may carry a covariance.
+    pub fn insert_transform(
+        &mut self,
+        src: &str,
+        dst: &str,
+        tf: Transform,
+    ) -> Result<(), InsertionError> {
+        if let Err(error) = tf.validate() {
+            return Err(InsertionError::InvalidRotation {
+                src: src.to_string(),
+                dst: dst.to_string(),
+                error,
+            });
+        }
+
+        // A transform from a frame to itself only adds the frame.
+        if src == dst {
+            let tf: na::Isometry3<f64> = tf.into();
+            let id = na::Isometry3::identity();
+            if !abs_diff_eq!(tf, id) {
+                return Err(InsertionError::inconsistent_transform_error(id, tf));
+            }
+            self.insert_coord(src);
+            return Ok(());
+        }
+
+        self.insert_isometry(src, dst, tf.clone().into())?;
+        self.push_edge(CoordTransform {
+            src: src.to_string(),
+            dst: dst.to_string(),
+            tf,
+        });
+        Ok(())
+    }
+
+    /// The frames without any inserted transform.
+    pub(crate) fn isolated_coords(&self) -> Vec<&str> {
+        let mut coords: Vec<_> = self
+            .coord_to_mid
+            .keys()
+            .filter(|coord| !self.adj.contains_key(coord.as_str()))
+            .map(String::as_str)
+            .collect();
+        coords.sort_unstable();
+        coords
+    }
+
+    fn push_edge(&mut self, trans: CoordTransform) {
+        let idx = self.edges.len();
+        self.adj
+            .entry(trans.src.clone())
+            .or_default()
+            .push((idx, true));
+        self.adj
+            .entry(trans.dst.clone())
+            .or_default()
+            .push((idx, false));
+        self.edges.push(trans);
+    }
+
+    fn insert_isometry(
+        &mut self,
+        src: &str,
+        dst: &str,
+        tf: na::Isometry3<f64>,
     ) -> Result<(), InsertionError> {
         let src_mid = self.coord_to_mid.get(src).copied();
         let dst_mid = self.coord_to_mid.get(dst).copied();
@@ -98,7 +230,7 @@ impl TransformSet {
         }
     }
 
-    pub fn merge(self, other: Self) -> Self {
+    pub fn merge(self, _other: Self) -> Self {
         todo!()
     }
 
@@ -108,19 +240,25 @@ impl TransformSet {
     {
         let mut topo_sort = TopologicalSort::new();
         let mut adj: HashMap<Rc<String>, HashMap<Rc<String>, _>> = HashMap::new();
+        let mut edges = vec![];
 
         for trans in iter {
-            let CoordTransform { src, dst, tf } = trans;
+            let CoordTransform { src, dst, tf } = trans.clone();
+            if let Err(error) = tf.validate() {
+                return Err(InsertionError::InvalidRotation { src, dst, error });
+            }
             let tf: na::Isometry3<f64> = tf.into();
             let src = Rc::new(src);
             let dst = Rc::new(dst);
 
+            // A transform from a frame to itself only adds the frame.
             if src == dst {
                 let id = na::Isometry3::identity();
                 if !abs_diff_eq!(tf, id) {
                     return Err(InsertionError::inconsistent_transform_error(id, tf));
                 }
             } else {
+                edges.push(trans);
                 adj.entry(dst.clone())
                     .or_default()
                     .insert(src.clone(), tf.inverse());
@@ -136,6 +274,9 @@ impl TransformSet {
             .enumerate()
             .map(|(mid, comp)| {
                 let mut mset = MutualSet::new();
+                if comp.seq.is_empty() {
+                    mset.insert_coord(&comp.start);
+                }
 
                 for (src, dst) in comp.seq {
                     let tf = adj[&src][&dst];
@@ -151,11 +292,17 @@ impl TransformSet {
             .flat_map(|(&mid, mset)| mset.coord_iter().map(move |coord| (coord.to_string(), mid)))
             .collect();
 
-        Ok(Self {
+        let mut set = Self {
             mid: mid_to_set.len(),
             coord_to_mid,
             mid_to_set,
-        })
+            edges: vec![],
+            adj: HashMap::new(),
+        };
+        for trans in edges {
+            set.push_edge(trans);
+        }
+        Ok(set)
     }
 
     pub fn from_json_file<P>(path: P) -> anyhow::Result<Self>
@@ -196,19 +343,44 @@ impl TransformSet {
         Ok(merged_set)
     }
 
+    /// Find the inserted transforms on the path from `src` to `dst`.
+    /// Each step is the index of the transform and whether it's
+    /// traversed in the inserted direction.
+    fn find_path(&self, src: &str, dst: &str) -> Option<Vec<(usize, bool)>> {
+        // Breadth-first search from src, remembering the step that
+        // reaches each coordinate.
+        let mut prev: HashMap<&str, Option<(&str, usize, bool)>> = HashMap::new();
+        let mut queue = VecDeque::from([src]);
+        prev.insert(src, None);
+
+        while let Some(curr) = queue.pop_front() {
+            if curr == dst {
+                break;
+            }
+            for &(idx, forward) in self.adj.get(curr).into_iter().flatten() {
+                let edge = &self.edges[idx];
+                let next = if forward { &edge.dst } else { &edge.src };
+                let next = next.as_str();
+                if !prev.contains_key(next) {
+                    prev.insert(next, Some((curr, idx, forward)));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut curr = dst;
+        while let Some((from, idx, forward)) = *prev.get(curr)? {
+            path.push((idx, forward));
+            curr = from;
+        }
+        path.reverse();
+        Some(path)
+    }
+
     fn next_mid(&mut self) -> usize {
         let out = self.mid;
         self.mid += 1;
         out
     }
 }
-
-impl Default for TransformSet {
-    fn default() -> Self {
-        Self {
-            mid: 0,
-            coord_to_mid: HashMap::new(),
-            mid_to_set: HashMap::new(),
-        }
-    }
-}
@@ -1,16 +1,60 @@
-use anyhow::bail;
+use crate::{expr, serde_options};
+use anyhow::{bail, Context};
 use approx::AbsDiffEq;
 use noisy_float::types::{r64, R64};
 use num::{Float, Zero};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
-use std::{cmp::Ordering, f64::consts::PI, str::FromStr};
-
-pub type Length = R64;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use std::{
+    cmp::Ordering,
+    f64::consts::PI,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AngleUnit {
+    #[serde(rename = "rad", alias = "radian")]
     Radian,
+    #[serde(rename = "deg", alias = "degree")]
     Degree,
+    #[serde(rename = "grad", alias = "gradian")]
+    Gradian,
+    #[serde(rename = "turn")]
+    Turn,
+    #[serde(rename = "arcmin")]
+    ArcMinute,
+    #[serde(rename = "arcsec")]
+    ArcSecond,
+    /// Degrees written in degree-minute-second notation like
+    /// `12°30'15"`. The value is stored in degrees.
+    #[serde(rename = "dms")]
+    Dms,
+}
+
+impl AngleUnit {
+    /// The angle of one unit in radians.
+    pub fn to_radians_factor(&self) -> f64 {
+        match self {
+            AngleUnit::Radian => 1.0,
+            AngleUnit::Degree | AngleUnit::Dms => PI / 180.0,
+            AngleUnit::Gradian => PI / 200.0,
+            AngleUnit::Turn => PI * 2.0,
+            AngleUnit::ArcMinute => PI / (180.0 * 60.0),
+            AngleUnit::ArcSecond => PI / (180.0 * 3600.0),
+        }
+    }
+
+    /// The value of a full turn in this unit.
+    pub fn full_turn(&self) -> f64 {
+        match self {
+            AngleUnit::Radian => PI * 2.0,
+            AngleUnit::Degree | AngleUnit::Dms => 360.0,
+            AngleUnit::Gradian => 400.0,
+            AngleUnit::Turn => 1.0,
+            AngleUnit::ArcMinute => 360.0 * 60.0,
+            AngleUnit::ArcSecond => 360.0 * 3600.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -22,11 +66,7 @@ pub struct Angle {
 impl Angle {
     pub fn normalize(&self) -> Self {
         let Self { unit, value } = *self;
-
-        let value = match unit {
-            AngleUnit::Radian => value.raw().rem_euclid(PI * 2.0),
-            AngleUnit::Degree => value.raw().rem_euclid(360.0),
-        };
+        let value = value.raw().rem_euclid(unit.full_turn());
 
         Self {
             unit,
@@ -42,31 +82,33 @@ impl Angle {
     }
 
     pub fn to_radians(&self) -> Self {
-        let Self { unit, value } = *self;
-
-        let new_value = match unit {
-            AngleUnit::Radian => value,
-            AngleUnit::Degree => value.to_radians(),
-        };
-
-        Self {
-            unit: AngleUnit::Radian,
-            value: new_value,
-        }
+        self.to_unit(AngleUnit::Radian)
     }
 
     pub fn to_degrees(&self) -> Self {
-        let Self { unit, value } = *self;
+        self.to_unit(AngleUnit::Degree)
+    }
 
-        let new_value = match unit {
-            AngleUnit::Radian => value.to_degrees(),
-            AngleUnit::Degree => value,
-        };
+    /// Convert the angle to the unit.
+    pub fn to_unit(&self, unit: AngleUnit) -> Self {
+        let Self {
+            unit: orig_unit,
+            value,
+        } = *self;
 
-        Self {
-            unit: AngleUnit::Degree,
-            value: new_value,
+        // Degree and DMS share the same value.
+        if orig_unit.to_radians_factor() == unit.to_radians_factor() {
+            return Self { unit, value };
         }
+
+        let radians = self.as_radians_value();
+        let value = match unit {
+            AngleUnit::Radian => radians,
+            AngleUnit::Degree | AngleUnit::Dms => radians.to_degrees(),
+            _ => radians / unit.to_radians_factor(),
+        };
+
+        Self { unit, value }
     }
 
     pub fn as_radians_value(&self) -> R64 {
@@ -74,17 +116,13 @@ impl Angle {
 
         match unit {
             AngleUnit::Radian => value,
-            AngleUnit::Degree => value.to_radians(),
+            AngleUnit::Degree | AngleUnit::Dms => value.to_radians(),
+            _ => value * unit.to_radians_factor(),
         }
     }
 
     pub fn as_degrees_value(&self) -> R64 {
-        let Self { unit, value } = *self;
-
-        match unit {
-            AngleUnit::Radian => value.to_degrees(),
-            AngleUnit::Degree => value,
-        }
+        self.to_degrees().value
     }
 
     pub fn from_radians(value: R64) -> Self {
@@ -106,23 +144,51 @@ impl FromStr for Angle {
     type Err = anyhow::Error;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let (unit, value) = if let Some(prefix) = text.strip_suffix('°') {
-            let value: f64 = prefix.parse()?;
-            (AngleUnit::Degree, value)
-        } else if let Some(prefix) = text.strip_suffix("rad") {
-            let value: f64 = prefix.parse()?;
-            (AngleUnit::Radian, value)
-        } else if let Some(prefix) = text.strip_suffix("deg") {
-            let value: f64 = prefix.parse()?;
-            (AngleUnit::Degree, value)
-        } else if let Some(prefix) = text.strip_suffix('d') {
-            let value: f64 = prefix.parse()?;
-            (AngleUnit::Degree, value)
-        } else if let Some(prefix) = text.strip_suffix('r') {
-            let value: f64 = prefix.parse()?;
-            (AngleUnit::Radian, value)
-        } else {
-            bail!("unable to parse angle value '{text}'");
+        if let Some(value) = parse_dms(text)? {
+            let Ok(value) = R64::try_from(value) else {
+                bail!("invalid angle value '{value}'");
+            };
+            return Ok(Self {
+                unit: AngleUnit::Dms,
+                value,
+            });
+        }
+
+        // "grad" must be checked before "rad" and "d".
+        const SUFFIXES: [(&str, AngleUnit); 13] = [
+            ("°", AngleUnit::Degree),
+            ("grad", AngleUnit::Gradian),
+            ("gon", AngleUnit::Gradian),
+            ("rad", AngleUnit::Radian),
+            ("deg", AngleUnit::Degree),
+            ("turn", AngleUnit::Turn),
+            ("rev", AngleUnit::Turn),
+            ("arcmin", AngleUnit::ArcMinute),
+            ("arcsec", AngleUnit::ArcSecond),
+            ("'", AngleUnit::ArcMinute),
+            ("\"", AngleUnit::ArcSecond),
+            ("d", AngleUnit::Degree),
+            ("r", AngleUnit::Radian),
+        ];
+
+        let suffixed = SUFFIXES
+            .into_iter()
+            .find_map(|(suffix, unit)| Some((unit, text.strip_suffix(suffix)?)));
+
+        // Fall back to expressions like "-90d + 1.5d" or "(pi/4)r" if
+        // it's not a plain number with a suffix.
+        let (unit, value) = match suffixed {
+            Some((unit, prefix)) => match prefix.parse() {
+                Ok(value) => (unit, value),
+                Err(_) => match expr::eval_angle(text) {
+                    Ok(output) => output,
+                    Err(err) => (unit, expr::eval(prefix).map_err(|_| err)?),
+                },
+            },
+            // Plain numbers and their expressions have no unit and are
+            // rejected rather than taken as radians.
+            None => expr::eval_angle(text)
+                .with_context(|| format!("unable to parse angle value '{text}'"))?,
         };
 
         let Ok(value) = R64::try_from(value) else {
@@ -133,13 +199,27 @@ impl FromStr for Angle {
     }
 }
 
-impl ToString for Angle {
-    fn to_string(&self) -> String {
+impl Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self { unit, value } = *self;
 
         match unit {
-            AngleUnit::Radian => format!("{value}r"),
-            AngleUnit::Degree => format!("{value}d"),
+            AngleUnit::Radian => write!(f, "{value}r"),
+            AngleUnit::Degree => write!(f, "{value}d"),
+            AngleUnit::Gradian => write!(f, "{value}grad"),
+            AngleUnit::Turn => write!(f, "{value}turn"),
+            AngleUnit::ArcMinute => write!(f, "{value}arcmin"),
+            AngleUnit::ArcSecond => write!(f, "{value}arcsec"),
+            AngleUnit::Dms => {
+                // Split in integer micro-arcseconds to avoid round-off
+                // errors like 59.99999999 seconds.
+                let sign = if value < 0.0 { "-" } else { "" };
+                let micros = (value.raw().abs() * 3600e6).round() as u64;
+                let degrees = micros / 3_600_000_000;
+                let minutes = micros / 60_000_000 % 60;
+                let seconds = (micros % 60_000_000) as f64 / 1e6;
+                write!(f, "{sign}{degrees}°{minutes}'{seconds}\"")
+            }
         }
     }
 }
@@ -149,7 +229,9 @@ impl Serialize for Angle {
     where
         S: Serializer,
     {
-        self.to_string().serialize(serializer)
+        serde_options::round_angle(*self)
+            .to_string()
+            .serialize(serializer)
     }
 }
 
@@ -198,6 +280,227 @@ impl AbsDiffEq for Angle {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    Meter,
+    Centimeter,
+    Millimeter,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    /// The length of one unit in meters.
+    pub fn to_meters_factor(&self) -> f64 {
+        match self {
+            LengthUnit::Meter => 1.0,
+            LengthUnit::Centimeter => 0.01,
+            LengthUnit::Millimeter => 0.001,
+            LengthUnit::Inch => 0.0254,
+            LengthUnit::Foot => 0.3048,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            LengthUnit::Meter => "m",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Inch => "in",
+            LengthUnit::Foot => "ft",
+        }
+    }
+}
+
+/// A length value with a unit.
+///
+/// It is serialized as a plain number if the unit is meter, and
+/// otherwise a string with a unit suffix like `"12.5mm"`. Plain
+/// numbers are deserialized in meters.
+#[derive(Debug, Clone, Copy)]
+pub struct Length {
+    pub unit: LengthUnit,
+    pub value: R64,
+}
+
+impl Length {
+    pub fn zero() -> Self {
+        Self {
+            unit: LengthUnit::Meter,
+            value: R64::zero(),
+        }
+    }
+
+    pub fn from_meters(value: R64) -> Self {
+        Self {
+            unit: LengthUnit::Meter,
+            value,
+        }
+    }
+
+    pub fn as_meters_value(&self) -> R64 {
+        let Self { unit, value } = *self;
+        value * unit.to_meters_factor()
+    }
+
+    pub fn to_meters(&self) -> Self {
+        self.to_unit(LengthUnit::Meter)
+    }
+
+    pub fn to_unit(&self, unit: LengthUnit) -> Self {
+        if self.unit == unit {
+            return *self;
+        }
+
+        Self {
+            unit,
+            value: self.as_meters_value() / unit.to_meters_factor(),
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // Longer suffixes go first since "mm" and "cm" end with "m".
+        const UNITS: [LengthUnit; 5] = [
+            LengthUnit::Millimeter,
+            LengthUnit::Centimeter,
+            LengthUnit::Inch,
+            LengthUnit::Foot,
+            LengthUnit::Meter,
+        ];
+
+        // A string without a suffix is an expression in meters, but a
+        // bare number is rejected.
+        let (unit, prefix) = match UNITS
+            .into_iter()
+            .find_map(|unit| Some((unit, text.strip_suffix(unit.suffix())?)))
+        {
+            Some(output) => output,
+            None if text.trim().parse::<f64>().is_ok() => {
+                bail!("unable to parse length value '{text}' without a length unit")
+            }
+            None => (LengthUnit::Meter, text),
+        };
+        let prefix = prefix.trim_end();
+        let value: f64 = match prefix.parse() {
+            Ok(value) => value,
+            Err(_) => expr::eval(prefix)?,
+        };
+
+        let Ok(value) = R64::try_from(value) else {
+            bail!("invalid length value '{value}'");
+        };
+
+        Ok(Self { unit, value })
+    }
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { unit, value } = *self;
+        write!(f, "{value}{}", unit.suffix())
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let length = Self {
+            unit: self.unit,
+            value: serde_options::round_number(self.value),
+        };
+
+        match length.unit {
+            LengthUnit::Meter => length.value.serialize(serializer),
+            _ => length.to_string().serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Serialized {
+            Number(R64),
+            Text(String),
+        }
+
+        match Serialized::deserialize(deserializer)? {
+            Serialized::Number(value) => Ok(Self::from_meters(value)),
+            Serialized::Text(text) => text
+                .parse()
+                .map_err(|err| D::Error::custom(format!("{err}"))),
+        }
+    }
+}
+
+impl PartialEq for Length {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_meters_value() == other.as_meters_value()
+    }
+}
+
+impl AbsDiffEq for Length {
+    type Epsilon = f64;
+
+    fn default_epsilon() -> Self::Epsilon {
+        f64::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.as_meters_value()
+            .raw()
+            .abs_diff_eq(&other.as_meters_value().raw(), epsilon)
+    }
+}
+
+/// Parse the degree-minute-second notation like `12°30'15"`. It
+/// returns `None` if no minute or second part follows the degree
+/// sign.
+fn parse_dms(text: &str) -> anyhow::Result<Option<f64>> {
+    let Some((degrees, rest)) = text.split_once('°') else {
+        return Ok(None);
+    };
+    if rest.is_empty() {
+        return Ok(None);
+    }
+
+    let (minutes, rest) = match rest.split_once('\'') {
+        Some((minutes, rest)) => (minutes, rest),
+        None => ("0", rest),
+    };
+    let seconds = match rest {
+        "" => "0",
+        _ => match rest.strip_suffix('"') {
+            Some(seconds) => seconds,
+            None => bail!("unable to parse angle value '{text}'"),
+        },
+    };
+
+    let (sign, degrees) = match degrees.strip_prefix('-') {
+        Some(degrees) => (-1.0, degrees),
+        None => (1.0, degrees),
+    };
+    let degrees: f64 = degrees.parse()?;
+    let minutes: f64 = minutes.parse()?;
+    let seconds: f64 = seconds.parse()?;
+
+    if !(0.0..60.0).contains(&minutes) || !(0.0..60.0).contains(&seconds) {
+        bail!("minutes and seconds must be in [0, 60) in angle value '{text}'");
+    }
+
+    Ok(Some(sign * (degrees + minutes / 60.0 + seconds / 3600.0)))
+}
+
 fn angle_eq(lhs: &Angle, rhs: &Angle) -> bool {
     lhs.as_radians_value().eq(&rhs.as_radians_value())
 }
@@ -208,7 +511,8 @@ fn angle_ord(lhs: &Angle, rhs: &Angle) -> Ordering {
 
 #[cfg(test)]
 mod tests {
-    use super::{Angle, AngleUnit};
+    use super::{Angle, AngleUnit, Length, LengthUnit};
+    use approx::assert_abs_diff_eq;
     use noisy_float::types::r64;
     use std::f64::consts::FRAC_PI_2;
 
@@ -259,6 +563,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_angle_units() {
+        let cases = [
+            ("100grad", AngleUnit::Gradian, 100.0),
+            ("50gon", AngleUnit::Gradian, 50.0),
+            ("0.25turn", AngleUnit::Turn, 0.25),
+            ("-1rev", AngleUnit::Turn, -1.0),
+            ("30arcmin", AngleUnit::ArcMinute, 30.0),
+            ("15'", AngleUnit::ArcMinute, 15.0),
+            ("3600arcsec", AngleUnit::ArcSecond, 3600.0),
+            ("2.5\"", AngleUnit::ArcSecond, 2.5),
+            ("12°", AngleUnit::Degree, 12.0),
+            ("12°30'", AngleUnit::Dms, 12.5),
+            ("-12°30'36\"", AngleUnit::Dms, -12.51),
+        ];
+
+        for (text, unit, value) in cases {
+            let angle: Angle = text.parse().unwrap();
+            assert_eq!(angle.unit, unit, "{text}");
+            assert_abs_diff_eq!(angle.value.raw(), value, epsilon = 1e-9);
+        }
+
+        let angle: Angle = "-90d + 1.5d".parse().unwrap();
+        assert_eq!(angle.unit, AngleUnit::Degree);
+        assert_abs_diff_eq!(angle.value.raw(), -88.5, epsilon = 1e-12);
+
+        let angle: Angle = "pi/2".parse().unwrap();
+        assert_eq!(angle.unit, AngleUnit::Radian);
+        assert_abs_diff_eq!(angle.value.raw(), FRAC_PI_2, epsilon = 1e-12);
+
+        let angle: Angle = "(pi/4)r".parse().unwrap();
+        assert_eq!(angle.unit, AngleUnit::Radian);
+        assert_abs_diff_eq!(angle.value.raw(), FRAC_PI_2 / 2.0, epsilon = 1e-12);
+
+        assert!("90d + 1".parse::<Angle>().is_err());
+        assert!("1.5".parse::<Angle>().is_err());
+        assert!("2*1".parse::<Angle>().is_err());
+        assert!("12°75'".parse::<Angle>().is_err());
+        assert!("12°30'15".parse::<Angle>().is_err());
+    }
+
+    #[test]
+    fn angle_unit_conversion() {
+        let angle = Angle::from_degrees(r64(90.0));
+        let expects = [
+            (AngleUnit::Radian, FRAC_PI_2),
+            (AngleUnit::Gradian, 100.0),
+            (AngleUnit::Turn, 0.25),
+            (AngleUnit::ArcMinute, 5400.0),
+            (AngleUnit::ArcSecond, 324000.0),
+            (AngleUnit::Dms, 90.0),
+        ];
+
+        for (unit, value) in expects {
+            let converted = angle.to_unit(unit);
+            assert_eq!(converted.unit, unit);
+            assert_abs_diff_eq!(converted.value.raw(), value, epsilon = 1e-9);
+            assert_abs_diff_eq!(converted.to_degrees(), angle, epsilon = 1e-12);
+
+            // Round trip through the text form.
+            let parsed: Angle = converted.to_string().parse().unwrap();
+            assert_eq!(parsed.unit, unit);
+            assert_abs_diff_eq!(parsed, converted, epsilon = 1e-12);
+        }
+
+        let angle: Angle = "-12°30'36\"".parse().unwrap();
+        assert_eq!(angle.to_string(), "-12°30'36\"");
+    }
+
     #[test]
     fn angle_unit() {
         let angle = Angle {
@@ -302,4 +675,45 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn parse_length() {
+        let length: Length = "12.5mm".parse().unwrap();
+        assert_eq!(length.unit, LengthUnit::Millimeter);
+        assert_eq!(length.value, r64(12.5));
+
+        let length: Length = "-0.3m".parse().unwrap();
+        assert_eq!(length.unit, LengthUnit::Meter);
+        assert_eq!(length.value, r64(-0.3));
+
+        let length: Length = "2in".parse().unwrap();
+        assert_eq!(length.unit, LengthUnit::Inch);
+        assert_eq!(length, Length::from_meters(r64(0.0508)));
+
+        let length: Length = "sqrt(2) / 2".parse().unwrap();
+        assert_eq!(length.unit, LengthUnit::Meter);
+        assert_abs_diff_eq!(length.value.raw(), 0.5f64.sqrt(), epsilon = 1e-12);
+
+        let length: Length = "(1 + 2) * 10 mm".parse().unwrap();
+        assert_eq!(length.unit, LengthUnit::Millimeter);
+        assert_abs_diff_eq!(length.value.raw(), 30.0, epsilon = 1e-12);
+
+        assert!("1.0".parse::<Length>().is_err());
+        assert!("1.0km".parse::<Length>().is_err());
+    }
+
+    #[test]
+    fn length_serde() {
+        let length: Length = serde_json::from_str("1.5").unwrap();
+        assert_eq!(length, Length::from_meters(r64(1.5)));
+        assert_eq!(serde_json::to_string(&length).unwrap(), "1.5");
+
+        let length: Length = serde_json::from_str(r#""30cm""#).unwrap();
+        assert_eq!(length.unit, LengthUnit::Centimeter);
+        assert_eq!(serde_json::to_string(&length).unwrap(), r#""30cm""#);
+
+        let length = length.to_unit(LengthUnit::Millimeter);
+        assert_eq!(length.unit, LengthUnit::Millimeter);
+        assert_abs_diff_eq!(length.value.raw(), 300.0, epsilon = 1e-9);
+    }
 }
@@ -0,0 +1,427 @@
+use crate::{Angle, CoordTransform, Euler, EulerConvention, Transform, TransformSet, Translation};
+use anyhow::{bail, ensure, Context, Result};
+use nalgebra as na;
+use noisy_float::types::r64;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+impl TransformSet {
+    /// Build a transform set from a URDF robot description. Links
+    /// become coordinate frames and each joint is a transform from
+    /// the parent link to the child link.
+    ///
+    /// Movable joints are placed at the positions in radians or
+    /// meters given in `joint_positions`. Joints without a given
+    /// position are placed at zero, clamped to the joint limits.
+    /// Mimic joints follow the joints they mimic. Planar and floating
+    /// joints are placed at their origins. Links without joints are
+    /// frames on their own.
+    pub fn from_urdf_str(text: &str, joint_positions: &HashMap<String, f64>) -> Result<Self> {
+        let doc = roxmltree::Document::parse(text)?;
+        let robot = doc.root_element();
+        ensure!(
+            robot.has_tag_name("robot"),
+            "expect a <robot> root element, but found <{}>",
+            robot.tag_name().name()
+        );
+
+        let joints: Vec<_> = robot
+            .children()
+            .filter(|node| node.has_tag_name("joint"))
+            .map(|node| parse_joint(&node))
+            .collect::<Result<_>>()?;
+        let joint_map: HashMap<_, _> = joints.iter().map(|joint| (joint.name, joint)).collect();
+
+        for name in joint_positions.keys() {
+            ensure!(
+                joint_map.contains_key(name.as_str()),
+                "the joint '{name}' is not found"
+            );
+        }
+
+        let list: Vec<_> = joints
+            .iter()
+            .map(|joint| {
+                let position = resolve_position(joint, &joint_map, joint_positions, &mut vec![])
+                    .with_context(|| format!("unable to parse the joint '{}'", joint.name))?;
+                joint.to_coord_transform(position)
+            })
+            .collect::<Result<_>>()?;
+        let mut set = Self::try_from_iter(list)?;
+
+        // Links without joints, such as the only link of a single-link
+        // robot, are frames on their own.
+        for link in robot.children().filter(|node| node.has_tag_name("link")) {
+            set.insert_coord(required_attribute(&link, "name")?);
+        }
+
+        Ok(set)
+    }
+
+    pub fn from_urdf_file<P>(path: P, joint_positions: &HashMap<String, f64>) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let text = fs::read_to_string(path)?;
+        Self::from_urdf_str(&text, joint_positions)
+    }
+
+    /// Write the frames connected to `root` as a URDF robot
+    /// description. Each frame becomes a link attached to its parent
+    /// by a fixed joint named "{parent}_to_{child}", following a
+    /// breadth-first traversal of the inserted transforms. Colliding
+    /// joint names get the suffixes "_2", "_3" and so on. The other
+    /// frames are omitted.
+    pub fn to_urdf_string(&self, root: &str, robot_name: &str) -> Result<String> {
+        ensure!(
+            self.contains_coord(root),
+            "the coordinate '{root}' is not found"
+        );
+
+        let mut adj: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            adj.entry(&edge.src).or_default().push(&edge.dst);
+            adj.entry(&edge.dst).or_default().push(&edge.src);
+        }
+
+        let mut links = vec![root];
+        let mut joints = vec![];
+        let mut visited = HashSet::from([root]);
+        let mut queue = VecDeque::from([root]);
+
+        while let Some(parent) = queue.pop_front() {
+            for &child in adj.get(parent).into_iter().flatten() {
+                if !visited.insert(child) {
+                    continue;
+                }
+                let iso = self.get(parent, child).unwrap();
+                links.push(child);
+                joints.push((parent, child, iso));
+                queue.push_back(child);
+            }
+        }
+
+        let mut text = String::new();
+        writeln!(text, r#"<?xml version="1.0"?>"#)?;
+        writeln!(text, r#"<robot name="{}">"#, escape(robot_name))?;
+        for link in links {
+            writeln!(text, r#"  <link name="{}"/>"#, escape(link))?;
+        }
+        let mut used = HashSet::new();
+        for (parent, child, iso) in joints {
+            let [x, y, z] = iso.translation.vector.map(positive_zero).into();
+            let (roll, pitch, yaw) = iso.rotation.euler_angles();
+            let [roll, pitch, yaw] = [roll, pitch, yaw].map(positive_zero);
+
+            let base = format!("{parent}_to_{child}");
+            let mut name = base.clone();
+            let mut count = 1;
+            while !used.insert(name.clone()) {
+                count += 1;
+                name = format!("{base}_{count}");
+            }
+
+            writeln!(text, r#"  <joint name="{}" type="fixed">"#, escape(&name))?;
+            writeln!(text, r#"    <parent link="{}"/>"#, escape(parent))?;
+            writeln!(text, r#"    <child link="{}"/>"#, escape(child))?;
+            writeln!(
+                text,
+                r#"    <origin xyz="{x} {y} {z}" rpy="{roll} {pitch} {yaw}"/>"#
+            )?;
+            writeln!(text, "  </joint>")?;
+        }
+        writeln!(text, "</robot>")?;
+
+        Ok(text)
+    }
+}
+
+/// A joint parsed from URDF, which is placed at a position later.
+struct Joint<'a> {
+    name: &'a str,
+    kind: &'a str,
+    src: &'a str,
+    dst: &'a str,
+    xyz: [f64; 3],
+    rpy: [f64; 3],
+    axis: [f64; 3],
+    lower: Option<f64>,
+    upper: Option<f64>,
+    mimic: Option<Mimic<'a>>,
+}
+
+/// The `<mimic>` element. The position of the joint is `multiplier *
+/// position + offset`, where `position` is of the mimicked joint.
+struct Mimic<'a> {
+    joint: &'a str,
+    multiplier: f64,
+    offset: f64,
+}
+
+fn parse_joint<'a>(joint: &roxmltree::Node<'a, '_>) -> Result<Joint<'a>> {
+    let name = required_attribute(joint, "name")?;
+    parse_joint_inner(joint, name).with_context(|| format!("unable to parse the joint '{name}'"))
+}
+
+fn parse_joint_inner<'a>(joint: &roxmltree::Node<'a, '_>, name: &'a str) -> Result<Joint<'a>> {
+    let kind = required_attribute(joint, "type")?;
+    ensure!(
+        matches!(
+            kind,
+            "fixed" | "revolute" | "continuous" | "prismatic" | "planar" | "floating"
+        ),
+        "unknown joint type '{kind}'"
+    );
+    let src = required_attribute(&required_child(joint, "parent")?, "link")?;
+    let dst = required_attribute(&required_child(joint, "child")?, "link")?;
+
+    let origin = child(joint, "origin");
+    let xyz = parse_vector3(origin.and_then(|node| node.attribute("xyz")))?;
+    let rpy = parse_vector3(origin.and_then(|node| node.attribute("rpy")))?;
+
+    let axis = parse_vector3(child(joint, "axis").and_then(|node| node.attribute("xyz")))?
+        .unwrap_or([1.0, 0.0, 0.0]);
+    let limit = child(joint, "limit");
+    let lower = parse_scalar(limit.and_then(|node| node.attribute("lower")))?;
+    let upper = parse_scalar(limit.and_then(|node| node.attribute("upper")))?;
+
+    let mimic = match child(joint, "mimic") {
+        Some(node) => Some(Mimic {
+            joint: required_attribute(&node, "joint")?,
+            multiplier: parse_scalar(node.attribute("multiplier"))?.unwrap_or(1.0),
+            offset: parse_scalar(node.attribute("offset"))?.unwrap_or(0.0),
+        }),
+        None => None,
+    };
+
+    Ok(Joint {
+        name,
+        kind,
+        src,
+        dst,
+        xyz: xyz.unwrap_or([0.0; 3]),
+        rpy: rpy.unwrap_or([0.0; 3]),
+        axis,
+        lower,
+        upper,
+        mimic,
+    })
+}
+
+/// Find the position of a movable joint, or `None` for the other
+/// joints. `visiting` keeps the chain of mimic joints to detect
+/// cycles.
+fn resolve_position<'a>(
+    joint: &Joint<'a>,
+    joint_map: &HashMap<&str, &Joint<'a>>,
+    joint_positions: &HashMap<String, f64>,
+    visiting: &mut Vec<&'a str>,
+) -> Result<Option<f64>> {
+    let Joint {
+        name, kind, mimic, ..
+    } = joint;
+    let position = joint_positions.get(*name).copied();
+    let is_movable = matches!(*kind, "revolute" | "continuous" | "prismatic");
+
+    if !is_movable {
+        ensure!(
+            position.is_none(),
+            "unable to set the position of a {kind} joint"
+        );
+        return Ok(None);
+    }
+
+    if let Some(Mimic {
+        joint: leader,
+        multiplier,
+        offset,
+    }) = mimic
+    {
+        ensure!(
+            position.is_none(),
+            "unable to set the position of a joint mimicking '{leader}'"
+        );
+        ensure!(
+            !visiting.contains(leader),
+            "the joint '{leader}' mimics itself through '{name}'"
+        );
+        let Some(leader_joint) = joint_map.get(leader) else {
+            bail!("the mimicked joint '{leader}' is not found");
+        };
+
+        visiting.push(*name);
+        let leader_position = resolve_position(leader_joint, joint_map, joint_positions, visiting)?;
+        visiting.pop();
+
+        let Some(leader_position) = leader_position else {
+            bail!("the mimicked joint '{leader}' is not movable");
+        };
+        return Ok(Some(multiplier * leader_position + offset));
+    }
+
+    let position = match *kind {
+        "continuous" => position.unwrap_or(0.0),
+        _ => {
+            let position = position.unwrap_or(0.0);
+            let position = joint.lower.map_or(position, |lower| position.max(lower));
+            joint.upper.map_or(position, |upper| position.min(upper))
+        }
+    };
+    Ok(Some(position))
+}
+
+impl Joint<'_> {
+    /// Place the joint at the position from [resolve_position].
+    fn to_coord_transform(&self, position: Option<f64>) -> Result<CoordTransform> {
+        let Self {
+            name,
+            kind,
+            src,
+            dst,
+            xyz,
+            rpy,
+            axis,
+            ..
+        } = *self;
+
+        let motion = match (kind, position) {
+            ("revolute" | "continuous", Some(position)) => Some(revolute_motion(axis, position)),
+            ("prismatic", Some(position)) => Some(prismatic_motion(axis, position)),
+            _ => None,
+        };
+        let motion = motion
+            .transpose()
+            .with_context(|| format!("unable to parse the joint '{name}'"))?;
+
+        let tf = match motion {
+            // Keep the origin values as they are written.
+            None => Transform {
+                r: Euler {
+                    order: "rpy".parse()?,
+                    angles: rpy
+                        .into_iter()
+                        .map(|val| Angle::from_radians(r64(val)))
+                        .collect(),
+                    convention: EulerConvention::Extrinsic,
+                }
+                .into(),
+                t: Translation::from_meters(xyz.map(r64)),
+                cov: None,
+            },
+            Some(motion) => {
+                let origin = na::Isometry3::from_parts(
+                    xyz.into(),
+                    na::UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2]),
+                );
+                let tf = Transform::from(origin * motion);
+                Transform {
+                    r: tf.r.into_euler_format().into_radians(),
+                    ..tf
+                }
+            }
+        };
+
+        Ok(CoordTransform {
+            src: src.to_string(),
+            dst: dst.to_string(),
+            tf,
+        })
+    }
+}
+
+fn revolute_motion(axis: [f64; 3], angle: f64) -> Result<na::Isometry3<f64>> {
+    let axis = unit_axis(axis)?;
+    Ok(na::Isometry3::from_parts(
+        na::Translation3::identity(),
+        na::UnitQuaternion::from_axis_angle(&axis, angle),
+    ))
+}
+
+fn prismatic_motion(axis: [f64; 3], distance: f64) -> Result<na::Isometry3<f64>> {
+    let axis = unit_axis(axis)?;
+    Ok(na::Translation3::from(axis.into_inner() * distance).into())
+}
+
+fn unit_axis(axis: [f64; 3]) -> Result<na::Unit<na::Vector3<f64>>> {
+    let Some(axis) = na::Unit::try_new(na::Vector3::from(axis), 1e-9) else {
+        bail!("the joint axis must be non-zero");
+    };
+    Ok(axis)
+}
+
+fn child<'a, 'input>(
+    node: &roxmltree::Node<'a, 'input>,
+    tag: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+fn required_child<'a, 'input>(
+    node: &roxmltree::Node<'a, 'input>,
+    tag: &str,
+) -> Result<roxmltree::Node<'a, 'input>> {
+    child(node, tag).with_context(|| format!("missing <{tag}> element"))
+}
+
+fn required_attribute<'a>(node: &roxmltree::Node<'a, '_>, name: &str) -> Result<&'a str> {
+    node.attribute(name).with_context(|| {
+        format!(
+            "missing \"{name}\" attribute in <{}>",
+            node.tag_name().name()
+        )
+    })
+}
+
+fn parse_scalar(text: Option<&str>) -> Result<Option<f64>> {
+    let Some(text) = text else {
+        return Ok(None);
+    };
+    let value: f64 = text
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid number '{text}'"))?;
+    ensure!(value.is_finite(), "invalid number '{text}'");
+    Ok(Some(value))
+}
+
+fn parse_vector3(text: Option<&str>) -> Result<Option<[f64; 3]>> {
+    let Some(text) = text else {
+        return Ok(None);
+    };
+    let values: Vec<f64> = text
+        .split_whitespace()
+        .map(|token| Ok(parse_scalar(Some(token))?.unwrap()))
+        .collect::<Result<_>>()?;
+    let Ok(values) = <[f64; 3]>::try_from(values) else {
+        bail!("expect three numbers, but found '{text}'");
+    };
+    Ok(Some(values))
+}
+
+/// Avoid writing "-0".
+fn positive_zero(value: f64) -> f64 {
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{Rotation, RotationMean, Transform, TransformMean};
+
+const EPSILON: f64 = 1e-7;
+
+#[test]
+fn average_rotation() -> Result<()> {
+    let angle = 0.1;
+    let rots: Vec<Rotation> = [-angle, angle]
+        .into_iter()
+        .map(|yaw| na::UnitQuaternion::from_euler_angles(0.0, 0.0, yaw).into())
+        .collect();
+
+    let RotationMean { mean, angular_std } = Rotation::mean(&rots)?;
+    let mean: na::UnitQuaternion<f64> = mean.into();
+    assert_abs_diff_eq!(mean.angle(), 0.0, epsilon = EPSILON);
+    assert_abs_diff_eq!(angular_std, angle, epsilon = EPSILON);
+
+    // The full weight on the second rotation yields it as the mean.
+    let RotationMean { mean, angular_std } = Rotation::weighted_mean(&rots, &[0.0, 1.0])?;
+    let mean: na::UnitQuaternion<f64> = mean.into();
+    let expect: na::UnitQuaternion<f64> = rots[1].clone().into();
+    assert_abs_diff_eq!(mean.angle_to(&expect), 0.0, epsilon = EPSILON);
+    assert_abs_diff_eq!(angular_std, 0.0, epsilon = EPSILON);
+
+    Ok(())
+}
+
+#[test]
+fn average_transform() -> Result<()> {
+    let tfs: Vec<Transform> = [
+        na::Isometry3::translation(1.0, 0.0, 0.0),
+        na::Isometry3::translation(-1.0, 0.0, 0.0),
+        na::Isometry3::translation(0.0, 1.0, 0.0),
+        na::Isometry3::translation(0.0, -1.0, 0.0),
+    ]
+    .into_iter()
+    .map(Transform::from)
+    .collect();
+
+    let TransformMean {
+        mean,
+        angular_std,
+        translation_std,
+    } = Transform::mean(&tfs)?;
+    let mean: na::Isometry3<f64> = mean.into();
+    assert_abs_diff_eq!(mean, na::Isometry3::identity(), epsilon = EPSILON);
+    assert_abs_diff_eq!(angular_std, 0.0, epsilon = EPSILON);
+    assert_abs_diff_eq!(translation_std, 1.0, epsilon = EPSILON);
+
+    Ok(())
+}
+
+#[test]
+fn average_invalid_weights() {
+    let rots = vec![Rotation::from(na::UnitQuaternion::<f64>::identity()); 2];
+    assert!(Rotation::mean(&[]).is_err());
+    assert!(Rotation::weighted_mean(&rots, &[1.0]).is_err());
+    assert!(Rotation::weighted_mean(&rots, &[1.0, -1.0]).is_err());
+    assert!(Rotation::weighted_mean(&rots, &[0.0, 0.0]).is_err());
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{AxisConvention, CoordTransform, Rotation, Transform, TransformSet, Translation};
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn convention_parsing() -> Result<()> {
+    assert_eq!("flu".parse::<AxisConvention>()?, AxisConvention::FLU);
+    assert_eq!("ENU".parse::<AxisConvention>()?, AxisConvention::FLU);
+    assert_eq!("ned".parse::<AxisConvention>()?, AxisConvention::NED);
+    assert_eq!("unity".parse::<AxisConvention>()?, AxisConvention::RUF);
+    assert_eq!(AxisConvention::RDF.to_string(), "rdf");
+    assert!(!AxisConvention::RUF.is_right_handed());
+    assert!("ffu".parse::<AxisConvention>().is_err());
+    assert!("fl".parse::<AxisConvention>().is_err());
+
+    // ENU to NED swaps x and y and negates z.
+    let basis = AxisConvention::ENU.change_of_basis(&AxisConvention::NED);
+    assert_abs_diff_eq!(
+        basis * na::Vector3::new(1.0, 2.0, 3.0),
+        na::Vector3::new(2.0, 1.0, -3.0)
+    );
+    Ok(())
+}
+
+#[test]
+fn transform_change_convention() -> Result<()> {
+    let iso = na::Isometry3::new(
+        na::Vector3::new(1.0, -2.0, 0.5),
+        na::Vector3::new(0.1, 0.2, 0.3),
+    );
+    let tf: Transform = Transform::from(iso).into_euler_format();
+
+    // Changing both frames transforms points consistently.
+    for to in [
+        AxisConvention::RDF,
+        AxisConvention::NED,
+        AxisConvention::RUF,
+    ] {
+        let basis = AxisConvention::FLU.change_of_basis(&to);
+        let changed = tf.change_convention(&AxisConvention::FLU, &to);
+        assert!(matches!(changed.r, Rotation::Euler(_)));
+
+        let point = na::Vector3::new(0.3, 0.7, -1.1);
+        let expect = basis * iso.transform_point(&point.into()).coords;
+        let actual: [f64; 3] = changed.transform_point((basis * point).into());
+        assert_abs_diff_eq!(na::Vector3::from(actual), expect, epsilon = EPSILON);
+
+        let back = changed.change_convention(&to, &AxisConvention::FLU);
+        let back: na::Isometry3<f64> = back.into();
+        assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+    }
+
+    // Changing the destination of an identity gives the change of
+    // basis between the frames.
+    let id = Transform {
+        r: na::UnitQuaternion::<f64>::identity().into(),
+        t: Translation::zero(),
+        cov: None,
+    };
+    let body_to_optical = id.change_dst_convention(&AxisConvention::FLU, &AxisConvention::RDF)?;
+    let optical_z: [f64; 3] = body_to_optical.transform_vector([0.0, 0.0, 1.0]);
+    assert_abs_diff_eq!(&optical_z[..], &[1.0, 0.0, 0.0][..], epsilon = EPSILON);
+
+    // A single frame cannot change the handedness.
+    assert!(tf
+        .change_src_convention(&AxisConvention::FLU, &AxisConvention::RUF)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn transform_set_change_convention() -> Result<()> {
+    let car_to_camera = na::Isometry3::new(
+        na::Vector3::new(1.5, 0.0, 1.2),
+        na::Vector3::new(0.0, 0.1, 0.0),
+    );
+    let set = TransformSet::try_from_iter([CoordTransform {
+        src: "car".to_string(),
+        dst: "camera".to_string(),
+        tf: car_to_camera.into(),
+    }])?;
+
+    let set = set.change_coord_convention("camera", &AxisConvention::FLU, &AxisConvention::RDF)?;
+    let basis = AxisConvention::FLU.change_of_basis(&AxisConvention::RDF);
+    let point = na::Point3::new(0.3, 0.7, 5.0);
+    let expect = car_to_camera.transform_point(&(basis.transpose() * point.coords).into());
+    let actual = set.get("car", "camera").unwrap().transform_point(&point);
+    assert_abs_diff_eq!(actual, expect, epsilon = EPSILON);
+
+    assert!(set
+        .change_coord_convention("lidar", &AxisConvention::FLU, &AxisConvention::RDF)
+        .is_err());
+
+    let set = set.change_convention(&AxisConvention::FLU, &AxisConvention::NED)?;
+    let basis = AxisConvention::FLU.change_of_basis(&AxisConvention::NED);
+    let actual = set
+        .get("car", "camera")
+        .unwrap()
+        .transform_point(&(basis * point.coords).into());
+    assert_abs_diff_eq!(actual.coords, basis * expect.coords, epsilon = EPSILON);
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{AffineMatrix, DualQuaternion, HomogeneousMatrix, Rotation, Transform};
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn try_from_na_isometry3() {
+    let iso = na::Isometry3::new(
+        na::Vector3::new(1.0, -2.0, 0.5),
+        na::Vector3::new(0.1, 0.2, 0.3),
+    );
+    let tf = Transform::try_from_na_isometry3(&iso).unwrap();
+    let back: na::Isometry3<f64> = tf.into();
+    assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+
+    let mut iso = iso;
+    iso.translation.vector.y = f64::NAN;
+    let err = Transform::try_from_na_isometry3(&iso).unwrap_err();
+    assert_eq!(err.component, "translation.y");
+    assert!(err.value.is_nan());
+
+    let iso = na::Isometry3::from_parts(
+        na::Translation3::new(0.0, 0.0, 0.0),
+        na::Unit::new_unchecked(na::Quaternion::new(f64::INFINITY, 0.0, 0.0, 0.0)),
+    );
+    let err = Transform::try_from_na_isometry3(&iso).unwrap_err();
+    assert_eq!(err.component, "rotation.w");
+    assert_eq!(err.value, f64::INFINITY);
+}
+
+#[test]
+fn try_from_na_unit_quaternion() {
+    let quat = na::UnitQuaternion::from_euler_angles(0.1f32, -0.2, 0.3);
+    let rot = Rotation::try_from_na_unit_quaternion(&quat).unwrap();
+    let back: na::UnitQuaternion<f32> = rot.into();
+    assert_abs_diff_eq!(back, quat, epsilon = 1e-6);
+
+    let quat = na::Unit::new_unchecked(na::Quaternion::new(1.0, 0.0, f64::NAN, 0.0));
+    let err = Rotation::try_from_na_unit_quaternion(&quat).unwrap_err();
+    assert_eq!(err.component, "rotation.j");
+    assert_eq!(
+        err.to_string(),
+        "the rotation.j component has invalid value NaN"
+    );
+}
+
+#[test]
+fn try_from_na_isometry3_outputs() {
+    let iso = na::Isometry3::new(
+        na::Vector3::new(1.0, -2.0, 0.5),
+        na::Vector3::new(0.1, 0.2, 0.3),
+    );
+    let mat = AffineMatrix::try_from_na_isometry3(&iso).unwrap();
+    let back: na::Isometry3<f64> = mat.into();
+    assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+    let mat = HomogeneousMatrix::try_from_na_isometry3(&iso).unwrap();
+    let back: na::Isometry3<f64> = mat.try_into().unwrap();
+    assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+    let dual_quat = DualQuaternion::try_from_na_isometry3(&iso).unwrap();
+    let back: na::Isometry3<f64> = dual_quat.try_into().unwrap();
+    assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+
+    let mut iso = iso;
+    iso.translation.vector.z = f64::NEG_INFINITY;
+    let err = AffineMatrix::try_from_na_isometry3(&iso).unwrap_err();
+    assert_eq!(err.component, "translation.z");
+    assert!(HomogeneousMatrix::try_from_na_isometry3(&iso).is_err());
+    assert!(DualQuaternion::try_from_na_isometry3(&iso).is_err());
+}
+
+#[test]
+fn try_from_na_unit_dual_quaternion() {
+    let iso = na::Isometry3::new(
+        na::Vector3::new(1.0, -2.0, 0.5),
+        na::Vector3::new(0.1, 0.2, 0.3),
+    );
+    let dual_quat = DualQuaternion::try_from_na_unit_dual_quaternion(
+        &na::UnitDualQuaternion::from_isometry(&iso),
+    )
+    .unwrap();
+    let back: na::Isometry3<f64> = dual_quat.try_into().unwrap();
+    assert_abs_diff_eq!(back, iso, epsilon = EPSILON);
+
+    let mut iso = iso;
+    iso.translation.vector.y = f64::NAN;
+    let dual_quat = na::UnitDualQuaternion::from_isometry(&iso);
+    let err = DualQuaternion::try_from_na_unit_dual_quaternion(&dual_quat).unwrap_err();
+    assert!(err.component.starts_with("dual."), "{err}");
+}
+
+#[test]
+fn try_interpolate_non_finite() {
+    let start = Transform::from(na::Isometry3::translation(f64::MAX, 0.0, 0.0));
+    let end = Transform::from(na::Isometry3::translation(-f64::MAX, 0.0, 0.0));
+
+    // The extrapolated translation overflows.
+    let err = start.try_interpolate(&end, 2.0).unwrap_err();
+    assert_eq!(err.component, "translation.x");
+    assert!(start.try_sclerp(&end, 2.0).is_err());
+
+    let tf = start.try_interpolate(&start, 0.5).unwrap();
+    assert_eq!(tf.t.as_meters_values()[0], f64::MAX);
+}
@@ -0,0 +1,257 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{
+    CoordTransform, Covariance, MaybeTransform, Perturbation, TangentOrder, Transform, TransformSet,
+};
+
+const EPSILON: f64 = 1e-9;
+
+fn make_tf(tx: f64, ty: f64, tz: f64, rx: f64, ry: f64, rz: f64) -> Transform {
+    na::Isometry3::new(na::Vector3::new(tx, ty, tz), na::Vector3::new(rx, ry, rz)).into()
+}
+
+fn outer(vec: &na::Vector6<f64>) -> na::Matrix6<f64> {
+    vec * vec.transpose()
+}
+
+/// Returns the right perturbation `log(T⁻¹ * perturbed)`.
+fn right_delta(tf: &Transform, perturbed: &Transform) -> na::Vector6<f64> {
+    (&tf.inverse() * perturbed).log().into()
+}
+
+/// Returns the left perturbation `log(perturbed * T⁻¹)`.
+fn left_delta(tf: &Transform, perturbed: &Transform) -> na::Vector6<f64> {
+    (perturbed * &tf.inverse()).log().into()
+}
+
+/// Swap `[rho, omega]` to `[omega, rho]`.
+fn rotation_first(vec: &na::Vector6<f64>) -> na::Vector6<f64> {
+    na::Vector6::from_fn(|idx, _| vec[(idx + 3) % 6])
+}
+
+#[test]
+fn covariance_serde() -> Result<()> {
+    let text = r#"{
+        "r": {"format": "quaternion", "ijkw": [0, 0, 0, 1]},
+        "t": [1.0, 2.0, 3.0],
+        "cov": {
+            "order": "rotation-first",
+            "matrix": [
+                [1, 0, 0, 0, 0, 0],
+                [0, 2, 0, 0, 0, 0],
+                [0, 0, 3, 0, 0, 0],
+                [0, 0, 0, 4, 0, 0],
+                [0, 0, 0, 0, 5, 0],
+                [0, 0, 0, 0, 0, "6 * 1"]
+            ]
+        }
+    }"#;
+    let tf: Transform = serde_json::from_str(text)?;
+    let cov = tf.cov.as_ref().unwrap();
+    assert_eq!(cov.perturbation, Perturbation::Right);
+    assert_eq!(cov.order, TangentOrder::RotationFirst);
+    assert_eq!(cov.to_na_matrix6()[(5, 5)], 6.0);
+
+    let value = serde_json::to_value(&tf)?;
+    assert_eq!(value["cov"]["order"], "rotation-first");
+    assert_eq!(value["cov"]["perturbation"], "right");
+
+    // Transforms without covariances are written as before.
+    let tf = make_tf(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    assert!(serde_json::to_value(&tf)?.get("cov").is_none());
+
+    // Asymmetric matrices are rejected.
+    let text = text.replace("[0, 2, 0", "[1, 2, 0");
+    assert!(serde_json::from_str::<Transform>(&text).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn rotation_covariance_serde() -> Result<()> {
+    let rot = make_tf(0.0, 0.0, 0.0, 0.1, 0.2, 0.3).r;
+    let cov = Covariance::from_na_matrix6(&na::Matrix6::from_diagonal_element(0.5));
+    let tf = MaybeTransform {
+        r: rot,
+        t: None,
+        cov: Some(cov),
+    };
+
+    // The rotation stays a rotation without a zero translation.
+    let value = serde_json::to_value(&tf)?;
+    assert!(value.get("t").is_none());
+    assert!(value.get("r").is_some());
+    assert!(value.get("cov").is_some());
+
+    let parsed: MaybeTransform = serde_json::from_value(value)?;
+    assert!(parsed.t.is_none());
+    assert_abs_diff_eq!(
+        parsed.cov.unwrap().to_na_matrix6(),
+        na::Matrix6::from_diagonal_element(0.5),
+        epsilon = EPSILON
+    );
+
+    Ok(())
+}
+
+#[test]
+fn covariance_inverse() {
+    let tf = make_tf(1.0, -2.0, 0.5, 0.1, 0.2, 0.3);
+    let xi = na::Vector6::new(1e-3, -2e-3, 3e-3, 2e-3, 1e-3, -1e-3);
+
+    // A rank-one covariance propagates like the perturbation.
+    let perturbed = &tf * &Transform::exp(xi.into());
+    let expect = outer(&right_delta(&tf.inverse(), &perturbed.inverse()));
+
+    let inv = tf
+        .clone()
+        .with_covariance(Covariance::from_na_matrix6(&outer(&xi)))
+        .inverse();
+    let actual = inv.cov.unwrap().to_na_matrix6();
+    assert_abs_diff_eq!(actual, expect, epsilon = EPSILON);
+
+    // The same for the left perturbation in rotation-first order.
+    let perturbed = &Transform::exp(xi.into()) * &tf;
+    let expect = outer(&rotation_first(&left_delta(
+        &tf.inverse(),
+        &perturbed.inverse(),
+    )));
+
+    let cov = Covariance::from_na_matrix6_with(
+        &outer(&rotation_first(&xi)),
+        Perturbation::Left,
+        TangentOrder::RotationFirst,
+    );
+    let inv = tf.with_covariance(cov).inverse();
+    let cov = inv.cov.unwrap();
+    assert_eq!(cov.perturbation, Perturbation::Left);
+    assert_eq!(cov.order, TangentOrder::RotationFirst);
+    assert_abs_diff_eq!(cov.to_na_matrix6(), expect, epsilon = EPSILON);
+}
+
+#[test]
+fn covariance_product() {
+    let lhs = make_tf(1.0, -2.0, 0.5, 0.1, 0.2, 0.3);
+    let rhs = make_tf(-0.5, 0.3, 2.0, -0.4, 0.0, 0.7);
+    let xi = na::Vector6::new(1e-3, -2e-3, 3e-3, 2e-3, 1e-3, -1e-3);
+    let zeta = na::Vector6::new(-1e-3, 1e-3, 2e-3, 0.0, -3e-3, 1e-3);
+
+    // The right perturbation of rhs passes through, while the one of
+    // lhs is moved across rhs.
+    let prod = &lhs * &rhs;
+    let perturbed = &(&lhs * &Transform::exp(xi.into())) * &rhs;
+    let expect = outer(&right_delta(&prod, &perturbed)) + outer(&zeta);
+
+    let lhs = lhs.with_covariance(Covariance::from_na_matrix6(&outer(&xi)));
+    let rhs_cov = rhs
+        .clone()
+        .with_covariance(Covariance::from_na_matrix6(&outer(&zeta)));
+    let actual = (&lhs * &rhs_cov).cov.unwrap().to_na_matrix6();
+    assert_abs_diff_eq!(actual, expect, epsilon = EPSILON);
+
+    // Operands without covariances contribute nothing.
+    let actual = (&lhs * &rhs).cov.unwrap().to_na_matrix6();
+    assert_abs_diff_eq!(actual, expect - outer(&zeta), epsilon = EPSILON);
+    assert!((&rhs * &rhs).cov.is_none());
+}
+
+#[test]
+fn covariance_transform_set() -> Result<()> {
+    let car_to_lidar1 = make_tf(10.0, 0.0, 3.0, 0.0, 0.0, 0.5);
+    let car_to_lidar2 = make_tf(-10.0, 0.0, 3.0, 0.0, 0.0, -0.5);
+    let cov1 = Covariance::from_na_matrix6(&na::Matrix6::from_diagonal_element(1e-4));
+    let cov2 = Covariance::from_na_matrix6(&na::Matrix6::from_diagonal_element(4e-4));
+
+    let set = TransformSet::try_from_iter([
+        CoordTransform {
+            src: "car".to_string(),
+            dst: "lidar1".to_string(),
+            tf: car_to_lidar1.clone().with_covariance(cov1.clone()),
+        },
+        CoordTransform {
+            src: "car".to_string(),
+            dst: "lidar2".to_string(),
+            tf: car_to_lidar2.clone().with_covariance(cov2.clone()),
+        },
+    ])?;
+
+    let expect =
+        &car_to_lidar1.with_covariance(cov1).inverse() * &car_to_lidar2.with_covariance(cov2);
+    let actual = set.get_transform("lidar1", "lidar2").unwrap();
+    let actual_iso: na::Isometry3<f64> = actual.clone().into();
+    assert_abs_diff_eq!(
+        actual_iso,
+        set.get("lidar1", "lidar2").unwrap(),
+        epsilon = EPSILON
+    );
+    assert_abs_diff_eq!(
+        actual.cov.unwrap().to_na_matrix6(),
+        expect.cov.unwrap().to_na_matrix6(),
+        epsilon = EPSILON
+    );
+
+    // The covariances survive serialization.
+    let text = serde_json::to_string(&set)?;
+    let set: TransformSet = serde_json::from_str(&text)?;
+    assert!(set.get_transform("car", "lidar2").unwrap().cov.is_some());
+    assert!(set.get_transform("car", "xxx").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn covariance_set_round_trip() -> Result<()> {
+    let cov = Covariance::from_na_matrix6(&na::Matrix6::from_diagonal_element(0.01));
+    let mut set = TransformSet::new();
+    let mut ego_to_cam = make_tf(1.0, 0.0, 1.5, 0.0, 0.0, 0.3);
+    ego_to_cam.r = ego_to_cam.r.into_euler_format();
+    set.insert_transform("ego", "cam", ego_to_cam.with_covariance(cov.clone()))?;
+    set.insert_transform(
+        "ego",
+        "lidar",
+        make_tf(0.5, 0.0, 2.0, 0.0, 0.1, 0.0).with_covariance(cov),
+    )?;
+
+    // The inserted transforms are written as they are, so the
+    // covariances are not combined again on reload.
+    let text = serde_json::to_string(&set)?;
+    let restored: TransformSet = serde_json::from_str(&text)?;
+    for (src, dst) in [("ego", "lidar"), ("cam", "lidar"), ("lidar", "cam")] {
+        assert_abs_diff_eq!(
+            restored
+                .get_transform(src, dst)
+                .unwrap()
+                .cov
+                .unwrap()
+                .to_na_matrix6(),
+            set.get_transform(src, dst)
+                .unwrap()
+                .cov
+                .unwrap()
+                .to_na_matrix6(),
+            epsilon = EPSILON
+        );
+    }
+    assert_abs_diff_eq!(
+        restored
+            .get_transform("ego", "lidar")
+            .unwrap()
+            .cov
+            .unwrap()
+            .to_na_matrix6(),
+        na::Matrix6::from_diagonal_element(0.01),
+        epsilon = EPSILON
+    );
+
+    // The rotation formats are kept.
+    let formats = |set: &TransformSet| -> Result<Vec<_>> {
+        let value = serde_json::to_value(set.transforms())?;
+        Ok((0..2)
+            .map(|idx| value[idx]["tf"]["r"]["format"].clone())
+            .collect())
+    };
+    assert_eq!(formats(&restored)?, ["euler", "quaternion"]);
+
+    Ok(())
+}
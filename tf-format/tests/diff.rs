@@ -0,0 +1,48 @@
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use std::f64::consts::FRAC_PI_2;
+use tf_format::{Rotation, RotationDiff, Transform, TransformDiff};
+
+const EPSILON: f64 = 1e-7;
+
+#[test]
+fn rotation_diff() {
+    let lhs: Rotation = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.1).into();
+    let rhs: Rotation = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.1 + FRAC_PI_2).into();
+
+    // The formats of operands do not affect the difference.
+    let RotationDiff { angle, axis } = lhs
+        .clone()
+        .into_euler_format()
+        .diff(&rhs.clone().into_rotation_matrix_format());
+    assert_abs_diff_eq!(angle, FRAC_PI_2, epsilon = EPSILON);
+    let axis = axis.unwrap();
+    assert_abs_diff_eq!(na::Vector3::from(axis), na::Vector3::z(), epsilon = EPSILON);
+
+    let RotationDiff { angle, axis } = lhs.diff(&lhs.clone().into_quaternion_format());
+    assert_abs_diff_eq!(angle, 0.0, epsilon = EPSILON);
+    assert!(axis.is_none());
+}
+
+#[test]
+fn transform_diff() {
+    let lhs: Transform = na::Isometry3::translation(1.0, 2.0, 3.0).into();
+    let rhs: Transform = na::Isometry3::from_parts(
+        na::Translation3::new(1.0, 5.0, 7.0),
+        na::UnitQuaternion::from_euler_angles(FRAC_PI_2, 0.0, 0.0),
+    )
+    .into();
+
+    let TransformDiff {
+        rotation,
+        translation,
+        translation_norm,
+    } = lhs.diff(&rhs);
+    assert_abs_diff_eq!(rotation.angle, FRAC_PI_2, epsilon = EPSILON);
+    assert_abs_diff_eq!(
+        na::Vector3::from(translation),
+        na::Vector3::new(0.0, 3.0, 4.0),
+        epsilon = EPSILON
+    );
+    assert_abs_diff_eq!(translation_norm, 5.0, epsilon = EPSILON);
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use serde::Deserialize;
+use std::{f64::consts::FRAC_PI_2, fs::File, io::BufReader, path::Path};
+use tf_format::{Rotation, Transform};
+
+const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/example_config");
+
+const EPSILON: f64 = 1e-7;
+
+#[test]
+fn slerp_rotation() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+
+    let start: Rotation = load_json(config_dir.join("rot_axis_angle.json"))?;
+    let end: Rotation = load_json(config_dir.join("rot_quaternion.json"))?;
+    let start_quat: na::UnitQuaternion<f64> = start.clone().into();
+    let end_quat: na::UnitQuaternion<f64> = end.clone().into();
+
+    for t in [0.0, 0.25, 0.5, 1.0] {
+        let rot = start.slerp(&end, t);
+        assert!(matches!(rot, Rotation::AxisAngle(_)));
+
+        let quat: na::UnitQuaternion<f64> = rot.into();
+        let expect = start_quat.slerp(&end_quat, t);
+        assert_abs_diff_eq!(quat, expect, epsilon = EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn interpolate_transform() {
+    let start: Transform = na::Isometry3::translation(1.0, 0.0, 0.0).into();
+    let end: Transform = na::Isometry3::from_parts(
+        na::Translation3::new(0.0, 1.0, 0.0),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2),
+    )
+    .into();
+
+    // Linear interpolation moves along the chord.
+    let mid: na::Isometry3<f64> = start.interpolate(&end, 0.5).into();
+    let expect = na::Isometry3::from_parts(
+        na::Translation3::new(0.5, 0.5, 0.0),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2 / 2.0),
+    );
+    assert_abs_diff_eq!(mid, expect, epsilon = EPSILON);
+
+    // Screw interpolation rotates the point around the z axis.
+    let mid: na::Isometry3<f64> = start.sclerp(&end, 0.5).into();
+    let half = FRAC_PI_2 / 2.0;
+    let expect = na::Isometry3::from_parts(
+        na::Translation3::new(half.cos(), half.sin(), 0.0),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, half),
+    );
+    assert_abs_diff_eq!(mid, expect, epsilon = EPSILON);
+
+    for (t, expect) in [(0.0, &start), (1.0, &end)] {
+        let expect: na::Isometry3<f64> = expect.clone().into();
+        let tf: na::Isometry3<f64> = start.interpolate(&end, t).into();
+        assert_abs_diff_eq!(tf, expect, epsilon = EPSILON);
+        let tf: na::Isometry3<f64> = start.sclerp(&end, t).into();
+        assert_abs_diff_eq!(tf, expect, epsilon = EPSILON);
+    }
+}
+
+fn load_json<T, P>(path: P) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    let data = serde_json::from_reader(reader)?;
+    Ok(data)
+}
@@ -38,6 +38,7 @@ fn inverse_rotation() -> Result<()> {
         let Euler {
             order: EulerAxisOrder(order),
             angles,
+            ..
         } = euler;
 
         let [r, p, y] = *angles else { panic!() };
@@ -73,7 +74,7 @@ fn inverse_rotation() -> Result<()> {
         let Rotation::Quaternion(quat) = rot else {
             panic!();
         };
-        let Quaternion { ijkw } = quat;
+        let Quaternion { ijkw, .. } = quat;
         assert_abs_diff_eq_list!(ijkw.iter().map(|v| v.raw()), [0.0, 0.0, 0.0, 1.0]);
     }
 
@@ -103,6 +104,7 @@ fn inverse_rotation() -> Result<()> {
         let Transform {
             t: Translation(trans),
             r: Rotation::Euler(euler),
+            ..
         } = tf
         else {
             panic!();
@@ -114,7 +116,10 @@ fn inverse_rotation() -> Result<()> {
             ..
         } = euler;
 
-        assert_abs_diff_eq_list!(trans.iter().map(|v| v.raw()), [1.0, -2.0, 0.3]);
+        assert_abs_diff_eq_list!(
+            trans.iter().map(|v| v.as_meters_value().raw()),
+            [1.0, -2.0, 0.3]
+        );
         assert_eq!(
             order,
             vec![EulerAxis::Roll, EulerAxis::Pitch, EulerAxis::Yaw]
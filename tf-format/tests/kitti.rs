@@ -0,0 +1,97 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{KittiOptions, TransformSet};
+
+const EPSILON: f64 = 1e-9;
+
+/// The calibration of the KITTI object detection frame 000000.
+const CALIB: &str = "\
+P0: 7.070493000000e+02 0.000000000000e+00 6.040814000000e+02 0.000000000000e+00 0.000000000000e+00 7.070493000000e+02 1.805066000000e+02 0.000000000000e+00 0.000000000000e+00 0.000000000000e+00 1.000000000000e+00 0.000000000000e+00
+R0_rect: 9.999128000000e-01 1.009263000000e-02 -8.511932000000e-03 -1.012729000000e-02 9.999406000000e-01 -4.037671000000e-03 8.470675000000e-03 4.123522000000e-03 9.999556000000e-01
+Tr_velo_to_cam: 6.927964000000e-03 -9.999722000000e-01 -2.757829000000e-03 -2.457729000000e-02 -1.162982000000e-03 2.749836000000e-03 -9.999955000000e-01 -6.127237000000e-02 9.999753000000e-01 6.931141000000e-03 -1.143899000000e-03 -3.321029000000e-01
+Tr_imu_to_velo: 9.999976000000e-01 7.553071000000e-04 -2.035826000000e-03 -8.086759000000e-01 -7.854027000000e-04 9.998898000000e-01 -1.482298000000e-02 3.195559000000e-01 2.024406000000e-03 1.482454000000e-02 9.998881000000e-01 -7.997231000000e-01
+";
+
+fn velo_to_cam() -> na::Matrix4<f64> {
+    na::Matrix4::new(
+        6.927964e-03,
+        -9.999722e-01,
+        -2.757829e-03,
+        -2.457729e-02,
+        -1.162982e-03,
+        2.749836e-03,
+        -9.999955e-01,
+        -6.127237e-02,
+        9.999753e-01,
+        6.931141e-03,
+        -1.143899e-03,
+        -3.321029e-01,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    )
+}
+
+#[test]
+fn kitti_read() -> Result<()> {
+    let set = TransformSet::from_kitti_str(CALIB, &KittiOptions::default())?;
+
+    // The matrix maps velodyne points to camera points.
+    let point = na::Point3::new(10.0, 2.0, -1.0);
+    let cam_point = set.get("cam0", "velo").unwrap() * point;
+    let expect = velo_to_cam() * point.to_homogeneous();
+    assert_abs_diff_eq!(cam_point.coords, expect.xyz(), epsilon = 1e-5);
+
+    assert!(set.get("imu", "cam0_rect").is_some());
+    assert!(
+        set.get("cam0_rect", "cam0")
+            .unwrap()
+            .translation
+            .vector
+            .norm()
+            < EPSILON
+    );
+
+    // Custom frame names.
+    let mut options = KittiOptions::default();
+    options.set_frames("Tr_velo_to_cam", "lidar", "camera");
+    let set = TransformSet::from_kitti_str(CALIB, &options)?;
+    assert!(set.get("lidar", "camera").is_some());
+    assert!(set.get("velo", "camera").is_none());
+
+    // Malformed entries are rejected.
+    let text = CALIB.replace("Tr_imu_to_velo: 9.999976000000e-01 ", "Tr_imu_to_velo: ");
+    assert!(TransformSet::from_kitti_str(&text, &KittiOptions::default()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn kitti_write() -> Result<()> {
+    let options = KittiOptions::default();
+    let set = TransformSet::from_kitti_str(CALIB, &options)?;
+    let text = set.to_kitti_string(&options)?;
+
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("R0_rect: 9.99912"));
+    assert!(lines[1].starts_with("Tr_velo_to_cam: 6.92"));
+    assert!(lines[1].ends_with(" -3.321029000000e-01"));
+
+    let restored = TransformSet::from_kitti_str(&text, &options)?;
+    for (src, dst) in [("cam0", "velo"), ("cam0_rect", "imu")] {
+        assert_abs_diff_eq!(
+            restored.get(src, dst).unwrap(),
+            set.get(src, dst).unwrap(),
+            epsilon = EPSILON
+        );
+    }
+
+    // Sets without any of the entries are rejected.
+    let set = TransformSet::from_kitti_str("P0: 1 2 3", &options)?;
+    assert!(set.to_kitti_string(&options).is_err());
+
+    Ok(())
+}
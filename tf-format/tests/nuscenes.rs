@@ -0,0 +1,134 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use std::path::Path;
+use tf_format::{NuScenes, TransformSet};
+
+const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/example_config");
+
+fn load() -> Result<NuScenes> {
+    NuScenes::from_dir(Path::new(CONFIG_DIR).join("nuscenes"))
+}
+
+#[test]
+fn nuscenes_sensor_set() -> Result<()> {
+    let dataset = load()?;
+    assert_eq!(dataset.calibrated_sensors.len(), 3);
+    assert_eq!(dataset.calibrated_sensors[0].channel, "CAM_FRONT");
+
+    // The lidar has different calibrations in two logs.
+    assert!(dataset.sensor_set().is_err());
+
+    let set = dataset.sensor_set_from_tokens([
+        "1d31c729b073425e8e0202c5c6e66ee1",
+        "a183049901c24361a6b0b11b8013137c",
+    ])?;
+    let ego_to_lidar = set.get(NuScenes::EGO_FRAME, "LIDAR_TOP").unwrap();
+    assert_abs_diff_eq!(
+        ego_to_lidar.translation.vector,
+        na::Vector3::new(0.943713, 0.0, 1.84023),
+        epsilon = 1e-9
+    );
+
+    // The quaternion is in w, x, y, z order.
+    let quat = na::UnitQuaternion::new_normalize(na::Quaternion::new(
+        0.7077955119163518,
+        -0.006492242056004365,
+        0.010646214713995808,
+        -0.7063073142877817,
+    ));
+    assert_abs_diff_eq!(ego_to_lidar.rotation, quat, epsilon = 1e-9);
+    assert!(set.get("CAM_FRONT", "LIDAR_TOP").is_some());
+
+    assert!(dataset.sensor_set_from_tokens(["xxx"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn nuscenes_scene_selection() -> Result<()> {
+    let dataset = load()?;
+    assert_eq!(dataset.scenes()?.len(), 2);
+    assert_eq!(
+        dataset.scenes()?[1].logfile,
+        "n008-2018-08-01-15-16-36-0400"
+    );
+
+    let lidar_x = |set: &TransformSet| {
+        set.get(NuScenes::EGO_FRAME, "LIDAR_TOP")
+            .unwrap()
+            .translation
+            .x
+    };
+
+    // The scenes of the two logs use different lidar calibrations.
+    let set = dataset.sensor_set_for_scene("scene-0061")?;
+    assert_abs_diff_eq!(lidar_x(&set), 0.943713, epsilon = 1e-9);
+    assert!(set.contains_coord("CAM_FRONT"));
+
+    let set = dataset.sensor_set_for_scene("fcbccedd61424f1b85dcbf8f897f9754")?;
+    assert_abs_diff_eq!(lidar_x(&set), 0.985793, epsilon = 1e-9);
+    assert!(!set.contains_coord("CAM_FRONT"));
+
+    let set = dataset.sensor_set_for_log("n008-2018-08-01-15-16-36-0400")?;
+    assert_abs_diff_eq!(lidar_x(&set), 0.985793, epsilon = 1e-9);
+
+    // The time stamp selects the scene of the nearest sample.
+    let set = dataset.sensor_set_at(1531883530424377)?;
+    assert_abs_diff_eq!(lidar_x(&set), 0.943713, epsilon = 1e-9);
+
+    assert!(dataset.sensor_set_for_scene("scene-9999").is_err());
+    assert!(dataset.sensor_set_for_log("xxx").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn nuscenes_ego_poses() -> Result<()> {
+    let poses = load()?.ego_poses;
+    assert_eq!(poses.len(), 2);
+
+    // The poses are sorted by time stamp.
+    let timestamps: Vec<_> = poses.iter().map(|pose| pose.timestamp).collect();
+    assert_eq!(timestamps, [1531883530399377, 1531883530449377]);
+
+    let nearest = poses.nearest(1531883530440000).unwrap();
+    assert_eq!(nearest.token, "bddd80ae33ec4e32b27fdb3c1160a30e");
+
+    let mid: na::Isometry3<f64> = poses.interpolate(1531883530424377)?.unwrap().into();
+    assert_abs_diff_eq!(
+        mid.translation.vector,
+        na::Vector3::new(
+            (410.77878632230204 + 410.8014053380817) / 2.0,
+            (1179.4673290964536 + 1179.5082036018425) / 2.0,
+            0.0
+        ),
+        epsilon = 1e-6
+    );
+
+    assert!(poses.interpolate(1531883530399376)?.is_none());
+    assert!(poses.interpolate(1531883530449378)?.is_none());
+    assert!(poses.interpolate(1531883530449377)?.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn nuscenes_calibration_tables_only() -> Result<()> {
+    // The scene, log, sample and sample data tables are missing.
+    let dataset = NuScenes::from_dir(Path::new(CONFIG_DIR).join("nuscenes_calibration"))?;
+    assert_eq!(dataset.calibrated_sensors.len(), 3);
+    assert_eq!(dataset.ego_poses.len(), 2);
+
+    let set = dataset.sensor_set_from_tokens(["1d31c729b073425e8e0202c5c6e66ee1"])?;
+    assert!(set.contains_coord(NuScenes::EGO_FRAME));
+    assert_eq!(set.transforms().len(), 1);
+
+    // The tables are needed only to select the calibrations.
+    let err = dataset.sensor_set_for_scene("scene-0061").unwrap_err();
+    assert!(format!("{err:#}").contains("scene.json"), "{err:#}");
+    let err = dataset.sensor_set_at(1531883530424377).unwrap_err();
+    assert!(format!("{err:#}").contains("sample.json"), "{err:#}");
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use noisy_float::types::r64;
+use std::f64::consts::FRAC_PI_2;
+use tf_format::{
+    Angle, AngleUnit, Euler, EulerAxis, EulerAxisOrder, EulerConvention, Length, LengthUnit,
+    MaybeTransform, Rotation, Transform, TransformSet, Translation,
+};
+
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn rotation_mul() {
+    let lhs: Rotation = Euler {
+        order: EulerAxisOrder(vec![EulerAxis::Yaw]),
+        angles: vec![Angle::from_degrees(r64(30.0))],
+        convention: EulerConvention::Extrinsic,
+    }
+    .into();
+    let rhs: Rotation = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 0.5).into();
+
+    let prod = &lhs * &rhs;
+    let Rotation::Euler(Euler { angles, .. }) = &prod else {
+        panic!("expect Euler variant");
+    };
+    assert!(angles.iter().all(|angle| angle.unit == AngleUnit::Degree));
+
+    let quat: na::UnitQuaternion<f64> = prod.into();
+    let expect = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 30f64.to_radians() + 0.5);
+    assert_abs_diff_eq!(quat, expect, epsilon = EPSILON);
+
+    let point = lhs.transform_point([1.0, 0.0, 0.0]);
+    let half = 30f64.to_radians();
+    assert_abs_diff_eq!(
+        na::Vector3::from(point),
+        na::Vector3::new(half.cos(), half.sin(), 0.0),
+        epsilon = EPSILON
+    );
+}
+
+#[test]
+fn euler_results_in_rpy() {
+    let zyz: Rotation = Euler {
+        order: "zyz".parse().unwrap(),
+        angles: [10.0, 20.0, 30.0]
+            .map(|deg| Angle::from_degrees(r64(deg)))
+            .to_vec(),
+        convention: EulerConvention::Intrinsic,
+    }
+    .into();
+    let quat: na::UnitQuaternion<f64> = zyz.clone().into();
+
+    let check = |rot: &Rotation, expect: na::UnitQuaternion<f64>| {
+        let Rotation::Euler(euler) = rot else {
+            panic!("expect Euler variant");
+        };
+        // Only the convention of the input is kept.
+        assert_eq!(euler.order.to_string(), "rpy");
+        assert_eq!(euler.convention, EulerConvention::Intrinsic);
+        let actual: na::UnitQuaternion<f64> = euler.clone().into();
+        assert_abs_diff_eq!(actual.angle_to(&expect), 0.0, epsilon = EPSILON);
+    };
+
+    let inv = zyz.inverse();
+    check(&inv, quat.inverse());
+    check(&inv.inverse(), quat);
+    check(&(&zyz * &zyz), quat * quat);
+
+    let trans = Transform {
+        r: zyz.clone(),
+        t: Translation::from_meters([r64(1.0), r64(2.0), r64(3.0)]),
+        cov: None,
+    };
+    check(&trans.inverse().r, quat.inverse());
+    check(
+        &(&trans * &trans.inverse()).r,
+        na::UnitQuaternion::identity(),
+    );
+}
+
+#[test]
+fn length_unit_preserved() {
+    let mm = |value: f64| Length {
+        unit: LengthUnit::Millimeter,
+        value: r64(value),
+    };
+    let lhs = Transform {
+        r: na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2).into(),
+        t: Translation([mm(1000.0), Length::zero(), mm(-500.0)]),
+        cov: None,
+    };
+    let rhs: Transform = na::Isometry3::translation(0.0, 2.0, 0.0).into();
+    let units = [
+        LengthUnit::Millimeter,
+        LengthUnit::Meter,
+        LengthUnit::Millimeter,
+    ];
+
+    let inv = lhs.inverse();
+    assert_eq!(inv.t.0.map(|v| v.unit), units);
+    assert_abs_diff_eq!(
+        na::Isometry3::<f64>::from(inv),
+        na::Isometry3::from(lhs.clone()).inverse(),
+        epsilon = EPSILON
+    );
+
+    for tf in [
+        lhs.interpolate(&rhs, 0.5),
+        lhs.sclerp(&rhs, 0.5),
+        Transform::mean(&[lhs.clone(), rhs.clone()]).unwrap().mean,
+    ] {
+        assert_eq!(tf.t.0.map(|v| v.unit), units);
+    }
+
+    let mut set = TransformSet::new();
+    set.insert_transform("a", "b", lhs.clone()).unwrap();
+    set.insert_transform("b", "c", rhs.clone()).unwrap();
+    let tf = set.get_transform("a", "c").unwrap();
+    assert_eq!(tf.t.0.map(|v| v.unit), units);
+    assert_abs_diff_eq!(
+        na::Isometry3::<f64>::from(tf),
+        na::Isometry3::from(lhs) * na::Isometry3::from(rhs),
+        epsilon = EPSILON
+    );
+}
+
+#[test]
+fn transform_mul() {
+    let lhs = Transform {
+        r: na::UnitQuaternion::from_euler_angles(0.0, 0.0, FRAC_PI_2).into(),
+        t: Translation([
+            Length {
+                unit: LengthUnit::Millimeter,
+                value: r64(1000.0),
+            },
+            Length::zero(),
+            Length::zero(),
+        ]),
+        cov: None,
+    };
+    let rhs: Transform = na::Isometry3::translation(1.0, 0.0, 0.0).into();
+
+    let prod = &lhs * &rhs;
+    assert!(matches!(prod.r, Rotation::Quaternion(_)));
+    assert_eq!(prod.t.0[0].unit, LengthUnit::Millimeter);
+
+    let iso: na::Isometry3<f64> = prod.clone().into();
+    let expect = na::Isometry3::<f64>::from(lhs.clone()) * na::Isometry3::from(rhs.clone());
+    assert_abs_diff_eq!(iso, expect, epsilon = EPSILON);
+
+    let point = lhs.transform_point(na::Point3::new(1.0, 0.0, 0.0));
+    assert_abs_diff_eq!(point, na::Point3::new(1.0, 1.0, 0.0), epsilon = EPSILON);
+    let vec = lhs.transform_vector([1.0, 0.0, 0.0]);
+    assert_abs_diff_eq!(
+        na::Vector3::from(vec),
+        na::Vector3::new(0.0, 1.0, 0.0),
+        epsilon = EPSILON
+    );
+
+    // A rotation times a transform keeps the translation.
+    let rot: MaybeTransform = Rotation::from(na::UnitQuaternion::<f64>::identity()).into();
+    let prod = rot * MaybeTransform::from(rhs);
+    let iso: na::Isometry3<f64> = prod.to_na_isometry3();
+    assert!(prod.t.is_some());
+    assert_abs_diff_eq!(
+        iso,
+        na::Isometry3::translation(1.0, 0.0, 0.0),
+        epsilon = EPSILON
+    );
+}
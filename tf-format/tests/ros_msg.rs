@@ -0,0 +1,164 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use tf_format::{CoordTransform, TfMessage, TransformSet, TransformStamped};
+
+const EPSILON: f64 = 1e-9;
+
+/// The output of `ros2 topic echo --once /tf_static`.
+const TF_STATIC: &str = r#"
+transforms:
+- header:
+    stamp:
+      sec: 1700000000
+      nanosec: 500
+    frame_id: base_link
+  child_frame_id: lidar
+  transform:
+    translation:
+      x: 0.5
+      y: 0.0
+      z: 1.2
+    rotation:
+      x: 0.0
+      y: 0.0
+      z: 0.7071067811865476
+      w: 0.7071067811865476
+- header:
+    stamp:
+      sec: 1700000000
+      nanosec: 500
+    frame_id: base_link
+  child_frame_id: camera
+  transform:
+    translation:
+      x: 0.3
+      y: -0.1
+      z: 1.0
+    rotation:
+      x: 0.0
+      y: 0.0
+      z: 0.0
+      w: 1.0
+"#;
+
+/// A ROS 1 message with the "seq", "secs" and "nsecs" fields.
+const ROS1_MSG: &str = r#"
+header:
+  seq: 3
+  stamp:
+    secs: 12
+    nsecs: 34
+  frame_id: "map"
+child_frame_id: "odom"
+transform:
+  translation: {x: 1.0, y: 2.0, z: 3.0}
+  rotation: {x: 0.0, y: 0.0, z: 0.0, w: 2.0}
+"#;
+
+#[test]
+fn ros_msg_deserialize() -> Result<()> {
+    let set: TransformSet = serde_yaml::from_str(TF_STATIC)?;
+    let base_to_lidar = na::Isometry3::from_parts(
+        na::Translation3::new(0.5, 0.0, 1.2),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, std::f64::consts::FRAC_PI_2),
+    );
+    let base_to_camera = na::Isometry3::translation(0.3, -0.1, 1.0);
+    assert_abs_diff_eq!(
+        set.get("lidar", "camera").unwrap(),
+        base_to_lidar.inverse() * base_to_camera,
+        epsilon = EPSILON
+    );
+
+    // The quaternion is normalized and the time stamp is discarded.
+    let trans: CoordTransform = serde_yaml::from_str(ROS1_MSG)?;
+    assert_eq!(trans.src, "map");
+    assert_eq!(trans.dst, "odom");
+    let iso: na::Isometry3<f64> = trans.tf.into();
+    assert_abs_diff_eq!(
+        iso,
+        na::Isometry3::translation(1.0, 2.0, 3.0),
+        epsilon = EPSILON
+    );
+
+    let msg: TransformStamped = serde_yaml::from_str(ROS1_MSG)?;
+    assert_eq!(msg.header.stamp.sec, 12);
+    assert_eq!(msg.header.stamp.nanosec, 34);
+
+    // Mixing the layouts is rejected.
+    let text =
+        "{src: map, child_frame_id: odom, tf: {r: {format: quaternion, ijkw: [0, 0, 0, 1]}}}";
+    assert!(serde_yaml::from_str::<CoordTransform>(text).is_err());
+
+    // A zero quaternion is rejected.
+    let text = ROS1_MSG.replace("w: 2.0", "w: 0.0");
+    assert!(serde_yaml::from_str::<CoordTransform>(&text).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn ros_msg_serialize() -> Result<()> {
+    let set: TransformSet = serde_yaml::from_str(TF_STATIC)?;
+    let text = serde_yaml::to_string(&TfMessage::from(&set))?;
+    let msg: TfMessage = serde_yaml::from_str(&text)?;
+    assert_eq!(msg.transforms.len(), 2);
+    assert_eq!(msg.transforms[0].header.frame_id, "base_link");
+    assert_eq!(msg.transforms[0].child_frame_id, "lidar");
+    assert_abs_diff_eq!(
+        msg.transforms[0].transform.rotation.z,
+        std::f64::consts::FRAC_1_SQRT_2,
+        epsilon = EPSILON
+    );
+
+    let restored = TransformSet::try_from(&msg)?;
+    assert_abs_diff_eq!(
+        restored.get("lidar", "camera").unwrap(),
+        set.get("lidar", "camera").unwrap(),
+        epsilon = EPSILON
+    );
+
+    // The tf-format layout is read as before.
+    let text = serde_json::to_string(&set)?;
+    let restored: TransformSet = serde_json::from_str(&text)?;
+    assert_abs_diff_eq!(
+        restored.get("lidar", "camera").unwrap(),
+        set.get("lidar", "camera").unwrap(),
+        epsilon = EPSILON
+    );
+
+    Ok(())
+}
+
+#[test]
+fn ros_msg_documents() -> Result<()> {
+    // The later message moves the lidar, and the last one publishes
+    // the camera transform in the reversed direction.
+    let text = format!(
+        "{TF_STATIC}---\n{}---\n{}---\n",
+        TF_STATIC.replace("x: 0.5", "x: 0.7"),
+        r#"
+transforms:
+- src: camera
+  dst: base_link
+  tf:
+    r: {format: quaternion, ijkw: [0, 0, 0, 1]}
+    t: [-0.3, 0.1, -2.0]
+"#
+    );
+    let set = TransformSet::from_documents(serde_yaml::Deserializer::from_str(&text))?;
+
+    assert_eq!(set.transforms().len(), 2);
+    assert_abs_diff_eq!(
+        set.get("base_link", "lidar").unwrap().translation.vector,
+        na::Vector3::new(0.7, 0.0, 1.2),
+        epsilon = EPSILON
+    );
+    assert_abs_diff_eq!(
+        set.get("base_link", "camera").unwrap().translation.vector,
+        na::Vector3::new(0.3, -0.1, 2.0),
+        epsilon = EPSILON
+    );
+
+    Ok(())
+}
@@ -4,16 +4,20 @@ use itertools::izip;
 use nalgebra as na;
 use noisy_float::types::r64;
 use rand::prelude::*;
-use serde::Deserialize;
-use std::{fs::File, io::BufReader, path::Path};
+use serde::{de::DeserializeSeed, Deserialize};
+use std::{f64::consts::PI, fs::File, io::BufReader, num::NonZeroUsize, path::Path};
 use tf_format::{
-    Angle, AxisAngle, Euler, EulerAxis, EulerAxisOrder, Quaternion, Rotation, RotationMatrix,
-    Transform, Translation,
+    restore_expressions, with_serde_options, Angle, AngleUnit, AxisAngle, DualQuaternion, Euler,
+    EulerAxis, EulerAxisOrder, EulerConvention, Gibbs, HomogeneousMatrix, LengthUnit,
+    MaybeTransform, MaybeTransformSeed, Mrp, Precision, Quaternion, QuaternionConvention,
+    QuaternionOrder, Rotation, RotationMatrix, SerdeOptions, Transform, TransformMatrix,
+    TransformSet, Translation, Twist,
 };
 
 const CONFIG_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/example_config");
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn json_parsing() -> Result<()> {
     let config_dir = Path::new(CONFIG_DIR);
 
@@ -27,6 +31,7 @@ fn json_parsing() -> Result<()> {
         let Euler {
             order: EulerAxisOrder(order),
             angles,
+            ..
         } = euler;
 
         let [r, p, y] = **angles else { panic!() };
@@ -73,7 +78,7 @@ fn json_parsing() -> Result<()> {
         let Rotation::Quaternion(quat) = rot else {
             panic!();
         };
-        let Quaternion { ijkw } = quat;
+        let Quaternion { ijkw, .. } = quat;
 
         assert_eq!(ijkw, [r64(0.0), r64(0.0), r64(0.0), r64(1.0)]);
     }
@@ -102,6 +107,7 @@ fn json_parsing() -> Result<()> {
         let Transform {
             t: Translation(trans),
             r: Rotation::Euler(euler),
+            ..
         } = tf
         else {
             panic!();
@@ -110,11 +116,13 @@ fn json_parsing() -> Result<()> {
         let Euler {
             order: EulerAxisOrder(order),
             angles,
+            ..
         } = euler;
 
         let [y, p, r] = *angles else { panic!() };
 
-        assert_eq!(trans, [1.0, -2.0, 0.3]);
+        assert_eq!(trans.map(|v| v.unit), [LengthUnit::Meter; 3]);
+        assert_eq!(trans.map(|v| v.value), [1.0, -2.0, 0.3]);
         assert_eq!(
             order,
             vec![EulerAxis::Yaw, EulerAxis::Pitch, EulerAxis::Roll]
@@ -183,6 +191,7 @@ fn euler_nalgebra_conversion() -> Result<()> {
     let euler = Euler {
         order: EulerAxisOrder(vec![EulerAxis::Roll, EulerAxis::Pitch, EulerAxis::Yaw]),
         angles: vec![r, p, y],
+        convention: EulerConvention::Extrinsic,
     };
     let rot: Rotation = euler.into();
     let quat: na::UnitQuaternion<f64> = rot.into();
@@ -195,6 +204,562 @@ fn euler_nalgebra_conversion() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn euler_convention() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+
+    let rot: Rotation = load_json(config_dir.join("rot_euler.json"))?;
+    let Rotation::Euler(extrinsic) = &rot else {
+        panic!();
+    };
+    assert_eq!(extrinsic.convention, EulerConvention::Extrinsic);
+
+    // The default convention is omitted in the output.
+    let text = serde_json::to_string(&rot)?;
+    assert!(!text.contains("convention"));
+
+    let rot: Rotation = load_json(config_dir.join("rot_euler_intrinsic.json"))?;
+    let Rotation::Euler(intrinsic) = &rot else {
+        panic!();
+    };
+    assert_eq!(intrinsic.convention, EulerConvention::Intrinsic);
+
+    // Intrinsic "ypr" equals to extrinsic "rpy" with reversed angles.
+    let quat1: na::UnitQuaternion<f64> = extrinsic.clone().into();
+    let quat2: na::UnitQuaternion<f64> = intrinsic.clone().into();
+    assert_abs_diff_eq!(quat1, quat2, epsilon = 1e-6);
+
+    let text = serde_json::to_string(&rot)?;
+    assert!(text.contains(r#""convention":"intrinsic""#));
+    let rot2: Rotation = serde_json::from_str(&text)?;
+    let quat3: na::UnitQuaternion<f64> = rot2.into();
+    assert_abs_diff_eq!(quat2, quat3, epsilon = 1e-6);
+
+    Ok(())
+}
+
+#[test]
+fn quaternion_layout() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let expect = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 45f64.to_radians());
+
+    for name in ["rot_quaternion_wxyz.json", "rot_quaternion_jpl.json"] {
+        let rot: Rotation = load_json(config_dir.join(name))?;
+        let quat: na::UnitQuaternion<f64> = rot.clone().into();
+        assert_abs_diff_eq!(quat, expect, epsilon = 1e-6);
+
+        // The layout and convention are preserved after round trip.
+        let text = serde_json::to_string(&rot)?;
+        let rot2: Rotation = serde_json::from_str(&text)?;
+        let (Rotation::Quaternion(orig), Rotation::Quaternion(new)) = (rot, rot2) else {
+            panic!();
+        };
+        assert_eq!(orig.order, new.order);
+        assert_eq!(orig.convention, new.convention);
+        assert_eq!(orig.ijkw, new.ijkw);
+    }
+
+    {
+        let quat = Quaternion::from_unit_quaternion(
+            &expect,
+            QuaternionOrder::Wxyz,
+            QuaternionConvention::Jpl,
+        );
+        let value = serde_json::to_value(Rotation::from(quat))?;
+        assert!(value.get("ijkw").is_none());
+        assert_eq!(value["convention"], "jpl");
+        let wxyz: Vec<f64> = serde_json::from_value(value["wxyz"].clone())?;
+        assert_abs_diff_eq!(wxyz[0], expect.w, epsilon = 1e-9);
+        assert_abs_diff_eq!(wxyz[3], -expect.k, epsilon = 1e-9);
+    }
+
+    {
+        // The default convention is omitted in the output.
+        let value = serde_json::to_value(Rotation::from(expect))?;
+        assert!(value.get("convention").is_none());
+    }
+
+    {
+        let text = r#"{"format": "quaternion", "ijkw": [0, 0, 0, 1], "wxyz": [1, 0, 0, 0]}"#;
+        assert!(serde_json::from_str::<Rotation>(text).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn matrix_parsing() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let expect = na::Isometry3::from_parts(
+        na::Translation3::new(1.0, -2.0, 0.3),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, 90f64.to_radians()),
+    );
+
+    for name in ["tfmat_homogeneous.json", "tfmat_affine.json"] {
+        let tf: MaybeTransform = load_json(config_dir.join(name))?;
+        assert!(tf.t.is_some());
+        let iso: na::Isometry3<f64> = tf.to_na_isometry3();
+        assert_abs_diff_eq!(iso, expect, epsilon = 1e-6);
+    }
+
+    {
+        let mat = HomogeneousMatrix::from(expect);
+        let text = serde_json::to_string(&TransformMatrix::from(mat))?;
+        let tf: MaybeTransform = serde_json::from_str(&text)?;
+        assert_abs_diff_eq!(tf.to_na_isometry3::<f64>(), expect, epsilon = 1e-6);
+    }
+
+    {
+        let text = r#"{
+            "format": "homogeneous-matrix",
+            "matrix": [[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0], [0, 0, 1, 1]]
+        }"#;
+        assert!(serde_json::from_str::<MaybeTransform>(text).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rotation_matrix_validation() -> Result<()> {
+    let reflection = r#"{
+        "format": "rotation-matrix",
+        "matrix": [[1, 0, 0], [0, 1, 0], [0, 0, -1]]
+    }"#;
+    let skewed = r#"{
+        "format": "affine-matrix",
+        "matrix": [[1, 0.01, 0, 1], [0, 1, 0, 2], [0, 0, 1, 3]]
+    }"#;
+
+    // Every loader rejects invalid matrices with the validation error.
+    assert!(serde_json::from_str::<RotationMatrix>(
+        r#"{"matrix": [[1, 0, 0], [0, 1, 0], [0, 0, -1]]}"#
+    )
+    .is_err());
+    assert!(serde_json::from_str::<Rotation>(reflection).is_err());
+    let err = serde_json::from_str::<MaybeTransform>(reflection).unwrap_err();
+    assert!(err.to_string().contains("reflection"), "{err}");
+    let err = serde_json::from_str::<MaybeTransform>(skewed).unwrap_err();
+    assert!(err.to_string().contains("not orthonormal"), "{err}");
+    let text = format!(r#"{{"r": {reflection}, "t": [0, 0, 0]}}"#);
+    assert!(serde_json::from_str::<Transform>(&text).is_err());
+
+    // Matrices typed with 4 decimals are accepted.
+    let typed = r#"{
+        "format": "rotation-matrix",
+        "matrix": [[0.7071, -0.7071, 0], [0.7071, 0.7071, 0], [0, 0, 1]]
+    }"#;
+    assert!(serde_json::from_str::<Rotation>(typed).is_ok());
+
+    // The repair is explicit.
+    let value: serde_json::Value = serde_json::from_str(reflection)?;
+    let (tf, report) = MaybeTransform::deserialize_repaired(value)?;
+    assert!(tf.validate().is_ok());
+    assert!(report.unwrap().determinant < 0.0);
+
+    let value: serde_json::Value = serde_json::from_str(skewed)?;
+    let (tf, report) = MaybeTransform::deserialize_repaired(value)?;
+    assert!(tf.validate().is_ok());
+    assert!(report.unwrap().distance > 0.0);
+    let [x, y, z] = tf.t.unwrap().as_meters_values();
+    assert_eq!([x.raw(), y.raw(), z.raw()], [1.0, 2.0, 3.0]);
+
+    let value: serde_json::Value =
+        serde_json::from_str(r#"{"format": "mrp", "params": [0, 0, 0]}"#)?;
+    let (_, report) = MaybeTransform::deserialize_repaired(value)?;
+    assert!(report.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn dual_quaternion_parsing() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let expect = na::Isometry3::from_parts(
+        na::Translation3::new(1.0, -2.0, 0.3),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, 90f64.to_radians()),
+    );
+
+    {
+        let tf: MaybeTransform = load_json(config_dir.join("tfdq_dual_quaternion.json"))?;
+        assert!(tf.t.is_some());
+        assert_abs_diff_eq!(tf.to_na_isometry3::<f64>(), expect, epsilon = 1e-6);
+    }
+
+    {
+        let dual_quat = DualQuaternion::from(expect);
+        let text = serde_json::to_string(&dual_quat)?;
+        let tf: MaybeTransform = serde_json::from_str(&text)?;
+        assert_abs_diff_eq!(tf.to_na_isometry3::<f64>(), expect, epsilon = 1e-6);
+
+        let dual_quat: na::UnitDualQuaternion<f64> = dual_quat.try_into()?;
+        assert_abs_diff_eq!(
+            dual_quat,
+            na::UnitDualQuaternion::from_isometry(&expect),
+            epsilon = 1e-6
+        );
+    }
+
+    {
+        let text = r#"{
+            "format": "dual-quaternion",
+            "real": [0, 0, 0, 0],
+            "dual": [1, 0, 0, 0]
+        }"#;
+        assert!(serde_json::from_str::<MaybeTransform>(text).is_err());
+    }
+
+    {
+        // The format tag is checked.
+        let text = r#"{
+            "format": "twist",
+            "real": [0, 0, 0, 1],
+            "dual": [0, 0, 0, 0]
+        }"#;
+        assert!(serde_json::from_str::<DualQuaternion>(text).is_err());
+        assert!(serde_json::from_str::<MaybeTransform>(text).is_err());
+        let text = r#"{"real": [0, 0, 0, 1], "dual": [0, 0, 0, 0]}"#;
+        assert!(serde_json::from_str::<DualQuaternion>(text).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn length_unit_parsing() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+
+    let tf: Transform = load_json(config_dir.join("tf_euler_length_unit.json"))?;
+    let Translation(trans) = tf.t;
+    assert_eq!(
+        trans.map(|v| v.unit),
+        [
+            LengthUnit::Millimeter,
+            LengthUnit::Centimeter,
+            LengthUnit::Meter
+        ]
+    );
+
+    let expect: Transform = load_json(config_dir.join("tf_euler.json"))?;
+    let iso: na::Isometry3<f64> = tf.clone().into();
+    let expect: na::Isometry3<f64> = expect.into();
+    assert_abs_diff_eq!(iso, expect, epsilon = 1e-9);
+
+    // Meters are written as plain numbers and others with suffixes.
+    let tf = tf.into_length_unit(LengthUnit::Millimeter);
+    let text = serde_json::to_string(&tf.t)?;
+    assert_eq!(text, r#"["1000mm","-2000mm","300mm"]"#);
+    let tf = tf.into_length_unit(LengthUnit::Meter);
+    let text = serde_json::to_string(&tf.t)?;
+    assert_eq!(text, "[1.0,-2.0,0.3]");
+
+    Ok(())
+}
+
+#[test]
+fn expression_parsing() -> Result<()> {
+    let expect = na::UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0);
+
+    let texts = [
+        r#"{"format": "euler", "order": "rpy", "angles": ["0d", "-90d + 90d", "pi/2"]}"#,
+        r#"{"format": "quaternion", "ijkw": [0, 0, "sqrt(2)/2", "cos(pi/4)"]}"#,
+        r#"{"format": "rotation-matrix", "matrix": [[0, -1, 0], ["sin(pi/2)", 0, 0], [0, 0, 1]]}"#,
+        r#"{"format": "rodrigues", "params": [0, 0, "pi/2"]}"#,
+    ];
+
+    for text in texts {
+        let rot: Rotation = serde_json::from_str(text)?;
+        let quat: na::UnitQuaternion<f64> = rot.clone().into();
+        assert_abs_diff_eq!(quat, expect, epsilon = 1e-9);
+
+        // The evaluated values are written instead of expressions.
+        let text = serde_json::to_string(&rot)?;
+        assert!(!text.contains("pi") && !text.contains("sqrt"), "{text}");
+    }
+
+    let text =
+        r#"{"r": {"format": "quaternion", "ijkw": [0, 0, 0, 1]}, "t": ["1/4", "3 * 10mm", 0]}"#;
+    let tf: Transform = serde_json::from_str(text)?;
+    let iso: na::Isometry3<f64> = tf.into();
+    assert_abs_diff_eq!(
+        iso.translation.vector,
+        na::Vector3::new(0.25, 0.03, 0.0),
+        epsilon = 1e-9
+    );
+
+    let text = r#"{"format": "quaternion", "ijkw": [0, 0, 0, "1/0"]}"#;
+    assert!(serde_json::from_str::<Rotation>(text).is_err());
+
+    // The expressions are restored where the values are unchanged.
+    let text = r#"{"r": {"format": "euler", "order": "rpy", "angles": ["0d", "-90d + 1.5d", "pi/2"]}, "t": ["1/4", "3 * 10mm", 0]}"#;
+    let source: serde_json::Value = serde_json::from_str(text)?;
+    let tf: Transform = serde_json::from_value(source.clone())?;
+    let mut output = serde_json::to_value(&tf)?;
+    restore_expressions(&mut output, &source);
+    assert_eq!(output["r"]["angles"], source["r"]["angles"]);
+    assert_eq!(output["t"][0], "1/4");
+    assert_eq!(output["t"][1], "3 * 10mm");
+
+    let mut output = serde_json::to_value(tf.into_degrees())?;
+    restore_expressions(&mut output, &source);
+    assert_eq!(output["r"]["angles"][1], source["r"]["angles"][1]);
+    assert_eq!(output["r"]["angles"][2], "90d");
+
+    Ok(())
+}
+
+#[test]
+fn numeric_angles() -> Result<()> {
+    let text =
+        r#"{"format": "euler", "order": "rpy", "angles": [90, "0.5r", 0], "angle_unit": "deg"}"#;
+    let rot: Rotation = serde_json::from_str(text)?;
+    let Rotation::Euler(Euler { angles, .. }) = &rot else {
+        panic!();
+    };
+    assert_eq!(
+        angles.iter().map(|angle| angle.unit).collect::<Vec<_>>(),
+        [AngleUnit::Degree, AngleUnit::Radian, AngleUnit::Degree]
+    );
+    assert_eq!(angles[0].value, 90.0);
+
+    // Suffixed strings are written by default.
+    let text = serde_json::to_string(&rot)?;
+    assert!(!text.contains("angle_unit"), "{text}");
+
+    // Numeric angles are written in the unit of the first angle.
+    let numeric = SerdeOptions {
+        numeric_angles: true,
+        ..Default::default()
+    };
+    let text = with_serde_options(numeric, || serde_json::to_string(&rot))?;
+    let value: serde_json::Value = serde_json::from_str(&text)?;
+    assert_eq!(value["angle_unit"], "deg");
+    let angles: Vec<f64> = serde_json::from_value(value["angles"].clone())?;
+    assert_abs_diff_eq!(&angles[..], &[90.0, 0.5f64.to_degrees(), 0.0][..]);
+
+    // Numbers without the unit need a default unit, which is given
+    // explicitly by the seed.
+    let text = r#"{"format": "axis-angle", "axis": [0, 0, 1], "angle": 0.5}"#;
+    assert!(serde_json::from_str::<Rotation>(text).is_err());
+    let seed = MaybeTransformSeed {
+        default_angle_unit: Some(AngleUnit::Radian),
+        ..Default::default()
+    };
+    let (tf, _) = seed.deserialize(&mut serde_json::Deserializer::from_str(text))?;
+    let Rotation::AxisAngle(AxisAngle { angle, .. }) = tf.r else {
+        panic!();
+    };
+    assert_eq!(angle, Angle::from_radians(r64(0.5)));
+
+    // The serde options don't change how documents are read.
+    let options = SerdeOptions {
+        numeric_angles: true,
+        ..Default::default()
+    };
+    assert!(with_serde_options(options, || serde_json::from_str::<Rotation>(text)).is_err());
+
+    // The document-level field applies to the rotation without its
+    // own unit.
+    let text = r#"{"r": {"format": "axis-angle", "axis": [0, 0, 1], "angle": 90}, "t": [0, 0, 0], "angle_unit": "deg"}"#;
+    let tf: MaybeTransform = serde_json::from_str(text)?;
+    let Rotation::AxisAngle(AxisAngle { angle, .. }) = tf.r else {
+        panic!();
+    };
+    assert_eq!(angle, Angle::from_degrees(r64(90.0)));
+
+    // So does the set-level field.
+    let text = r#"{"angle_unit": "deg", "transforms": [{"src": "a", "dst": "b", "tf": {"r": {"format": "euler", "order": "rpy", "angles": [0, 0, 90]}, "t": [0, 0, 0]}}]}"#;
+    let set: TransformSet = serde_json::from_str(text)?;
+    let quat = set.get("a", "b").unwrap().rotation;
+    assert_abs_diff_eq!(
+        quat,
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0),
+        epsilon = 1e-9
+    );
+
+    Ok(())
+}
+
+#[test]
+fn precision_and_snapping() -> Result<()> {
+    let text = r#"{"r": {"format": "euler", "order": "rpy", "angles": ["0d", "0d", "90d"]}, "t": [0.123456, "1.5mm", 0]}"#;
+    let tf: Transform = serde_json::from_str(text)?;
+
+    let snap = SerdeOptions {
+        snap_eps: Some(1e-12),
+        ..Default::default()
+    };
+    let mat = tf.clone().into_rotation_matrix_format();
+    let text = with_serde_options(snap, || serde_json::to_string(&mat.r))?;
+    assert_eq!(
+        text,
+        r#"{"format":"rotation-matrix","matrix":[[0.0,-1.0,0.0],[1.0,0.0,0.0],[0.0,0.0,1.0]]}"#
+    );
+
+    // Angles are snapped to multiples of 15 degrees.
+    let rot = Rotation::from(na::UnitQuaternion::from_euler_angles(
+        0.0,
+        0.0,
+        PI / 2.0 + 1e-14,
+    ))
+    .into_euler_format();
+    let text = with_serde_options(snap, || serde_json::to_string(&rot.into_degrees()))?;
+    assert!(text.contains(r#"["0d","0d","90d"]"#), "{text}");
+
+    let decimals = SerdeOptions {
+        precision: Some(Precision::Decimals(3)),
+        ..Default::default()
+    };
+    let quat = tf.clone().into_quaternion_format();
+    let value = with_serde_options(decimals, || serde_json::to_value(&quat))?;
+    assert_eq!(
+        value["r"]["ijkw"],
+        serde_json::json!([0.0, 0.0, 0.707, 0.707])
+    );
+    assert_eq!(value["t"], serde_json::json!([0.123, "1.5mm", 0.0]));
+
+    let significant = SerdeOptions {
+        precision: Some(Precision::Significant(NonZeroUsize::new(2).unwrap())),
+        ..Default::default()
+    };
+    let value = with_serde_options(significant, || serde_json::to_value(&quat))?;
+    assert_eq!(value["t"], serde_json::json!([0.12, "1.5mm", 0.0]));
+
+    Ok(())
+}
+
+#[test]
+fn twist_parsing() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+
+    // Moving 1m along a quarter circle ends at [2/pi, 2/pi, 0].
+    let expect = na::Isometry3::from_parts(
+        na::Translation3::new(2.0 / PI, 2.0 / PI, 0.0),
+        na::UnitQuaternion::from_euler_angles(0.0, 0.0, PI / 2.0),
+    );
+
+    let tf: MaybeTransform = load_json(config_dir.join("tftw_twist.json"))?;
+    assert_abs_diff_eq!(tf.to_na_isometry3::<f64>(), expect, epsilon = 1e-6);
+
+    let tf: Transform = expect.into();
+    let twist = Twist::from(tf.clone());
+    assert_abs_diff_eq!(
+        &twist.to_vector()[..],
+        &[1.0, 0.0, 0.0, 0.0, 0.0, PI / 2.0][..],
+        epsilon = 1e-6
+    );
+
+    let text = serde_json::to_string(&twist)?;
+    let tf2: MaybeTransform = serde_json::from_str(&text)?;
+    assert_abs_diff_eq!(tf2.to_na_isometry3::<f64>(), expect, epsilon = 1e-6);
+
+    let rot = Rotation::exp(tf.r.log());
+    let quat: na::UnitQuaternion<f64> = rot.into();
+    assert_abs_diff_eq!(quat, expect.rotation, epsilon = 1e-6);
+
+    assert!(Twist::from_vector([0.0, 0.0, 0.0, f64::NAN, 0.0, 0.0]).is_err());
+
+    // The format tag is checked.
+    assert!(serde_json::from_str::<Twist>(
+        r#"{"format": "dual-quaternion", "linear": [0, 0, 0], "angular": [0, 0, 0]}"#
+    )
+    .is_err());
+    assert!(
+        serde_json::from_str::<Twist>(r#"{"linear": [0, 0, 0], "angular": [0, 0, 0]}"#).is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mrp_and_gibbs() -> Result<()> {
+    let config_dir = Path::new(CONFIG_DIR);
+    let expect = na::UnitQuaternion::from_euler_angles(90f64.to_radians(), 0.0, 0.0);
+
+    for name in ["rot_mrp.json", "rot_gibbs.json"] {
+        let rot: Rotation = load_json(config_dir.join(name))?;
+        let quat: na::UnitQuaternion<f64> = rot.into();
+        assert_abs_diff_eq!(quat, expect, epsilon = 1e-6);
+    }
+
+    // The shadow set represents the same rotation.
+    {
+        let rot: Rotation = load_json(config_dir.join("rot_mrp.json"))?;
+        let Rotation::Mrp(mrp) = rot else { panic!() };
+        let shadow = mrp.shadow();
+        assert!(shadow.norm_squared() > 1.0);
+
+        let quat: na::UnitQuaternion<f64> = shadow.clone().into();
+        assert_abs_diff_eq!(quat, expect, epsilon = 1e-6);
+
+        let normalized = shadow.normalize();
+        assert_abs_diff_eq!(
+            normalized.norm_squared(),
+            mrp.norm_squared(),
+            epsilon = 1e-9
+        );
+    }
+
+    // Rotations beyond 180 degrees take the short way around.
+    {
+        let quat = na::UnitQuaternion::from_euler_angles(0.0, 0.0, 270f64.to_radians());
+        let mrp = Mrp::from(quat);
+        assert!(mrp.norm_squared() <= 1.0);
+        assert_abs_diff_eq!(na::UnitQuaternion::from(mrp), quat, epsilon = 1e-6);
+    }
+
+    // The Gibbs vector is undefined for 180 degrees.
+    {
+        let quat = na::UnitQuaternion::from_euler_angles(PI, 0.0, 0.0);
+        assert!(Gibbs::try_from(quat).is_err());
+        assert!(Rotation::from(quat).into_gibbs_format().is_err());
+        assert!(Mrp::from(quat).norm_squared() <= 1.0 + 1e-9);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn key_order_independence() -> Result<()> {
+    // The fields are looked up by key, so the reading and the restored
+    // expressions don't depend on the key order of the document, with
+    // or without the preserve_order feature of serde_json.
+    let text = r#"{
+        "r": {"format": "euler", "order": "rpy", "angles": [90, "pi/4", 0], "angle_unit": "deg"},
+        "t": ["1/4", "3 * 10mm", 0]
+    }"#;
+    let reordered = r#"{
+        "t": ["1/4", "3 * 10mm", 0],
+        "r": {"angle_unit": "deg", "angles": [90, "pi/4", 0], "order": "rpy", "format": "euler"}
+    }"#;
+
+    let source: serde_json::Value = serde_json::from_str(text)?;
+    let reordered_source: serde_json::Value = serde_json::from_str(reordered)?;
+    let tf: Transform = serde_json::from_value(source.clone())?;
+    let reordered_tf: Transform = serde_json::from_value(reordered_source.clone())?;
+    let iso: na::Isometry3<f64> = tf.clone().into();
+    let reordered_iso: na::Isometry3<f64> = reordered_tf.clone().into();
+    assert_abs_diff_eq!(iso, reordered_iso, epsilon = 1e-9);
+
+    let mut output = serde_json::to_value(&tf)?;
+    restore_expressions(&mut output, &source);
+    let mut reordered_output = serde_json::to_value(&reordered_tf)?;
+    restore_expressions(&mut reordered_output, &reordered_source);
+    assert_eq!(output, reordered_output);
+    assert_eq!(output["r"]["angles"][1], "pi/4");
+    assert_eq!(output["t"][1], "3 * 10mm");
+
+    // The repaired matrix is found by key as well.
+    let text =
+        r#"{"matrix": [[1, 0.01, 0, 1], [0, 1, 0, 2], [0, 0, 1, 3]], "format": "affine-matrix"}"#;
+    let (tf, report) =
+        MaybeTransform::deserialize_repaired(serde_json::from_str::<serde_json::Value>(text)?)?;
+    assert!(tf.validate().is_ok());
+    assert!(report.is_some());
+
+    Ok(())
+}
+
 fn load_json<T, P>(path: P) -> Result<T>
 where
     T: for<'de> Deserialize<'de>,
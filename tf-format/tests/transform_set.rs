@@ -84,6 +84,16 @@ fn transfrom_set_serde() -> Result<()> {
         epsilon = 1e-6
     );
 
+    let pairs: Vec<_> = set
+        .transforms()
+        .iter()
+        .map(|trans| (trans.src.as_str(), trans.dst.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [("map", "car"), ("car", "lidar1"), ("car", "lidar2")]
+    );
+
     Ok(())
 }
 
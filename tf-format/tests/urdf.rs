@@ -0,0 +1,204 @@
+use anyhow::Result;
+use approx::assert_abs_diff_eq;
+use nalgebra as na;
+use std::{collections::HashMap, f64::consts::FRAC_PI_2};
+use tf_format::TransformSet;
+
+const EPSILON: f64 = 1e-9;
+
+const ROBOT: &str = r#"<?xml version="1.0"?>
+<robot name="rover">
+  <link name="base_link"/>
+  <link name="lidar"/>
+  <link name="arm"/>
+  <link name="slider"/>
+  <joint name="base_to_lidar" type="fixed">
+    <parent link="base_link"/>
+    <child link="lidar"/>
+    <origin xyz="0.5 0 1.2" rpy="0 0.1 1.5707963267948966"/>
+  </joint>
+  <joint name="shoulder" type="revolute">
+    <parent link="base_link"/>
+    <child link="arm"/>
+    <origin xyz="0 0 0.3"/>
+    <axis xyz="0 0 1"/>
+    <limit lower="0.2" upper="1.0" effort="1" velocity="1"/>
+  </joint>
+  <joint name="rail" type="prismatic">
+    <parent link="arm"/>
+    <child link="slider"/>
+    <origin xyz="1 0 0" rpy="0 0 0"/>
+    <axis xyz="1 0 0"/>
+    <limit lower="-1" upper="1" effort="1" velocity="1"/>
+  </joint>
+</robot>
+"#;
+
+fn make_iso3(xyz: [f64; 3], rpy: [f64; 3]) -> na::Isometry3<f64> {
+    na::Isometry3::from_parts(
+        xyz.into(),
+        na::UnitQuaternion::from_euler_angles(rpy[0], rpy[1], rpy[2]),
+    )
+}
+
+#[test]
+fn urdf_import() -> Result<()> {
+    let set = TransformSet::from_urdf_str(ROBOT, &HashMap::new())?;
+
+    assert_abs_diff_eq!(
+        set.get("base_link", "lidar").unwrap(),
+        make_iso3([0.5, 0.0, 1.2], [0.0, 0.1, FRAC_PI_2]),
+        epsilon = EPSILON
+    );
+
+    // Zero is clamped to the lower limit of the revolute joint.
+    assert_abs_diff_eq!(
+        set.get("base_link", "arm").unwrap(),
+        make_iso3([0.0, 0.0, 0.3], [0.0, 0.0, 0.2]),
+        epsilon = EPSILON
+    );
+    assert_abs_diff_eq!(
+        set.get("arm", "slider").unwrap(),
+        make_iso3([1.0, 0.0, 0.0], [0.0; 3]),
+        epsilon = EPSILON
+    );
+
+    let positions = HashMap::from([("shoulder".to_string(), 0.5), ("rail".to_string(), 0.25)]);
+    let set = TransformSet::from_urdf_str(ROBOT, &positions)?;
+    assert_abs_diff_eq!(
+        set.get("base_link", "slider").unwrap(),
+        make_iso3([0.0, 0.0, 0.3], [0.0, 0.0, 0.5]) * make_iso3([1.25, 0.0, 0.0], [0.0; 3]),
+        epsilon = EPSILON
+    );
+
+    // Unknown joints are rejected.
+    let positions = HashMap::from([("elbow".to_string(), 0.5)]);
+    assert!(TransformSet::from_urdf_str(ROBOT, &positions).is_err());
+
+    // Positions of fixed joints are rejected.
+    let positions = HashMap::from([("base_to_lidar".to_string(), 0.5)]);
+    assert!(TransformSet::from_urdf_str(ROBOT, &positions).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn urdf_export() -> Result<()> {
+    let set = TransformSet::from_urdf_str(ROBOT, &HashMap::new())?;
+    let text = set.to_urdf_string("arm", "rover")?;
+    let exported = TransformSet::from_urdf_str(&text, &HashMap::new())?;
+
+    for (src, dst) in [
+        ("arm", "base_link"),
+        ("arm", "slider"),
+        ("lidar", "slider"),
+        ("base_link", "lidar"),
+    ] {
+        assert_abs_diff_eq!(
+            exported.get(src, dst).unwrap(),
+            set.get(src, dst).unwrap(),
+            epsilon = EPSILON
+        );
+    }
+
+    assert!(text.contains(r#"<joint name="arm_to_base_link" type="fixed">"#));
+    assert!(set.to_urdf_string("xxx", "rover").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn urdf_mimic() -> Result<()> {
+    let robot = r#"<robot name="gripper">
+  <link name="palm"/>
+  <link name="left"/>
+  <link name="right"/>
+  <joint name="left_finger" type="prismatic">
+    <parent link="palm"/>
+    <child link="left"/>
+    <axis xyz="0 1 0"/>
+    <limit lower="0" upper="0.1" effort="1" velocity="1"/>
+  </joint>
+  <joint name="right_finger" type="prismatic">
+    <parent link="palm"/>
+    <child link="right"/>
+    <axis xyz="0 1 0"/>
+    <limit lower="-0.1" upper="0" effort="1" velocity="1"/>
+    <mimic joint="left_finger" multiplier="-1" offset="0.01"/>
+  </joint>
+</robot>"#;
+
+    let positions = HashMap::from([("left_finger".to_string(), 0.05)]);
+    let set = TransformSet::from_urdf_str(robot, &positions)?;
+    assert_abs_diff_eq!(
+        set.get("palm", "right").unwrap(),
+        make_iso3([0.0, -0.04, 0.0], [0.0; 3]),
+        epsilon = EPSILON
+    );
+
+    // Mimic joints follow the mimicked joints only.
+    let positions = HashMap::from([("right_finger".to_string(), 0.05)]);
+    assert!(TransformSet::from_urdf_str(robot, &positions).is_err());
+
+    // Cycles of mimic joints are rejected.
+    let cyclic = robot.replace(
+        r#"<axis xyz="0 1 0"/>
+    <limit lower="0""#,
+        r#"<axis xyz="0 1 0"/>
+    <mimic joint="right_finger"/>
+    <limit lower="0""#,
+    );
+    assert!(TransformSet::from_urdf_str(&cyclic, &HashMap::new()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn urdf_single_link() -> Result<()> {
+    let robot = r#"<robot name="box"><link name="body"/></robot>"#;
+    let mut set = TransformSet::from_urdf_str(robot, &HashMap::new())?;
+    assert!(set.contains_coord("body"));
+    assert_abs_diff_eq!(
+        set.get("body", "body").unwrap(),
+        na::Isometry3::identity(),
+        epsilon = EPSILON
+    );
+
+    // The frame is kept through serialization.
+    let text = serde_json::to_string(&set)?;
+    let restored: TransformSet = serde_json::from_str(&text)?;
+    assert!(restored.contains_coord("body"));
+    assert_eq!(
+        restored.to_urdf_string("body", "box")?,
+        set.to_urdf_string("body", "box")?
+    );
+
+    // The frame is related to the frames inserted later.
+    let tf = make_iso3([1.0, 2.0, 3.0], [0.0; 3]);
+    set.insert("body", "sensor", tf)?;
+    assert_abs_diff_eq!(set.get("body", "sensor").unwrap(), tf, epsilon = EPSILON);
+
+    Ok(())
+}
+
+#[test]
+fn urdf_unique_joint_names() -> Result<()> {
+    let mut set = TransformSet::new();
+    let tf = make_iso3([1.0, 0.0, 0.0], [0.0; 3]);
+    set.insert("a", "b_to_c", tf)?;
+    set.insert("a", "a_to_b", tf)?;
+    set.insert("a_to_b", "c", tf)?;
+
+    let text = set.to_urdf_string("a", "chain")?;
+    assert!(text.contains(r#"<joint name="a_to_b_to_c" type="fixed">"#));
+    assert!(text.contains(r#"<joint name="a_to_b_to_c_2" type="fixed">"#));
+
+    let exported = TransformSet::from_urdf_str(&text, &HashMap::new())?;
+    assert_abs_diff_eq!(
+        exported.get("b_to_c", "c").unwrap(),
+        set.get("b_to_c", "c").unwrap(),
+        epsilon = EPSILON
+    );
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+// pyo3 0.20 macros emit impl blocks that newer rustc flags as non-local.
+#![allow(non_local_definitions)]
+
 mod serde;
 mod types;
 
@@ -1,11 +1,11 @@
 use nalgebra as na;
 use ndarray as nd;
 use noisy_float::types::R64;
-use num::NumCast;
 use numpy::{PyArray2, PyReadonlyArray2};
 use pyo3::{exceptions::PyValueError, prelude::*};
 use tf_format::{
-    AxisAngle, Euler, MaybeTransform, Quaternion, Rodrigues, Rotation, RotationMatrix, Translation,
+    AxisAngle, DualQuaternion, Euler, Gibbs, MaybeTransform, Mrp, Quaternion, Rodrigues, Rotation,
+    RotationMatrix, Transform, Translation,
 };
 
 const MIN_NORM: f64 = 1e-7;
@@ -34,38 +34,71 @@ impl PyMaybeTransform {
         })
     }
 
+    #[staticmethod]
+    pub fn from_dual_quat(real: [f64; 4], dual: [f64; 4]) -> PyResult<Self> {
+        macro_rules! cast {
+            ($val:expr) => {
+                R64::try_from($val).map_err(|err| {
+                    PyValueError::new_err(format!("invalid value '{}': {err}", $val))
+                })
+            };
+        }
+
+        let [ri, rj, rk, rw] = real;
+        let [di, dj, dk, dw] = dual;
+        let dual_quat = DualQuaternion {
+            real: [cast!(ri)?, cast!(rj)?, cast!(rk)?, cast!(rw)?],
+            dual: [cast!(di)?, cast!(dj)?, cast!(dk)?, cast!(dw)?],
+        };
+        let tf: Transform = dual_quat
+            .try_into()
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
+        let tf: MaybeTransform = tf.into();
+        Ok(tf.into())
+    }
+
     pub fn form(&self) -> PyRotationForm {
         self.r.form()
     }
 
-    pub fn to_form(&self, form: PyRotationForm) -> Self {
-        let r = self.r.to_form(form);
-        Self { t: self.t, r }
+    pub fn to_form(&self, form: PyRotationForm) -> PyResult<Self> {
+        let r = self.r.to_form(form)?;
+        Ok(Self { t: self.t, r })
     }
 
-    pub fn to_quat_form(&self) -> Self {
-        let r = self.r.to_quat_form();
-        Self { t: self.t, r }
+    pub fn to_quat_form(&self) -> PyResult<Self> {
+        let r = self.r.to_quat_form()?;
+        Ok(Self { t: self.t, r })
     }
 
-    pub fn to_euler_form(&self) -> Self {
-        let r = self.r.to_euler_form();
-        Self { t: self.t, r }
+    pub fn to_euler_form(&self) -> PyResult<Self> {
+        let r = self.r.to_euler_form()?;
+        Ok(Self { t: self.t, r })
     }
 
-    pub fn to_axis_angle_form(&self) -> Self {
-        let r = self.r.to_axis_angle_form();
-        Self { t: self.t, r }
+    pub fn to_axis_angle_form(&self) -> PyResult<Self> {
+        let r = self.r.to_axis_angle_form()?;
+        Ok(Self { t: self.t, r })
     }
 
-    pub fn to_rotation_matrix_form(&self) -> Self {
-        let r = self.r.to_rotation_matrix_form();
-        Self { t: self.t, r }
+    pub fn to_rotation_matrix_form(&self) -> PyResult<Self> {
+        let r = self.r.to_rotation_matrix_form()?;
+        Ok(Self { t: self.t, r })
     }
 
-    pub fn to_rodrigues_form(&self) -> Self {
-        let r = self.r.to_rodrigues_form();
-        Self { t: self.t, r }
+    pub fn to_rodrigues_form(&self) -> PyResult<Self> {
+        let r = self.r.to_rodrigues_form()?;
+        Ok(Self { t: self.t, r })
+    }
+
+    pub fn to_mrp_form(&self) -> PyResult<Self> {
+        let r = self.r.to_mrp_form()?;
+        Ok(Self { t: self.t, r })
+    }
+
+    pub fn to_gibbs_form(&self) -> PyResult<Self> {
+        let r = self.r.to_gibbs_form()?;
+        Ok(Self { t: self.t, r })
     }
 
     pub fn get_quat_ijkw(&self) -> [f64; 4] {
@@ -87,12 +120,30 @@ impl PyMaybeTransform {
     pub fn get_rodrigues(&self) -> [f64; 3] {
         self.r.get_rodrigues()
     }
+
+    pub fn get_mrp(&self) -> [f64; 3] {
+        self.r.get_mrp()
+    }
+
+    pub fn get_gibbs(&self) -> PyResult<[f64; 3]> {
+        self.r.get_gibbs()
+    }
+
+    /// Returns the real and dual parts of the transform as a unit
+    /// dual quaternion, both in x, y, z, w order.
+    pub fn get_dual_quat(&self) -> PyResult<([f64; 4], [f64; 4])> {
+        let tf: MaybeTransform = self.clone().try_into()?;
+        let DualQuaternion { real, dual } =
+            DualQuaternion::try_from_na_isometry3(&tf.to_na_isometry3::<f64>())
+                .map_err(|err| PyValueError::new_err(format!("{err}")))?;
+        Ok((real.map(|val| val.raw()), dual.map(|val| val.raw())))
+    }
 }
 
 impl From<MaybeTransform> for PyMaybeTransform {
     fn from(tf: MaybeTransform) -> Self {
-        let MaybeTransform { r, t } = tf;
-        let t = t.map(|Translation([x, y, z])| [x.raw(), y.raw(), z.raw()]);
+        let MaybeTransform { r, t, .. } = tf;
+        let t = t.map(|t| t.as_meters_values().map(|val| val.raw()));
         Self { t, r: r.into() }
     }
 }
@@ -112,11 +163,15 @@ impl TryFrom<PyMaybeTransform> for MaybeTransform {
         let PyMaybeTransform { r, t } = tf;
 
         let t = match t {
-            Some([x, y, z]) => Some(Translation([cast!(x)?, cast!(y)?, cast!(z)?])),
+            Some([x, y, z]) => Some(Translation::from_meters([cast!(x)?, cast!(y)?, cast!(z)?])),
             None => None,
         };
 
-        Ok(Self { r: r.into(), t })
+        Ok(Self {
+            r: r.into(),
+            t,
+            cov: None,
+        })
     }
 }
 
@@ -134,7 +189,8 @@ impl PyRotation {
                 "Almost zero-norm quaternion is not allowed",
             ));
         };
-        let rot: Rotation = rot.into();
+        let rot = Rotation::try_from_na_unit_quaternion(&rot)
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
         Ok(rot.into())
     }
 
@@ -146,7 +202,9 @@ impl PyRotation {
                 "Almost zero-norm axis is not allowed",
             ));
         };
-        let rot: Rotation = na::UnitQuaternion::from_axis_angle(&axis, radians).into();
+        let rot = na::UnitQuaternion::from_axis_angle(&axis, radians);
+        let rot = Rotation::try_from_na_unit_quaternion(&rot)
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
         Ok(rot.into())
     }
 
@@ -154,13 +212,51 @@ impl PyRotation {
     pub fn from_rodrigues(params: [f64; 3]) -> PyResult<Self> {
         macro_rules! cast {
             ($val:expr) => {
-                <R64 as NumCast>::from($val).unwrap()
+                R64::try_from($val).map_err(|err| {
+                    PyValueError::new_err(format!("invalid value '{}': {err}", $val))
+                })
             };
         }
 
         let [p1, p2, p3] = params;
         let rot: Rotation = Rodrigues {
-            params: [cast!(p1), cast!(p2), cast!(p3)],
+            params: [cast!(p1)?, cast!(p2)?, cast!(p3)?],
+        }
+        .into();
+        Ok(rot.into())
+    }
+
+    #[staticmethod]
+    pub fn from_mrp(params: [f64; 3]) -> PyResult<Self> {
+        macro_rules! cast {
+            ($val:expr) => {
+                R64::try_from($val).map_err(|err| {
+                    PyValueError::new_err(format!("invalid value '{}': {err}", $val))
+                })
+            };
+        }
+
+        let [p1, p2, p3] = params;
+        let rot: Rotation = Mrp {
+            params: [cast!(p1)?, cast!(p2)?, cast!(p3)?],
+        }
+        .into();
+        Ok(rot.into())
+    }
+
+    #[staticmethod]
+    pub fn from_gibbs(params: [f64; 3]) -> PyResult<Self> {
+        macro_rules! cast {
+            ($val:expr) => {
+                R64::try_from($val).map_err(|err| {
+                    PyValueError::new_err(format!("invalid value '{}': {err}", $val))
+                })
+            };
+        }
+
+        let [g1, g2, g3] = params;
+        let rot: Rotation = Gibbs {
+            params: [cast!(g1)?, cast!(g2)?, cast!(g3)?],
         }
         .into();
         Ok(rot.into())
@@ -177,11 +273,24 @@ impl PyRotation {
         }
 
         let matrix = matrix.as_standard_layout();
-        let matrix = na::Matrix3::from_row_slice(matrix.as_slice().unwrap());
+        let mut elems = matrix.iter().map(|&val| {
+            R64::try_from(val)
+                .map_err(|err| PyValueError::new_err(format!("invalid value '{val}': {err}")))
+        });
+        let mut next_row = || -> PyResult<_> {
+            Ok([
+                elems.next().unwrap()?,
+                elems.next().unwrap()?,
+                elems.next().unwrap()?,
+            ])
+        };
+        let rot = RotationMatrix {
+            matrix: [next_row()?, next_row()?, next_row()?],
+        };
+        rot.validate()
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
 
-        let rot = na::UnitQuaternion::from_matrix(&matrix);
         let rot: Rotation = rot.into();
-
         Ok(rot.into())
     }
 
@@ -192,42 +301,60 @@ impl PyRotation {
             Rotation::AxisAngle(_) => PyRotationForm::AxisAngle,
             Rotation::RotationMatrix(_) => PyRotationForm::RotationMatrix,
             Rotation::Rodrigues(_) => PyRotationForm::Rodrigues,
+            Rotation::Mrp(_) => PyRotationForm::Mrp,
+            Rotation::Gibbs(_) => PyRotationForm::Gibbs,
         }
     }
 
-    pub fn to_form(&self, form: PyRotationForm) -> Self {
-        match form {
-            PyRotationForm::Euler => self.to_euler_form(),
-            PyRotationForm::Quaternion => self.to_quat_form(),
-            PyRotationForm::AxisAngle => self.to_axis_angle_form(),
-            PyRotationForm::RotationMatrix => self.to_rotation_matrix_form(),
-            PyRotationForm::Rodrigues => self.to_rodrigues_form(),
-        }
+    pub fn to_form(&self, form: PyRotationForm) -> PyResult<Self> {
+        let rot = match form {
+            PyRotationForm::Euler => self.to_euler_form()?,
+            PyRotationForm::Quaternion => self.to_quat_form()?,
+            PyRotationForm::AxisAngle => self.to_axis_angle_form()?,
+            PyRotationForm::RotationMatrix => self.to_rotation_matrix_form()?,
+            PyRotationForm::Rodrigues => self.to_rodrigues_form()?,
+            PyRotationForm::Mrp => self.to_mrp_form()?,
+            PyRotationForm::Gibbs => self.to_gibbs_form()?,
+        };
+        Ok(rot)
     }
 
-    pub fn to_quat_form(&self) -> Self {
-        let rot: Quaternion = self.0.clone().into();
-        Self(rot.into())
+    pub fn to_quat_form(&self) -> PyResult<Self> {
+        let rot: Quaternion = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
     }
 
-    pub fn to_euler_form(&self) -> Self {
-        let rot: Euler = self.0.clone().into();
-        Self(rot.into())
+    pub fn to_euler_form(&self) -> PyResult<Self> {
+        let rot: Euler = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
     }
 
-    pub fn to_axis_angle_form(&self) -> Self {
-        let rot: AxisAngle = self.0.clone().into();
-        Self(rot.into())
+    pub fn to_axis_angle_form(&self) -> PyResult<Self> {
+        let rot: AxisAngle = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
     }
 
-    pub fn to_rotation_matrix_form(&self) -> Self {
-        let rot: RotationMatrix = self.0.clone().into();
-        Self(rot.into())
+    pub fn to_rotation_matrix_form(&self) -> PyResult<Self> {
+        let rot: RotationMatrix = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
     }
 
-    pub fn to_rodrigues_form(&self) -> Self {
-        let rot: Rodrigues = self.0.clone().into();
-        Self(rot.into())
+    pub fn to_rodrigues_form(&self) -> PyResult<Self> {
+        let rot: Rodrigues = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
+    }
+
+    pub fn to_mrp_form(&self) -> PyResult<Self> {
+        let rot: Mrp = self.checked_rotation()?.into();
+        Ok(Self(rot.into()))
+    }
+
+    pub fn to_gibbs_form(&self) -> PyResult<Self> {
+        let rot: Gibbs = self
+            .checked_rotation()?
+            .try_into()
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
+        Ok(Self(rot.into()))
     }
 
     pub fn get_euler_rpy(&self) -> [f64; 3] {
@@ -266,6 +393,34 @@ impl PyRotation {
         } = self.0.clone().into();
         [r1.raw(), r2.raw(), r3.raw()]
     }
+
+    pub fn get_mrp(&self) -> [f64; 3] {
+        let Mrp {
+            params: [p1, p2, p3],
+        } = self.0.clone().into();
+        [p1.raw(), p2.raw(), p3.raw()]
+    }
+
+    pub fn get_gibbs(&self) -> PyResult<[f64; 3]> {
+        let Gibbs {
+            params: [g1, g2, g3],
+        } = self
+            .0
+            .clone()
+            .try_into()
+            .map_err(|err| PyValueError::new_err(format!("{err}")))?;
+        Ok([g1.raw(), g2.raw(), g3.raw()])
+    }
+}
+
+impl PyRotation {
+    /// Convert the rotation to the quaternion form, which every other
+    /// form is converted through, and check that it's finite.
+    fn checked_rotation(&self) -> PyResult<Rotation> {
+        let quat: na::UnitQuaternion<f64> = self.0.clone().into();
+        Rotation::try_from_na_unit_quaternion(&quat)
+            .map_err(|err| PyValueError::new_err(format!("{err}")))
+    }
 }
 
 impl From<Rotation> for PyRotation {
@@ -288,4 +443,6 @@ pub enum PyRotationForm {
     AxisAngle,
     RotationMatrix,
     Rodrigues,
+    Mrp,
+    Gibbs,
 }
@@ -0,0 +1,57 @@
+use crate::{
+    cli::Average,
+    utils::{create_writer, guess_format, read_tf_from_path, write_tf_to_writer},
+};
+use anyhow::{bail, Result};
+use std::io::prelude::*;
+use tf_format::{MaybeTransform, Rotation, RotationMean, Transform, TransformMean};
+
+pub fn average(cli: Average) -> Result<()> {
+    let Average {
+        output_format,
+        weights,
+        repair_rotation,
+        output,
+        pretty,
+        input_files,
+    } = cli;
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the input file format using --output-format");
+    };
+
+    let input_tfs: Vec<MaybeTransform> = input_files
+        .iter()
+        .map(|path| read_tf_from_path(path, None, repair_rotation))
+        .collect::<Result<_>>()?;
+    let weights = weights.unwrap_or_else(|| vec![1.0; input_tfs.len()]);
+
+    let output_tf: MaybeTransform = if input_tfs.iter().all(|tf| tf.t.is_some()) {
+        let tfs: Vec<Transform> = input_tfs
+            .into_iter()
+            .map(|tf| tf.try_into().unwrap())
+            .collect();
+        let TransformMean {
+            mean,
+            angular_std,
+            translation_std,
+        } = Transform::weighted_mean(&tfs, &weights)?;
+        eprintln!(
+            "angular std-dev: {} deg, translation std-dev: {translation_std}",
+            angular_std.to_degrees()
+        );
+        mean.into()
+    } else if input_tfs.iter().all(|tf| tf.t.is_none()) {
+        let rots: Vec<Rotation> = input_tfs.into_iter().map(|tf| tf.r).collect();
+        let RotationMean { mean, angular_std } = Rotation::weighted_mean(&rots, &weights)?;
+        eprintln!("angular std-dev: {} deg", angular_std.to_degrees());
+        mean.into()
+    } else {
+        bail!("Input files must be either all transforms or all rotations");
+    };
+
+    let mut writer = create_writer(&output)?;
+    write_tf_to_writer(&output_tf, &mut writer, output_format, pretty)?;
+    writer.flush()?;
+
+    Ok(())
+}
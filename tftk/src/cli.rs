@@ -1,11 +1,22 @@
 use std::{ffi::OsString, path::PathBuf};
 
-use clap::{Parser, ValueEnum};
+use clap::{builder::RangedU64ValueParser, Args, Parser, ValueEnum};
+use tf_format::{AxisConvention, EulerAxisOrder, KittiKey};
 
 #[derive(Debug, Clone, Parser)]
 pub enum Cli {
     Convert(Convert),
     Compose(Compose),
+    Interpolate(Interpolate),
+    Average(Average),
+    Diff(Diff),
+    UrdfToSet(UrdfToSet),
+    SetToUrdf(SetToUrdf),
+    RosStatic(RosStatic),
+    ConvertSet(ConvertSet),
+    KittiToSet(KittiToSet),
+    SetToKitti(SetToKitti),
+    NuscenesToSet(NuscenesToSet),
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -16,15 +27,67 @@ pub struct Convert {
     #[clap(short = 't', long)]
     pub output_format: Option<FileFormat>,
 
-    #[clap(short = 'r', long)]
-    pub rotation_format: RotationFormat,
+    #[clap(
+        short = 'r',
+        long,
+        required_unless_present_any = ["matrix_format", "dual_quat"]
+    )]
+    pub rotation_format: Option<RotationFormat>,
+
+    #[clap(short = 'm', long, conflicts_with = "rotation_format")]
+    pub matrix_format: Option<MatrixFormat>,
+
+    /// Write the transform as a unit dual quaternion.
+    #[clap(long, conflicts_with_all = ["rotation_format", "matrix_format"])]
+    pub dual_quat: bool,
+
+    #[clap(flatten)]
+    pub rotation_options: RotationOptions,
 
     #[clap(short = 'a', long, default_value = "deg")]
     pub angle_format: AngleFormat,
 
+    /// Write angles as plain numbers along with an "angle_unit"
+    /// field instead of suffixed strings.
+    #[clap(long)]
+    pub numeric_angles: bool,
+
+    /// The unit of numeric angles in the input file without an
+    /// "angle_unit" field.
+    #[clap(long)]
+    pub input_angle_unit: Option<AngleFormat>,
+
+    /// The axis convention of the input, such as "flu", "rdf", "enu",
+    /// "ned" or "unity".
+    #[clap(long, requires = "to_convention")]
+    pub from_convention: Option<AxisConvention>,
+
+    /// Re-express the transform in the axis convention.
+    #[clap(long, requires = "from_convention")]
+    pub to_convention: Option<AxisConvention>,
+
+    #[clap(flatten)]
+    pub precision_options: PrecisionOptions,
+
     #[clap(short = 'k', long, default_value = "auto")]
     pub keep_translation: KeepTranslation,
 
+    /// Convert the translation to the length unit. The original
+    /// units are kept if not specified.
+    #[clap(short = 'l', long, conflicts_with_all = ["matrix_format", "dual_quat"])]
+    pub length_unit: Option<LengthFormat>,
+
+    /// Project invalid rotation matrices onto the nearest rotation
+    /// instead of reporting an error.
+    #[clap(long)]
+    pub repair_rotation: bool,
+
+    /// Write the expressions of the input, such as "pi/2", back in
+    /// place of the values they evaluate to, where the values are
+    /// unchanged.
+    #[clap(long)]
+    pub keep_expressions: bool,
+
     #[clap(long)]
     pub pretty: bool,
 
@@ -40,15 +103,37 @@ pub struct Compose {
     #[clap(short = 't', long)]
     pub output_format: Option<FileFormat>,
 
-    #[clap(short = 'r', long)]
-    pub rotation_format: RotationFormat,
+    #[clap(
+        short = 'r',
+        long,
+        required_unless_present_any = ["matrix_format", "dual_quat"]
+    )]
+    pub rotation_format: Option<RotationFormat>,
+
+    #[clap(short = 'm', long, conflicts_with = "rotation_format")]
+    pub matrix_format: Option<MatrixFormat>,
+
+    /// Write the transform as a unit dual quaternion.
+    #[clap(long, conflicts_with_all = ["rotation_format", "matrix_format"])]
+    pub dual_quat: bool,
+
+    #[clap(flatten)]
+    pub rotation_options: RotationOptions,
 
     #[clap(short = 'a', long, default_value = "deg")]
     pub angle_format: AngleFormat,
 
+    #[clap(flatten)]
+    pub precision_options: PrecisionOptions,
+
     #[clap(short = 'k', long, default_value = "auto")]
     pub keep_translation: KeepTranslation,
 
+    /// Project invalid rotation matrices onto the nearest rotation
+    /// instead of reporting an error.
+    #[clap(long)]
+    pub repair_rotation: bool,
+
     #[clap(short = 'o', long, default_value = "-")]
     pub output: OsString,
 
@@ -58,6 +143,290 @@ pub struct Compose {
     pub input_files: Vec<PathBuf>,
 }
 
+#[derive(Debug, Clone, Parser)]
+pub struct Interpolate {
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    /// The number of intervals. It writes steps + 1 transforms
+    /// including both ends.
+    #[clap(short = 'n', long, default_value = "10")]
+    pub steps: usize,
+
+    /// Use screw linear interpolation instead of interpolating the
+    /// rotation and translation separately.
+    #[clap(long)]
+    pub screw: bool,
+
+    /// Project invalid rotation matrices onto the nearest rotation
+    /// instead of reporting an error.
+    #[clap(long)]
+    pub repair_rotation: bool,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    pub start: PathBuf,
+
+    pub end: PathBuf,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct Average {
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    /// Comma-separated weights, one for each input file.
+    #[clap(short = 'w', long, value_delimiter = ',')]
+    pub weights: Option<Vec<f64>>,
+
+    /// Project invalid rotation matrices onto the nearest rotation
+    /// instead of reporting an error.
+    #[clap(long)]
+    pub repair_rotation: bool,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    #[clap(required = true)]
+    pub input_files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct Diff {
+    /// The tolerance of the rotation difference in degrees.
+    #[clap(long)]
+    pub tol_deg: Option<f64>,
+
+    /// The tolerance of the translation difference norm.
+    #[clap(long)]
+    pub tol_m: Option<f64>,
+
+    /// Project invalid rotation matrices onto the nearest rotation
+    /// instead of reporting an error.
+    #[clap(long)]
+    pub repair_rotation: bool,
+
+    pub lhs: PathBuf,
+
+    pub rhs: PathBuf,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct UrdfToSet {
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    /// The position of a movable joint as NAME=VALUE in radians or
+    /// meters. Other joints are placed at zero clamped to the limits.
+    #[clap(short = 'j', long = "joint", value_parser = parse_joint_position)]
+    pub joint_positions: Vec<(String, f64)>,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct SetToUrdf {
+    #[clap(short = 'f', long)]
+    pub input_format: Option<FileFormat>,
+
+    /// The frame at the root of the link tree.
+    #[clap(long)]
+    pub root: String,
+
+    #[clap(long, default_value = "robot")]
+    pub robot_name: String,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ConvertSet {
+    #[clap(short = 'f', long)]
+    pub input_format: Option<FileFormat>,
+
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    /// Write the ROS TFMessage layout instead of the transform list.
+    #[clap(long)]
+    pub ros_msg: bool,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct KittiToSet {
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    #[clap(flatten)]
+    pub key_options: KittiKeyOptions,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct SetToKitti {
+    #[clap(short = 'f', long)]
+    pub input_format: Option<FileFormat>,
+
+    #[clap(flatten)]
+    pub key_options: KittiKeyOptions,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct KittiKeyOptions {
+    /// The frames of a KITTI calibration entry as KEY=FROM:TO, where
+    /// the matrix maps points in FROM to TO. It overrides the default
+    /// "R0_rect=cam0:cam0_rect", "Tr_velo_to_cam=velo:cam0" and
+    /// "Tr_imu_to_velo=imu:velo".
+    #[clap(long = "kitti-key")]
+    pub kitti_keys: Vec<KittiKey>,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct NuscenesToSet {
+    #[clap(short = 't', long)]
+    pub output_format: Option<FileFormat>,
+
+    /// Use the calibrations of the scene, given by the name such as
+    /// "scene-0061" or the token.
+    #[clap(long, conflicts_with_all = ["log", "calibrated_sensors"])]
+    pub scene: Option<String>,
+
+    /// Use the calibrations of the log, given by the log file name or
+    /// the token.
+    #[clap(long, conflicts_with = "calibrated_sensors")]
+    pub log: Option<String>,
+
+    /// Use the calibrated sensor token. It's repeated for each
+    /// channel.
+    #[clap(long = "calibrated-sensor")]
+    pub calibrated_sensors: Vec<String>,
+
+    /// Add the transform from "global" to "ego" interpolated from the
+    /// ego poses at the time stamp in microseconds. The calibrations
+    /// of the scene at the time stamp are used unless selected
+    /// otherwise.
+    #[clap(long)]
+    pub timestamp: Option<i64>,
+
+    #[clap(long)]
+    pub pretty: bool,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+
+    /// The table directory of the dataset version, such as
+    /// "v1.0-mini".
+    pub dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RosStatic {
+    #[clap(short = 'f', long)]
+    pub input_format: Option<FileFormat>,
+
+    #[clap(short = 's', long, default_value = "ros2-cli")]
+    pub style: RosStyle,
+
+    /// Write yaw, pitch and roll in radians instead of the
+    /// quaternion.
+    #[clap(long)]
+    pub ypr: bool,
+
+    /// The parent frame of a single transform file. A transform set
+    /// is read if not specified.
+    #[clap(long, requires = "child_frame")]
+    pub parent_frame: Option<String>,
+
+    /// The child frame of a single transform file.
+    #[clap(long, requires = "parent_frame")]
+    pub child_frame: Option<String>,
+
+    #[clap(flatten)]
+    pub precision_options: PrecisionOptions,
+
+    #[clap(short = 'i', long, default_value = "-")]
+    pub input: OsString,
+
+    #[clap(short = 'o', long, default_value = "-")]
+    pub output: OsString,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RotationOptions {
+    #[clap(long, default_value = "rpy")]
+    pub euler_order: EulerAxisOrder,
+
+    #[clap(long, default_value = "extrinsic")]
+    pub euler_convention: EulerConvention,
+
+    #[clap(long, default_value = "xyzw")]
+    pub quat_order: QuatOrder,
+
+    #[clap(long, default_value = "hamilton")]
+    pub quat_convention: QuatConvention,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct PrecisionOptions {
+    /// Round the output numbers to the number of decimal places.
+    #[clap(long)]
+    pub precision: Option<usize>,
+
+    /// Round the output numbers to the number of significant digits.
+    #[clap(
+        long,
+        conflicts_with = "precision",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub significant_digits: Option<usize>,
+
+    /// Snap the output numbers within the distance to 0 or ±1, and
+    /// angles within the distance to multiples of 15 degrees.
+    #[clap(long)]
+    pub snap_eps: Option<f64>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
 #[clap(rename_all = "kebab-case")]
 pub enum FileFormat {
@@ -66,11 +435,39 @@ pub enum FileFormat {
     Yaml,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum RosStyle {
+    /// `rosrun tf2_ros static_transform_publisher` commands.
+    Ros1Cli,
+    /// `ros2 run tf2_ros static_transform_publisher` commands.
+    Ros2Cli,
+    /// A ROS 1 launch XML file.
+    Ros1Launch,
+    /// A ROS 2 Python launch file.
+    Ros2Launch,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
 #[clap(rename_all = "kebab-case")]
 pub enum AngleFormat {
     Deg,
     Rad,
+    Grad,
+    Turn,
+    Arcmin,
+    Arcsec,
+    Dms,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum LengthFormat {
+    M,
+    Cm,
+    Mm,
+    In,
+    Ft,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
@@ -81,6 +478,36 @@ pub enum RotationFormat {
     Mat,
     AxisAngle,
     Rodrigues,
+    Mrp,
+    Gibbs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum MatrixFormat {
+    Homogeneous,
+    Affine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum EulerConvention {
+    Intrinsic,
+    Extrinsic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum QuatOrder {
+    Xyzw,
+    Wxyz,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum QuatConvention {
+    Hamilton,
+    Jpl,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
@@ -90,3 +517,13 @@ pub enum KeepTranslation {
     Always,
     Discard,
 }
+
+fn parse_joint_position(text: &str) -> Result<(String, f64), String> {
+    let Some((name, value)) = text.split_once('=') else {
+        return Err(format!("expect NAME=VALUE, but found '{text}'"));
+    };
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid joint position '{value}'"))?;
+    Ok((name.to_string(), value))
+}
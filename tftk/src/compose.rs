@@ -1,21 +1,25 @@
 use crate::{
     cli::Compose,
     utils::{
-        create_writer, guess_format, keep_or_discard_translation, read_tf_from_path,
-        to_angle_format, to_rotation_format, write_tf_to_writer,
+        guess_format, keep_or_discard_translation, read_tf_from_path, to_angle_format,
+        to_matrix_format, to_rotation_format, to_serde_options, write_tf_to_output,
     },
 };
 use anyhow::{bail, Result};
 use nalgebra as na;
-use std::io::prelude::*;
-use tf_format::{MaybeTransform, Rotation, Translation};
+use tf_format::{DualQuaternion, MaybeTransform, Rotation, Transform, Translation};
 
 pub fn compose(cli: Compose) -> Result<()> {
     let Compose {
         output_format,
         rotation_format,
+        matrix_format,
+        dual_quat,
+        rotation_options,
         angle_format,
+        precision_options,
         keep_translation,
+        repair_rotation,
         pretty,
         output,
         input_files,
@@ -23,29 +27,54 @@ pub fn compose(cli: Compose) -> Result<()> {
     let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
         bail!("Please specify the input file format using --output-format");
     };
+    let serde_options = to_serde_options(&precision_options);
 
-    let (prod, has_trans) = input_files.iter().try_fold(
-        (na::Isometry3::identity(), false),
-        |(prod, has_trans), path| -> Result<_> {
-            let tf = read_tf_from_path(path, None)?;
-            let has_trans = has_trans | tf.t.is_some();
-            let iso: na::Isometry3<f64> = tf.to_na_isometry3();
-            let prod = prod * iso;
-            Ok((prod, has_trans))
-        },
-    )?;
-
-    let rot: Rotation = prod.rotation.into();
-    let trans: Option<Translation> = has_trans.then(|| prod.translation.into());
-
-    let rot = to_rotation_format(rot, rotation_format);
+    // The product has a translation if any file has one, and the
+    // covariances are propagated.
+    let identity = MaybeTransform::from(Rotation::from(na::UnitQuaternion::<f64>::identity()));
+    let prod = input_files
+        .iter()
+        .try_fold(identity, |prod, path| -> Result<_> {
+            let tf = read_tf_from_path(path, None, repair_rotation)?;
+            Ok(&prod * &tf)
+        })?;
+    let MaybeTransform {
+        t: prod_t,
+        cov: prod_cov,
+        ..
+    } = &prod;
+    let prod_iso: na::Isometry3<f64> = prod.to_na_isometry3();
+
+    if let Some(matrix_format) = matrix_format {
+        let output_tf = to_matrix_format(&prod_iso, matrix_format)?;
+        write_tf_to_output(&output_tf, &output, output_format, pretty, serde_options)?;
+        return Ok(());
+    }
+
+    if dual_quat {
+        let output_tf = DualQuaternion::try_from_na_isometry3(&prod_iso)?;
+        write_tf_to_output(&output_tf, &output, output_format, pretty, serde_options)?;
+        return Ok(());
+    }
+
+    let Some(rotation_format) = rotation_format else {
+        bail!("Please specify the rotation format using --rotation-format");
+    };
+
+    let Transform { r: rot, t, .. } = Transform::try_from_na_isometry3(&prod_iso)?;
+    let trans: Option<Translation> = prod_t.is_some().then_some(t);
+
+    let rot = to_rotation_format(rot, rotation_format, &rotation_options)?;
     let rot = to_angle_format(rot, angle_format);
     let trans = keep_or_discard_translation(trans, keep_translation);
-    let output_tf = MaybeTransform { r: rot, t: trans };
+    let cov = prod_cov.clone().filter(|_| trans.is_some());
+    let output_tf = MaybeTransform {
+        r: rot,
+        t: trans,
+        cov,
+    };
 
-    let mut writer = create_writer(&output)?;
-    write_tf_to_writer(&output_tf, &mut writer, output_format, pretty)?;
-    writer.flush()?;
+    write_tf_to_output(&output_tf, &output, output_format, pretty, serde_options)?;
 
     Ok(())
 }
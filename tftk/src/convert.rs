@@ -1,21 +1,32 @@
 use crate::{
     cli::Convert,
     utils::{
-        create_reader, create_writer, guess_format, keep_or_discard_translation,
-        read_tf_from_reader, to_angle_format, to_rotation_format, write_tf_to_writer,
+        create_reader, guess_format, keep_or_discard_translation, read_value_from_reader,
+        tf_from_value, to_angle_format, to_angle_unit, to_length_format, to_matrix_format,
+        to_rotation_format, to_serde_options, write_tf_to_output_with_expressions,
     },
 };
 use anyhow::{bail, Result};
-use std::io::prelude::*;
-use tf_format::{MaybeTransform, Transform};
+use tf_format::{DualQuaternion, MaybeTransform, MaybeTransformSeed, SerdeOptions, Transform};
 
 pub fn convert(opts: Convert) -> Result<()> {
     let Convert {
         input_format,
         output_format,
         rotation_format,
+        matrix_format,
+        dual_quat,
+        rotation_options,
         angle_format,
+        precision_options,
+        numeric_angles,
+        input_angle_unit,
+        from_convention,
+        to_convention,
         keep_translation,
+        length_unit,
+        repair_rotation,
+        keep_expressions,
         pretty,
         input,
         output,
@@ -28,26 +39,85 @@ pub fn convert(opts: Convert) -> Result<()> {
         bail!("Please specify the input file format using --output-format");
     };
 
-    let input_tf: MaybeTransform = {
-        let reader = create_reader(&input)?;
-        read_tf_from_reader(reader, input_format)?
+    let serde_options = SerdeOptions {
+        numeric_angles,
+        ..to_serde_options(&precision_options)
     };
 
-    let MaybeTransform { t: trans, r: rot } = input_tf;
-    let rot = to_rotation_format(rot, rotation_format);
+    let value = read_value_from_reader(create_reader(&input)?, input_format)?;
+    let source = keep_expressions.then(|| value.clone());
+    let seed = MaybeTransformSeed {
+        default_angle_unit: input_angle_unit.map(to_angle_unit),
+        repair_rotation,
+    };
+    let input_tf = tf_from_value(value, seed)?;
+    let input_tf = match (from_convention, to_convention) {
+        (Some(from), Some(to)) => input_tf.change_convention(&from, &to),
+        _ => input_tf,
+    };
+
+    if let Some(matrix_format) = matrix_format {
+        let output_tf = to_matrix_format(&input_tf.to_na_isometry3(), matrix_format)?;
+        write_tf_to_output_with_expressions(
+            &output_tf,
+            source.as_ref(),
+            &output,
+            output_format,
+            pretty,
+            serde_options,
+        )?;
+        return Ok(());
+    }
+
+    if dual_quat {
+        let output_tf = DualQuaternion::try_from_na_isometry3(&input_tf.to_na_isometry3::<f64>())?;
+        write_tf_to_output_with_expressions(
+            &output_tf,
+            source.as_ref(),
+            &output,
+            output_format,
+            pretty,
+            serde_options,
+        )?;
+        return Ok(());
+    }
+
+    let Some(rotation_format) = rotation_format else {
+        bail!("Please specify the rotation format using --rotation-format");
+    };
+
+    let MaybeTransform {
+        t: trans,
+        r: rot,
+        cov,
+    } = input_tf;
+    let rot = to_rotation_format(rot, rotation_format, &rotation_options)?;
     let rot = to_angle_format(rot, angle_format);
     let trans = keep_or_discard_translation(trans, keep_translation);
+    let trans = match length_unit {
+        Some(length_unit) => trans.map(|trans| to_length_format(trans, length_unit)),
+        None => trans,
+    };
 
+    // The covariance is discarded along with the translation.
     let output_tf: MaybeTransform = match trans {
-        Some(trans) => Transform { t: trans, r: rot }.into(),
+        Some(trans) => Transform {
+            t: trans,
+            r: rot,
+            cov,
+        }
+        .into(),
         None => rot.into(),
     };
 
-    {
-        let mut writer = create_writer(&output)?;
-        write_tf_to_writer(&output_tf, &mut writer, output_format, pretty)?;
-        writer.flush()?;
-    }
+    write_tf_to_output_with_expressions(
+        &output_tf,
+        source.as_ref(),
+        &output,
+        output_format,
+        pretty,
+        serde_options,
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,36 @@
+use crate::{
+    cli::ConvertSet,
+    utils::{create_reader, create_writer, guess_format, read_set_from_reader, write_tf_to_writer},
+};
+use anyhow::{bail, Result};
+use std::io::prelude::*;
+use tf_format::TfMessage;
+
+pub fn convert_set(cli: ConvertSet) -> Result<()> {
+    let ConvertSet {
+        input_format,
+        output_format,
+        ros_msg,
+        pretty,
+        input,
+        output,
+    } = cli;
+    let Some(input_format) = input_format.or_else(|| guess_format(&input)) else {
+        bail!("Please specify the input file format using --input-format");
+    };
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the output file format using --output-format");
+    };
+
+    let set = read_set_from_reader(create_reader(&input)?, input_format)?;
+
+    let mut writer = create_writer(&output)?;
+    if ros_msg {
+        write_tf_to_writer(&TfMessage::from(&set), &mut writer, output_format, pretty)?;
+    } else {
+        write_tf_to_writer(&set, &mut writer, output_format, pretty)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
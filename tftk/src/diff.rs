@@ -0,0 +1,86 @@
+use crate::{cli::Diff, utils::read_tf_from_path};
+use anyhow::{bail, ensure, Result};
+use tf_format::{MaybeTransform, RotationDiff, Transform, TransformDiff};
+
+pub fn diff(cli: Diff) -> Result<()> {
+    let Diff {
+        tol_deg,
+        tol_m,
+        repair_rotation,
+        lhs,
+        rhs,
+    } = cli;
+
+    let lhs = read_tf_from_path(&lhs, None, repair_rotation)?;
+    let rhs = read_tf_from_path(&rhs, None, repair_rotation)?;
+
+    let (rotation, trans_norm) = match (lhs, rhs) {
+        (
+            MaybeTransform {
+                r: lhs_r,
+                t: Some(lhs_t),
+                ..
+            },
+            MaybeTransform {
+                r: rhs_r,
+                t: Some(rhs_t),
+                ..
+            },
+        ) => {
+            let lhs = Transform {
+                r: lhs_r,
+                t: lhs_t,
+                cov: None,
+            };
+            let rhs = Transform {
+                r: rhs_r,
+                t: rhs_t,
+                cov: None,
+            };
+            let TransformDiff {
+                rotation,
+                translation: [x, y, z],
+                translation_norm,
+            } = lhs.diff(&rhs);
+            println!("translation: {translation_norm} ([{x}, {y}, {z}])");
+            (rotation, Some(translation_norm))
+        }
+        (
+            MaybeTransform {
+                r: lhs_r, t: None, ..
+            },
+            MaybeTransform {
+                r: rhs_r, t: None, ..
+            },
+        ) => (lhs_r.diff(&rhs_r), None),
+        _ => bail!("Both files must be either transforms or rotations"),
+    };
+
+    let RotationDiff { angle, axis } = rotation;
+
+    // Report an error rather than printing NaN or infinity.
+    ensure!(
+        angle.is_finite() && trans_norm.is_none_or(f64::is_finite),
+        "the difference is not finite"
+    );
+    let angle_deg = angle.to_degrees();
+    match axis {
+        Some([x, y, z]) => println!("rotation: {angle_deg} deg around [{x}, {y}, {z}]"),
+        None => println!("rotation: {angle_deg} deg"),
+    }
+
+    if let Some(tol_deg) = tol_deg {
+        ensure!(
+            angle_deg <= tol_deg,
+            "the rotation difference {angle_deg} deg exceeds the tolerance {tol_deg} deg"
+        );
+    }
+    if let (Some(tol_m), Some(trans_norm)) = (tol_m, trans_norm) {
+        ensure!(
+            trans_norm <= tol_m,
+            "the translation difference {trans_norm} exceeds the tolerance {tol_m}"
+        );
+    }
+
+    Ok(())
+}
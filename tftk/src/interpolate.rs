@@ -0,0 +1,87 @@
+use crate::{
+    cli::Interpolate,
+    utils::{create_writer, guess_format, read_tf_from_path, write_tf_to_writer},
+};
+use anyhow::{bail, Result};
+use std::io::prelude::*;
+use tf_format::{MaybeTransform, Transform};
+
+pub fn interpolate(cli: Interpolate) -> Result<()> {
+    let Interpolate {
+        output_format,
+        steps,
+        screw,
+        repair_rotation,
+        output,
+        pretty,
+        start,
+        end,
+    } = cli;
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the input file format using --output-format");
+    };
+    if steps == 0 {
+        bail!("--steps must be at least 1");
+    }
+
+    let start = read_tf_from_path(&start, None, repair_rotation)?;
+    let end = read_tf_from_path(&end, None, repair_rotation)?;
+
+    let ratios = (0..=steps).map(|step| step as f64 / steps as f64);
+
+    let output_tfs: Vec<MaybeTransform> = match (start, end) {
+        (
+            MaybeTransform {
+                r: start_r,
+                t: Some(start_t),
+                ..
+            },
+            MaybeTransform {
+                r: end_r,
+                t: Some(end_t),
+                ..
+            },
+        ) => {
+            let start = Transform {
+                r: start_r,
+                t: start_t,
+                cov: None,
+            };
+            let end = Transform {
+                r: end_r,
+                t: end_t,
+                cov: None,
+            };
+
+            ratios
+                .map(|ratio| {
+                    let tf = if screw {
+                        start.try_sclerp(&end, ratio)?
+                    } else {
+                        start.try_interpolate(&end, ratio)?
+                    };
+                    Ok(tf.into())
+                })
+                .collect::<Result<_>>()?
+        }
+        (
+            MaybeTransform {
+                r: start_r,
+                t: None,
+                ..
+            },
+            MaybeTransform {
+                r: end_r, t: None, ..
+            },
+        ) => ratios
+            .map(|ratio| Ok(start_r.try_slerp(&end_r, ratio)?.into()))
+            .collect::<Result<_>>()?,
+        _ => bail!("Both files must be either transforms or rotations"),
+    };
+
+    let mut writer = create_writer(&output)?;
+    write_tf_to_writer(&output_tfs, &mut writer, output_format, pretty)?;
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::{
+    cli::{KittiKeyOptions, KittiToSet, SetToKitti},
+    utils::{create_reader, create_writer, guess_format, read_set_from_reader, write_tf_to_writer},
+};
+use anyhow::{bail, Result};
+use std::io::prelude::*;
+use tf_format::{KittiKey, KittiOptions, TransformSet};
+
+pub fn kitti_to_set(cli: KittiToSet) -> Result<()> {
+    let KittiToSet {
+        output_format,
+        key_options,
+        pretty,
+        input,
+        output,
+    } = cli;
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the output file format using --output-format");
+    };
+
+    let text = {
+        let mut reader = create_reader(&input)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text
+    };
+    let set = TransformSet::from_kitti_str(&text, &to_kitti_options(&key_options))?;
+
+    let mut writer = create_writer(&output)?;
+    write_tf_to_writer(&set, &mut writer, output_format, pretty)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn set_to_kitti(cli: SetToKitti) -> Result<()> {
+    let SetToKitti {
+        input_format,
+        key_options,
+        input,
+        output,
+    } = cli;
+    let Some(input_format) = input_format.or_else(|| guess_format(&input)) else {
+        bail!("Please specify the input file format using --input-format");
+    };
+
+    let set = read_set_from_reader(create_reader(&input)?, input_format)?;
+    let text = set.to_kitti_string(&to_kitti_options(&key_options))?;
+
+    let mut writer = create_writer(&output)?;
+    writer.write_all(text.as_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn to_kitti_options(key_options: &KittiKeyOptions) -> KittiOptions {
+    let mut options = KittiOptions::default();
+    for KittiKey { key, from, to, .. } in &key_options.kitti_keys {
+        options.set_frames(key, from, to);
+    }
+    options
+}
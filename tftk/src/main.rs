@@ -1,6 +1,14 @@
+mod average;
 mod cli;
 mod compose;
 mod convert;
+mod convert_set;
+mod diff;
+mod interpolate;
+mod kitti;
+mod nuscenes;
+mod ros;
+mod urdf;
 mod utils;
 
 use anyhow::Result;
@@ -13,6 +21,16 @@ fn main() -> Result<()> {
     match cli {
         Cli::Convert(cli) => crate::convert::convert(cli)?,
         Cli::Compose(cli) => crate::compose::compose(cli)?,
+        Cli::Interpolate(cli) => crate::interpolate::interpolate(cli)?,
+        Cli::Average(cli) => crate::average::average(cli)?,
+        Cli::Diff(cli) => crate::diff::diff(cli)?,
+        Cli::UrdfToSet(cli) => crate::urdf::urdf_to_set(cli)?,
+        Cli::SetToUrdf(cli) => crate::urdf::set_to_urdf(cli)?,
+        Cli::RosStatic(cli) => crate::ros::ros_static(cli)?,
+        Cli::ConvertSet(cli) => crate::convert_set::convert_set(cli)?,
+        Cli::KittiToSet(cli) => crate::kitti::kitti_to_set(cli)?,
+        Cli::SetToKitti(cli) => crate::kitti::set_to_kitti(cli)?,
+        Cli::NuscenesToSet(cli) => crate::nuscenes::nuscenes_to_set(cli)?,
     }
 
     Ok(())
@@ -0,0 +1,52 @@
+use crate::{
+    cli::NuscenesToSet,
+    utils::{create_writer, guess_format, write_tf_to_writer},
+};
+use anyhow::{bail, Context, Result};
+use std::io::prelude::*;
+use tf_format::NuScenes;
+
+pub fn nuscenes_to_set(cli: NuscenesToSet) -> Result<()> {
+    let NuscenesToSet {
+        output_format,
+        scene,
+        log,
+        calibrated_sensors,
+        timestamp,
+        pretty,
+        output,
+        dir,
+    } = cli;
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the output file format using --output-format");
+    };
+
+    let dataset = NuScenes::from_dir(&dir)?;
+    let mut set = if let Some(scene) = &scene {
+        dataset.sensor_set_for_scene(scene)?
+    } else if let Some(log) = &log {
+        dataset.sensor_set_for_log(log)?
+    } else if !calibrated_sensors.is_empty() {
+        dataset.sensor_set_from_tokens(calibrated_sensors.iter().map(String::as_str))?
+    } else if let Some(timestamp) = timestamp {
+        dataset.sensor_set_at(timestamp)?
+    } else {
+        dataset
+            .sensor_set()
+            .context("Please select the calibrations using --scene, --log or --timestamp")?
+    };
+
+    if let Some(timestamp) = timestamp {
+        let tf = dataset
+            .ego_poses
+            .interpolate(timestamp)?
+            .with_context(|| format!("the time stamp {timestamp} is out of the ego pose range"))?;
+        set.insert_transform(NuScenes::GLOBAL_FRAME, NuScenes::EGO_FRAME, tf)?;
+    }
+
+    let mut writer = create_writer(&output)?;
+    write_tf_to_writer(&set, &mut writer, output_format, pretty)?;
+    writer.flush()?;
+
+    Ok(())
+}
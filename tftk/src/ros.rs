@@ -0,0 +1,411 @@
+use crate::{
+    cli::{RosStatic, RosStyle},
+    utils::{
+        create_reader, create_writer, guess_format, read_set_from_reader, read_tf_from_reader,
+        to_serde_options,
+    },
+};
+use anyhow::{bail, Result};
+use nalgebra as na;
+use noisy_float::types::r64;
+use std::{collections::HashSet, fmt::Write as _, io::prelude::*};
+use tf_format::{Angle, CoordTransform, SerdeOptions};
+
+/// A transform from the parent frame to the child frame, as published
+/// by `static_transform_publisher`.
+struct StaticTransform {
+    parent: String,
+    child: String,
+    iso: na::Isometry3<f64>,
+}
+
+impl StaticTransform {
+    /// Returns the named values in the positional order of ROS 1,
+    /// "x y z qx qy qz qw" or "x y z yaw pitch roll".
+    fn values(&self, ypr: bool, options: &SerdeOptions) -> Vec<(&'static str, String)> {
+        let fmt = |value: f64| format!("{}", options.round_number(value));
+        let fmt_angle = |value: f64| {
+            let angle = options.round_angle(Angle::from_radians(r64(value)));
+            format!("{}", angle.value)
+        };
+
+        let [x, y, z]: [f64; 3] = self.iso.translation.vector.into();
+        let mut values = vec![("x", fmt(x)), ("y", fmt(y)), ("z", fmt(z))];
+
+        if ypr {
+            let (roll, pitch, yaw) = self.iso.rotation.euler_angles();
+            values.extend([
+                ("yaw", fmt_angle(yaw)),
+                ("pitch", fmt_angle(pitch)),
+                ("roll", fmt_angle(roll)),
+            ]);
+        } else {
+            let quat = self.iso.rotation.quaternion();
+            values.extend([
+                ("qx", fmt(quat.i)),
+                ("qy", fmt(quat.j)),
+                ("qz", fmt(quat.k)),
+                ("qw", fmt(quat.w)),
+            ]);
+        }
+
+        values
+    }
+}
+
+/// Derive a node name for each transform from the frames. The names
+/// are valid ROS names, which start with a letter and have letters,
+/// digits and underscores only, and they are unique within the file.
+fn node_names(tfs: &[StaticTransform]) -> Vec<String> {
+    let mut used = HashSet::new();
+
+    tfs.iter()
+        .map(|tf| {
+            let base: String = format!("{}_to_{}", tf.parent, tf.child)
+                .chars()
+                .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+                .collect();
+            let base = if base.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+                base
+            } else {
+                format!("tf_{base}")
+            };
+
+            let mut name = base.clone();
+            let mut count = 1;
+            while !used.insert(name.clone()) {
+                count += 1;
+                name = format!("{base}_{count}");
+            }
+            name
+        })
+        .collect()
+}
+
+pub fn ros_static(cli: RosStatic) -> Result<()> {
+    let RosStatic {
+        input_format,
+        style,
+        ypr,
+        parent_frame,
+        child_frame,
+        precision_options,
+        input,
+        output,
+    } = cli;
+    let Some(input_format) = input_format.or_else(|| guess_format(&input)) else {
+        bail!("Please specify the input file format using --input-format");
+    };
+
+    let reader = create_reader(&input)?;
+    let tfs: Vec<StaticTransform> = match (parent_frame, child_frame) {
+        (Some(parent), Some(child)) => {
+            let tf = read_tf_from_reader(reader, input_format, false)?;
+            vec![StaticTransform {
+                parent,
+                child,
+                iso: tf.to_na_isometry3(),
+            }]
+        }
+        _ => {
+            let set = read_set_from_reader(reader, input_format)?;
+            set.transforms()
+                .iter()
+                .map(|trans| {
+                    let CoordTransform { src, dst, tf } = trans;
+                    StaticTransform {
+                        parent: src.clone(),
+                        child: dst.clone(),
+                        iso: tf.clone().into(),
+                    }
+                })
+                .collect()
+        }
+    };
+
+    let text = render(&tfs, style, ypr, &to_serde_options(&precision_options))?;
+
+    let mut writer = create_writer(&output)?;
+    writer.write_all(text.as_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn render(
+    tfs: &[StaticTransform],
+    style: RosStyle,
+    ypr: bool,
+    options: &SerdeOptions,
+) -> Result<String> {
+    match style {
+        RosStyle::Ros1Cli => render_ros1_cli(tfs, ypr, options),
+        RosStyle::Ros2Cli => render_ros2_cli(tfs, ypr, options),
+        RosStyle::Ros1Launch => render_ros1_launch(tfs, ypr, options),
+        RosStyle::Ros2Launch => render_ros2_launch(tfs, ypr, options),
+    }
+}
+
+fn render_ros1_cli(tfs: &[StaticTransform], ypr: bool, options: &SerdeOptions) -> Result<String> {
+    let mut text = String::new();
+    for tf in tfs {
+        let values: Vec<_> = tf
+            .values(ypr, options)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        writeln!(
+            text,
+            "rosrun tf2_ros static_transform_publisher {} {} {}",
+            values.join(" "),
+            shell_quote(&tf.parent),
+            shell_quote(&tf.child)
+        )?;
+    }
+    Ok(text)
+}
+
+fn render_ros2_cli(tfs: &[StaticTransform], ypr: bool, options: &SerdeOptions) -> Result<String> {
+    let mut text = String::new();
+    for tf in tfs {
+        let args: Vec<_> = tf
+            .values(ypr, options)
+            .into_iter()
+            .map(|(name, value)| format!("--{name} {value}"))
+            .collect();
+        writeln!(
+            text,
+            "ros2 run tf2_ros static_transform_publisher {} --frame-id {} --child-frame-id {}",
+            args.join(" "),
+            shell_quote(&tf.parent),
+            shell_quote(&tf.child)
+        )?;
+    }
+    Ok(text)
+}
+
+fn render_ros1_launch(
+    tfs: &[StaticTransform],
+    ypr: bool,
+    options: &SerdeOptions,
+) -> Result<String> {
+    let mut text = String::new();
+    writeln!(text, "<launch>")?;
+    for (tf, name) in tfs.iter().zip(node_names(tfs)) {
+        let values: Vec<_> = tf
+            .values(ypr, options)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        // roslaunch splits the arguments like a shell.
+        let args = format!(
+            "{} {} {}",
+            values.join(" "),
+            shell_quote(&tf.parent),
+            shell_quote(&tf.child)
+        );
+        writeln!(
+            text,
+            r#"  <node pkg="tf2_ros" type="static_transform_publisher" name="{}" args="{}"/>"#,
+            name,
+            xml_escape(&args)
+        )?;
+    }
+    writeln!(text, "</launch>")?;
+    Ok(text)
+}
+
+fn render_ros2_launch(
+    tfs: &[StaticTransform],
+    ypr: bool,
+    options: &SerdeOptions,
+) -> Result<String> {
+    let mut text = String::new();
+    writeln!(text, "from launch import LaunchDescription")?;
+    writeln!(text, "from launch_ros.actions import Node")?;
+    writeln!(text)?;
+    writeln!(text)?;
+    writeln!(text, "def generate_launch_description():")?;
+    writeln!(text, "    return LaunchDescription([")?;
+    for (tf, name) in tfs.iter().zip(node_names(tfs)) {
+        writeln!(text, "        Node(")?;
+        writeln!(text, r#"            package="tf2_ros","#)?;
+        writeln!(
+            text,
+            r#"            executable="static_transform_publisher","#
+        )?;
+        writeln!(text, r#"            name="{name}","#)?;
+        writeln!(text, "            arguments=[")?;
+        for (name, value) in tf.values(ypr, options) {
+            writeln!(text, r#"                "--{name}", "{value}","#)?;
+        }
+        writeln!(
+            text,
+            r#"                "--frame-id", {},"#,
+            python_quote(&tf.parent)
+        )?;
+        writeln!(
+            text,
+            r#"                "--child-frame-id", {},"#,
+            python_quote(&tf.child)
+        )?;
+        writeln!(text, "            ],")?;
+        writeln!(text, "        ),")?;
+    }
+    writeln!(text, "    ])")?;
+    Ok(text)
+}
+
+/// Quote the word for POSIX shells if it has special characters.
+fn shell_quote(word: &str) -> String {
+    let is_plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "_-./:".contains(ch));
+    if is_plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+fn python_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', r"\\").replace('"', "\\\""))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{node_names, render, StaticTransform};
+    use crate::cli::RosStyle;
+    use nalgebra as na;
+    use std::f64::consts::FRAC_PI_2;
+    use tf_format::{Precision, SerdeOptions};
+
+    fn make_tfs() -> Vec<StaticTransform> {
+        vec![
+            StaticTransform {
+                parent: "base_link".to_string(),
+                child: "lidar".to_string(),
+                iso: na::Isometry3::translation(0.5, 0.0, 1.2),
+            },
+            StaticTransform {
+                parent: "base_link".to_string(),
+                child: "front camera".to_string(),
+                iso: na::Isometry3::new(
+                    na::Vector3::new(1.0, -0.25, 0.0),
+                    na::Vector3::new(0.0, 0.0, FRAC_PI_2),
+                ),
+            },
+        ]
+    }
+
+    fn render_style(style: RosStyle, ypr: bool) -> String {
+        let options = SerdeOptions {
+            precision: Some(Precision::Decimals(6)),
+            ..SerdeOptions::default()
+        };
+        render(&make_tfs(), style, ypr, &options).unwrap()
+    }
+
+    #[test]
+    fn ros1_cli_output() {
+        let expect = "\
+rosrun tf2_ros static_transform_publisher 0.5 0 1.2 0 0 0 1 base_link lidar
+rosrun tf2_ros static_transform_publisher 1 -0.25 0 0 0 0.707107 0.707107 base_link 'front camera'
+";
+        assert_eq!(render_style(RosStyle::Ros1Cli, false), expect);
+
+        let expect = "\
+rosrun tf2_ros static_transform_publisher 0.5 0 1.2 0 0 0 base_link lidar
+rosrun tf2_ros static_transform_publisher 1 -0.25 0 1.570796 0 0 base_link 'front camera'
+";
+        assert_eq!(render_style(RosStyle::Ros1Cli, true), expect);
+    }
+
+    #[test]
+    fn ros2_cli_output() {
+        let expect = "\
+ros2 run tf2_ros static_transform_publisher --x 0.5 --y 0 --z 1.2 --qx 0 --qy 0 --qz 0 --qw 1 --frame-id base_link --child-frame-id lidar
+ros2 run tf2_ros static_transform_publisher --x 1 --y -0.25 --z 0 --qx 0 --qy 0 --qz 0.707107 --qw 0.707107 --frame-id base_link --child-frame-id 'front camera'
+";
+        assert_eq!(render_style(RosStyle::Ros2Cli, false), expect);
+    }
+
+    #[test]
+    fn ros1_launch_output() {
+        let expect = r#"<launch>
+  <node pkg="tf2_ros" type="static_transform_publisher" name="base_link_to_lidar" args="0.5 0 1.2 0 0 0 1 base_link lidar"/>
+  <node pkg="tf2_ros" type="static_transform_publisher" name="base_link_to_front_camera" args="1 -0.25 0 0 0 0.707107 0.707107 base_link 'front camera'"/>
+</launch>
+"#;
+        assert_eq!(render_style(RosStyle::Ros1Launch, false), expect);
+    }
+
+    #[test]
+    fn ros2_launch_output() {
+        let expect = r#"from launch import LaunchDescription
+from launch_ros.actions import Node
+
+
+def generate_launch_description():
+    return LaunchDescription([
+        Node(
+            package="tf2_ros",
+            executable="static_transform_publisher",
+            name="base_link_to_lidar",
+            arguments=[
+                "--x", "0.5",
+                "--y", "0",
+                "--z", "1.2",
+                "--qx", "0",
+                "--qy", "0",
+                "--qz", "0",
+                "--qw", "1",
+                "--frame-id", "base_link",
+                "--child-frame-id", "lidar",
+            ],
+        ),
+        Node(
+            package="tf2_ros",
+            executable="static_transform_publisher",
+            name="base_link_to_front_camera",
+            arguments=[
+                "--x", "1",
+                "--y", "-0.25",
+                "--z", "0",
+                "--qx", "0",
+                "--qy", "0",
+                "--qz", "0.707107",
+                "--qw", "0.707107",
+                "--frame-id", "base_link",
+                "--child-frame-id", "front camera",
+            ],
+        ),
+    ])
+"#;
+        assert_eq!(render_style(RosStyle::Ros2Launch, false), expect);
+    }
+
+    #[test]
+    fn unique_node_names() {
+        let tfs: Vec<_> = [("a-b", "c"), ("a_b", "c"), ("a.b", "c"), ("2d", "map")]
+            .into_iter()
+            .map(|(parent, child)| StaticTransform {
+                parent: parent.to_string(),
+                child: child.to_string(),
+                iso: na::Isometry3::identity(),
+            })
+            .collect();
+        assert_eq!(
+            node_names(&tfs),
+            ["a_b_to_c", "a_b_to_c_2", "a_b_to_c_3", "tf_2d_to_map"]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{
+    cli::{SetToUrdf, UrdfToSet},
+    utils::{create_reader, create_writer, guess_format, read_set_from_reader, write_tf_to_writer},
+};
+use anyhow::{bail, Result};
+use std::{collections::HashMap, io::prelude::*};
+use tf_format::TransformSet;
+
+pub fn urdf_to_set(cli: UrdfToSet) -> Result<()> {
+    let UrdfToSet {
+        output_format,
+        joint_positions,
+        pretty,
+        input,
+        output,
+    } = cli;
+    let Some(output_format) = output_format.or_else(|| guess_format(&output)) else {
+        bail!("Please specify the output file format using --output-format");
+    };
+
+    let text = {
+        let mut reader = create_reader(&input)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text
+    };
+    let joint_positions: HashMap<_, _> = joint_positions.into_iter().collect();
+    let set = TransformSet::from_urdf_str(&text, &joint_positions)?;
+
+    let mut writer = create_writer(&output)?;
+    write_tf_to_writer(&set, &mut writer, output_format, pretty)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn set_to_urdf(cli: SetToUrdf) -> Result<()> {
+    let SetToUrdf {
+        input_format,
+        root,
+        robot_name,
+        input,
+        output,
+    } = cli;
+    let Some(input_format) = input_format.or_else(|| guess_format(&input)) else {
+        bail!("Please specify the input file format using --input-format");
+    };
+
+    let set = read_set_from_reader(create_reader(&input)?, input_format)?;
+    let text = set.to_urdf_string(&root, &robot_name)?;
+
+    let mut writer = create_writer(&output)?;
+    writer.write_all(text.as_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
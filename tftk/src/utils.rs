@@ -1,18 +1,29 @@
-use crate::cli::{AngleFormat, FileFormat, KeepTranslation, RotationFormat};
+use crate::cli::{
+    AngleFormat, EulerConvention, FileFormat, KeepTranslation, LengthFormat, MatrixFormat,
+    PrecisionOptions, QuatConvention, QuatOrder, RotationFormat, RotationOptions,
+};
 use anyhow::{bail, Result};
-use noisy_float::types::R64;
-use num::Zero;
+use nalgebra as na;
+use serde::{de::DeserializeSeed, Serialize};
 use std::{
     ffi::OsStr,
     fs::File,
     io::{self, prelude::*, BufReader, BufWriter},
+    num::NonZeroUsize,
     path::Path,
 };
 use tf_format::{
-    AxisAngle, Euler, MaybeTransform, Quaternion, Rodrigues, Rotation, RotationMatrix, Translation,
+    restore_expressions, with_serde_options, AffineMatrix, AngleUnit, AxisAngle, HomogeneousMatrix,
+    LengthUnit, MaybeTransform, MaybeTransformSeed, Mrp, Precision, QuaternionConvention,
+    QuaternionOrder, Rodrigues, Rotation, RotationMatrix, RotationMatrixRepair, SerdeOptions,
+    TransformMatrix, TransformSet, Translation,
 };
 
-pub fn read_tf_from_path(path: &Path, format: Option<FileFormat>) -> Result<MaybeTransform> {
+pub fn read_tf_from_path(
+    path: &Path,
+    format: Option<FileFormat>,
+    repair_rotation: bool,
+) -> Result<MaybeTransform> {
     let Some(format) = format.or_else(|| guess_format(path.as_os_str())) else {
         bail!(
             "unable to determine the file format for path '{}'",
@@ -20,17 +31,34 @@ pub fn read_tf_from_path(path: &Path, format: Option<FileFormat>) -> Result<Mayb
         );
     };
     let reader = BufReader::new(File::open(path)?);
-    read_tf_from_reader(reader, format)
+    read_tf_from_reader(reader, format, repair_rotation)
 }
 
-pub fn read_tf_from_reader(mut reader: impl Read, format: FileFormat) -> Result<MaybeTransform> {
+pub fn read_tf_from_reader(
+    reader: impl Read,
+    format: FileFormat,
+    repair_rotation: bool,
+) -> Result<MaybeTransform> {
     // let Some(format) = guess_format(path.as_os_str()) else {
     //     bail!("unable to determine the file format for path '{}'", path.display());
     // };
 
     // let reader = BufReader::new(File::open(path)?);
 
-    let tf: MaybeTransform = match format {
+    let value = read_value_from_reader(reader, format)?;
+    let seed = MaybeTransformSeed {
+        repair_rotation,
+        ..Default::default()
+    };
+    tf_from_value(value, seed)
+}
+
+/// Read the document without interpreting it.
+pub fn read_value_from_reader(
+    mut reader: impl Read,
+    format: FileFormat,
+) -> Result<serde_json::Value> {
+    let value = match format {
         FileFormat::Json => serde_json::from_reader(reader)?,
         FileFormat::Json5 => {
             let mut text = String::new();
@@ -39,9 +67,42 @@ pub fn read_tf_from_reader(mut reader: impl Read, format: FileFormat) -> Result<
         }
         FileFormat::Yaml => serde_yaml::from_reader(reader)?,
     };
+    Ok(value)
+}
+
+/// Interpret the document as a transform. Invalid rotation matrices
+/// are rejected unless the repair is requested by the seed.
+pub fn tf_from_value(value: serde_json::Value, seed: MaybeTransformSeed) -> Result<MaybeTransform> {
+    let (tf, report) = seed.deserialize(value)?;
+    if let Some(report) = report {
+        let RotationMatrixRepair {
+            orthogonality_error,
+            determinant,
+            distance,
+        } = report;
+        eprintln!(
+            "repaired the rotation matrix: orthogonality error {orthogonality_error:e}, \
+             determinant {determinant}, distance to the repaired matrix {distance:e}"
+        );
+    }
     Ok(tf)
 }
 
+pub fn read_set_from_reader(mut reader: impl Read, format: FileFormat) -> Result<TransformSet> {
+    let set: TransformSet = match format {
+        FileFormat::Json => serde_json::from_reader(reader)?,
+        FileFormat::Json5 => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            json5::from_str(&text)?
+        }
+        FileFormat::Yaml => {
+            TransformSet::from_documents(serde_yaml::Deserializer::from_reader(reader))?
+        }
+    };
+    Ok(set)
+}
+
 // pub fn read_tf_from_str(text: &str, format: FileFormat) -> Result<MaybeTransform> {
 //     let tf: MaybeTransform = match format {
 //         FileFormat::Json => serde_json::from_str(text)?,
@@ -69,7 +130,7 @@ pub fn read_tf_from_reader(mut reader: impl Read, format: FileFormat) -> Result<
 // }
 
 pub fn write_tf_to_writer(
-    tf: &MaybeTransform,
+    tf: &impl Serialize,
     mut writer: impl Write,
     format: FileFormat,
     pretty: bool,
@@ -86,6 +147,41 @@ pub fn write_tf_to_writer(
     Ok(())
 }
 
+/// Write the transform with the serde options to the output file, or
+/// stdout if it's "-".
+pub fn write_tf_to_output(
+    tf: &impl Serialize,
+    output: &OsStr,
+    format: FileFormat,
+    pretty: bool,
+    serde_options: SerdeOptions,
+) -> Result<()> {
+    let mut writer = create_writer(output)?;
+    with_serde_options(serde_options, || {
+        write_tf_to_writer(tf, &mut writer, format, pretty)
+    })?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Like [write_tf_to_output], but the expressions in the `source`
+/// document, if any, are written back where the values are unchanged.
+pub fn write_tf_to_output_with_expressions(
+    tf: &impl Serialize,
+    source: Option<&serde_json::Value>,
+    output: &OsStr,
+    format: FileFormat,
+    pretty: bool,
+    serde_options: SerdeOptions,
+) -> Result<()> {
+    let Some(source) = source else {
+        return write_tf_to_output(tf, output, format, pretty, serde_options);
+    };
+    let mut value = with_serde_options(serde_options, || serde_json::to_value(tf))?;
+    restore_expressions(&mut value, source);
+    write_tf_to_output(&value, output, format, pretty, serde_options)
+}
+
 // pub fn tf_to_string(tf: &MaybeTransform, format: FileFormat) -> Result<String> {
 //     let text = match format {
 //         FileFormat::Json => serde_json::to_string(tf)?,
@@ -121,9 +217,7 @@ pub fn guess_format(spec: &OsStr) -> Option<FileFormat> {
     }
 
     let path = Path::new(spec);
-    let Some(ext) = path.extension() else {
-        return None;
-    };
+    let ext = path.extension()?;
 
     let format = if ext == "json" {
         FileFormat::Json
@@ -139,32 +233,115 @@ pub fn guess_format(spec: &OsStr) -> Option<FileFormat> {
 }
 
 pub fn to_angle_format(rot: Rotation, angle_format: AngleFormat) -> Rotation {
+    rot.into_angle_unit(to_angle_unit(angle_format))
+}
+
+pub fn to_angle_unit(angle_format: AngleFormat) -> AngleUnit {
     match angle_format {
-        AngleFormat::Deg => rot.into_degrees(),
-        AngleFormat::Rad => rot.into_radians(),
+        AngleFormat::Deg => AngleUnit::Degree,
+        AngleFormat::Rad => AngleUnit::Radian,
+        AngleFormat::Grad => AngleUnit::Gradian,
+        AngleFormat::Turn => AngleUnit::Turn,
+        AngleFormat::Arcmin => AngleUnit::ArcMinute,
+        AngleFormat::Arcsec => AngleUnit::ArcSecond,
+        AngleFormat::Dms => AngleUnit::Dms,
     }
 }
 
-pub fn to_rotation_format(rot: Rotation, rotation_format: RotationFormat) -> Rotation {
-    match rotation_format {
-        RotationFormat::Quat => Quaternion::from(rot).into(),
-        RotationFormat::Euler => Euler::from(rot).into(),
+pub fn to_length_format(trans: Translation, length_format: LengthFormat) -> Translation {
+    let unit = match length_format {
+        LengthFormat::M => LengthUnit::Meter,
+        LengthFormat::Cm => LengthUnit::Centimeter,
+        LengthFormat::Mm => LengthUnit::Millimeter,
+        LengthFormat::In => LengthUnit::Inch,
+        LengthFormat::Ft => LengthUnit::Foot,
+    };
+    trans.to_unit(unit)
+}
+
+pub fn to_rotation_format(
+    rot: Rotation,
+    rotation_format: RotationFormat,
+    options: &RotationOptions,
+) -> Result<Rotation> {
+    let RotationOptions {
+        euler_order,
+        euler_convention,
+        quat_order,
+        quat_convention,
+    } = options;
+
+    // Every format is converted through the quaternion, which is
+    // checked here instead of panicking on NaN or infinity.
+    let quat: na::UnitQuaternion<f64> = rot.into();
+    let rot = Rotation::try_from_na_unit_quaternion(&quat)?;
+
+    let rot = match rotation_format {
+        RotationFormat::Quat => {
+            let order = match quat_order {
+                QuatOrder::Xyzw => QuaternionOrder::Xyzw,
+                QuatOrder::Wxyz => QuaternionOrder::Wxyz,
+            };
+            let convention = match quat_convention {
+                QuatConvention::Hamilton => QuaternionConvention::Hamilton,
+                QuatConvention::Jpl => QuaternionConvention::Jpl,
+            };
+            rot.into_quaternion_format_with(order, convention)
+        }
+        RotationFormat::Euler => {
+            let convention = match euler_convention {
+                EulerConvention::Intrinsic => tf_format::EulerConvention::Intrinsic,
+                EulerConvention::Extrinsic => tf_format::EulerConvention::Extrinsic,
+            };
+            rot.into_euler_format_with(euler_order.clone(), convention)?
+        }
         RotationFormat::Mat => RotationMatrix::from(rot).into(),
         RotationFormat::AxisAngle => AxisAngle::from(rot).into(),
         RotationFormat::Rodrigues => Rodrigues::from(rot).into(),
+        RotationFormat::Mrp => Mrp::from(rot).into(),
+        RotationFormat::Gibbs => rot.into_gibbs_format()?,
+    };
+    Ok(rot)
+}
+
+pub fn to_serde_options(options: &PrecisionOptions) -> SerdeOptions {
+    let PrecisionOptions {
+        precision,
+        significant_digits,
+        snap_eps,
+    } = *options;
+
+    let precision = match (precision, significant_digits) {
+        (Some(digits), _) => Some(Precision::Decimals(digits)),
+        (None, Some(digits)) => NonZeroUsize::new(digits).map(Precision::Significant),
+        (None, None) => None,
+    };
+
+    SerdeOptions {
+        precision,
+        snap_eps,
+        ..SerdeOptions::default()
     }
 }
 
+pub fn to_matrix_format(
+    iso: &na::Isometry3<f64>,
+    matrix_format: MatrixFormat,
+) -> Result<TransformMatrix> {
+    let mat = match matrix_format {
+        MatrixFormat::Homogeneous => HomogeneousMatrix::try_from_na_isometry3(iso)?.into(),
+        MatrixFormat::Affine => AffineMatrix::try_from_na_isometry3(iso)?.into(),
+    };
+    Ok(mat)
+}
+
 pub fn keep_or_discard_translation(
     trans: Option<Translation>,
     keep: KeepTranslation,
 ) -> Option<Translation> {
     match keep {
         KeepTranslation::Auto => trans,
-        KeepTranslation::Always => {
-            let z = R64::zero();
-            Some(trans.unwrap_or(Translation([z; 3])))
-        }
+        KeepTranslation::Always => Some(trans.unwrap_or_else(Translation::zero)),
         KeepTranslation::Discard => None,
     }
 }